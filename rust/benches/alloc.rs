@@ -0,0 +1,59 @@
+//! Heap allocations made by the simulation hot path, counted with a wrapping global allocator.
+//! Run with `cargo bench --bench alloc`; every simulated hand should stay on the stack, on an infinite deck
+//! and on a finite shoe settling stands from its preallocated cache.
+
+use blackjack_solver::deck::{Deck, FiniteShoe};
+use blackjack_solver::engine::{generate_all_states, Action, BlackjackEngine};
+use blackjack_solver::rules::RuleSet;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const BATCH_SIZE: u32 = 2_000;
+
+/// Simulate every state's valid actions on `engine`, counting the allocations made
+fn count_allocations<D: Deck>(name: &str, engine: &mut BlackjackEngine<D>, rules: &RuleSet) {
+    let states = generate_all_states();
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let mut hands = 0u64;
+    for state in &states {
+        for action in Action::valid_actions(state, rules) {
+            engine.simulate_batch(state, action, BATCH_SIZE);
+            hands += BATCH_SIZE as u64;
+        }
+    }
+    let secs = start.elapsed().as_secs_f64();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    println!("{}: {} hands in {:.3}s", name, hands, secs);
+    println!("  allocations:      {}", allocations);
+    println!("  allocations/sec:  {:.1}", allocations as f64 / secs);
+    println!("  allocations/hand: {:.6}", allocations as f64 / hands as f64);
+}
+
+fn main() {
+    let rules = RuleSet::default();
+    count_allocations("infinite deck", &mut BlackjackEngine::new(), &rules);
+
+    let mut shoe = BlackjackEngine::with_rules(FiniteShoe::new(8), rules.clone()).with_exact_shoe_stands();
+    count_allocations("8-deck shoe, exact stands", &mut shoe, &rules);
+}
@@ -0,0 +1,55 @@
+//! Variance reduction of antithetic pairs against independent hands for the same budget,
+//! measured across repeated estimates and as the SEM the engine reports. Run with `cargo bench --bench antithetic`.
+
+use blackjack_solver::deck::{AntitheticDeck, Deck, InfiniteDeck, PlayerState};
+use blackjack_solver::engine::{Action, BlackjackEngine};
+use blackjack_solver::rules::RuleSet;
+use std::time::Instant;
+
+/// Hands per estimate (an even number, so the antithetic run ends on a full pair)
+const HANDS: u32 = 1_000;
+
+/// Repeated estimates used to measure the variance of the mean
+const ESTIMATES: u32 = 2_000;
+
+/// Variance across `ESTIMATES` of the mean result over `HANDS` hands
+fn variance_of_mean<D: Deck>(engine: &mut BlackjackEngine<D>, state: &PlayerState, action: Action) -> f64 {
+    let means: Vec<f64> = (0..ESTIMATES).map(|_| engine.simulate_batch(state, action, HANDS).ev()).collect();
+    let mean = means.iter().sum::<f64>() / means.len() as f64;
+    means.iter().map(|m| (m - mean) * (m - mean)).sum::<f64>() / (means.len() - 1) as f64
+}
+
+fn main() {
+    let rules = RuleSet { num_decks: None, ..RuleSet::default() };
+    let cases = [
+        (PlayerState::new(16, 10, false, false), Action::Hit),
+        (PlayerState::new(16, 10, false, false), Action::Stand),
+        (PlayerState::new(12, 4, false, false), Action::Stand),
+        (PlayerState::new(11, 6, false, false), Action::Double),
+        (PlayerState::new(18, 9, true, false), Action::Hit),
+        (PlayerState::new(16, 7, false, true), Action::Split),
+    ];
+
+    println!("{} estimates of {} hands each", ESTIMATES, HANDS);
+    println!(
+        "  {:<26} {:>12} {:>12} {:>10} {:>10} {:>10}",
+        "State / action", "Plain var", "Anti var", "Reduction", "Plain SEM", "Anti SEM"
+    );
+
+    let start = Instant::now();
+    for (state, action) in cases {
+        let mut plain = BlackjackEngine::with_rules(InfiniteDeck::new(), rules.clone());
+        let mut antithetic = BlackjackEngine::with_rules(AntitheticDeck::new(), rules.clone());
+        let plain_var = variance_of_mean(&mut plain, &state, action);
+        let anti_var = variance_of_mean(&mut antithetic, &state, action);
+        // Reported SEM of the same number of hands; the antithetic batch records each pair once
+        let plain_sem = plain.simulate_batch(&state, action, HANDS * ESTIMATES).sem();
+        let anti_sem = antithetic.simulate_batch(&state, action, HANDS * ESTIMATES).sem();
+        println!(
+            "  {:<26} {:>12.3e} {:>12.3e} {:>9.2}x {:>10.5} {:>10.5}",
+            format!("{} vs {} {:?}", state.total, state.dealer_upcard, action),
+            plain_var, anti_var, plain_var / anti_var, plain_sem, anti_sem
+        );
+    }
+    println!("  ({:.1}s)", start.elapsed().as_secs_f64());
+}
@@ -0,0 +1,27 @@
+//! Card draws per second: per-card RNG lookup vs the buffered infinite deck, and the finite shoe.
+//! Run with `cargo bench --bench draw`.
+
+use blackjack_solver::deck::{Deck, FiniteShoe, InfiniteDeck};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use fastrand::Rng;
+
+// Same table the deck uses, for the unbuffered baseline
+const CARD_LOOKUP: [u8; 13] = [2, 3, 4, 5, 6, 7, 8, 9, 10, 10, 10, 10, 11];
+
+fn draws(c: &mut Criterion) {
+    let mut group = c.benchmark_group("draw");
+    group.throughput(Throughput::Elements(1));
+
+    let mut rng = Rng::new();
+    group.bench_function("per-card lookup", |b| b.iter(|| CARD_LOOKUP[rng.usize(0..13)]));
+
+    let mut deck = InfiniteDeck::new();
+    group.bench_function("buffered InfiniteDeck", |b| b.iter(|| deck.draw()));
+
+    let mut shoe = FiniteShoe::new(8);
+    group.bench_function("FiniteShoe (8 decks)", |b| b.iter(|| shoe.draw()));
+    group.finish();
+}
+
+criterion_group!(benches, draws);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+//! Per-operation timings for the simulation hot path: card draws, hand valuation,
+//! dealer play, each action, and hands per second for a full batch.
+//! Run with `cargo bench --bench engine`; criterion reports each change against the previous run.
+
+use blackjack_solver::deck::{get_hand_for_state, Deck, Hand, InfiniteDeck, PlayerState};
+use blackjack_solver::engine::{Action, BlackjackEngine};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::hint::black_box;
+
+/// Hands in each timed `simulate_batch`
+const BATCH_SIZE: u32 = 200_000;
+
+fn hot_path(c: &mut Criterion) {
+    let mut deck = InfiniteDeck::new();
+    c.bench_function("InfiniteDeck::draw", |b| b.iter(|| deck.draw()));
+
+    let hands: Vec<Hand> = (0..1024)
+        .map(|_| {
+            let mut hand = Hand::from_cards(deck.draw(), deck.draw());
+            hand.push(deck.draw());
+            hand
+        })
+        .collect();
+    let mut i = 0;
+    c.bench_function("Hand::value (3 cards)", |b| {
+        b.iter(|| {
+            i = (i + 1) & 1023;
+            black_box(&hands[i]).value().total
+        })
+    });
+
+    let mut engine = BlackjackEngine::new();
+    c.bench_function("dealer_play", |b| {
+        b.iter(|| {
+            let mut dealer = Hand::from_cards(black_box(6), deck.draw());
+            engine.dealer_play(&mut dealer);
+            dealer.len()
+        })
+    });
+
+    let hard_16 = get_hand_for_state(16, false, false);
+    let pair_8s = get_hand_for_state(16, false, true);
+    for action in Action::ALL {
+        let hand = if action == Action::Split { &pair_8s } else { &hard_16 };
+        c.bench_function(&format!("simulate_action {:?}", action), |b| {
+            b.iter(|| engine.simulate_action(hand, black_box(10), action))
+        });
+    }
+
+    let state = PlayerState::new(16, 10, false, false);
+    let mut group = c.benchmark_group("simulate_batch");
+    group.throughput(Throughput::Elements(BATCH_SIZE as u64)).sample_size(10);
+    for action in [Action::Hit, Action::Stand] {
+        group.bench_function(format!("{:?}", action), |b| b.iter(|| engine.simulate_batch(&state, action, BATCH_SIZE)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, hot_path);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+//! Wall-clock benchmark of the full solve, and of per-task vs per-worker engine setup.
+//! Run with `cargo bench --bench solve`.
+
+use blackjack_solver::deck::InfiniteDeck;
+use blackjack_solver::engine::{generate_all_states, Action, BlackjackEngine};
+use blackjack_solver::progress::Progress;
+use blackjack_solver::rules::RuleSet;
+use blackjack_solver::solver::{solve, BATCH_SIZE};
+use criterion::{criterion_group, criterion_main, Criterion};
+use rayon::prelude::*;
+use std::time::Duration;
+
+fn engine_setup(c: &mut Criterion) {
+    let states = generate_all_states();
+    let rules = RuleSet::default();
+    let tasks: Vec<_> = states
+        .iter()
+        .flat_map(|state| Action::valid_actions(state, &rules).map(move |action| (*state, action)))
+        .collect();
+
+    // Small batches make engine setup a visible share of each task
+    let batch_size = BATCH_SIZE / 100;
+
+    let mut group = c.benchmark_group(format!("round of {} tasks x {} hands", tasks.len(), batch_size));
+    group.sample_size(10);
+    group.bench_function("engine per task", |b| {
+        b.iter(|| {
+            tasks
+                .par_iter()
+                .map(|(state, action)| BlackjackEngine::new().simulate_batch(state, *action, batch_size))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("engine per worker", |b| {
+        b.iter(|| {
+            tasks
+                .par_iter()
+                .map_init(BlackjackEngine::new, |engine, (state, action)| {
+                    engine.simulate_batch(state, *action, batch_size)
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+fn full_solve(c: &mut Criterion) {
+    let states = generate_all_states();
+    let rules = RuleSet::default();
+    // Each solve takes seconds, so take the fewest samples criterion allows
+    let mut group = c.benchmark_group("solve");
+    group.sample_size(10).measurement_time(Duration::from_secs(60));
+    group.bench_function(format!("full solve ({} states)", states.len()), |b| {
+        b.iter(|| solve(&states, &rules, InfiniteDeck::new, Progress::Quiet))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, engine_setup, full_solve);
+criterion_main!(benches);
@@ -0,0 +1,18 @@
+"""The bindings as Python calls them: `maturin develop` in rust/python, then `pytest rust/python/tests`."""
+
+import blackjack_solver_rs as bj
+
+INFINITE_DECK = {"num_decks": None}
+
+
+def test_solve_stands_on_hard_20_vs_6():
+    table = bj.solve(INFINITE_DECK, target_sem=0.01)
+    evs = table["Hard 20 vs 6"]
+    assert max(evs, key=evs.get) == "Stand"
+    assert 0.6 < evs["Stand"] < 0.8
+
+
+def test_strategy_doubles_hard_11_vs_6():
+    strategy = bj.Strategy(INFINITE_DECK, target_sem=0.01)
+    assert strategy.best_action(11, 6) == "Double"
+    assert set(strategy.evs(11, 6)) >= {"Hit", "Stand", "Double"}
@@ -0,0 +1,352 @@
+//! Whole-round figures derived from the solved table.
+//! Weights every starting hand by how often it is dealt from a given card distribution.
+
+use crate::deck::{FiniteShoe, Hand, InfiniteDeck, PlayerState};
+use crate::engine::{generate_all_states, Action, BlackjackEngine, OutcomeCounts, SplitHandCounts};
+use crate::exact::action_evs;
+use crate::progress::Progress;
+use crate::rules::RuleSet;
+use crate::solver::{get_best_action, solve_with_rules, ActionTable, StateStats};
+use crate::strategy::StrategyTable;
+use rayon::prelude::*;
+
+/// Probability of drawing a card value (2-11) from an infinite deck
+#[inline(always)]
+pub fn card_probability(card: u8) -> f64 {
+    if card == 10 { 4.0 / 13.0 } else { 1.0 / 13.0 }
+}
+
+/// Infinite-deck draw probabilities indexed by value - 2
+pub fn infinite_deck_probabilities() -> [f64; 10] {
+    std::array::from_fn(|idx| card_probability(idx as u8 + 2))
+}
+
+/// Per-round result of playing the solved strategy, in units of the initial bet
+#[derive(Debug, Clone, Copy)]
+pub struct RoundStats {
+    pub ev: f64,
+    pub std_dev: f64,
+}
+
+/// N0: rounds until the expected win equals one standard deviation of the result,
+/// `(std_dev / ev)^2`. `None` without a positive edge, which never overcomes the variance.
+pub fn n0(ev: f64, std_dev: f64) -> Option<f64> {
+    (ev > 0.0).then(|| (std_dev / ev).powi(2))
+}
+
+impl RoundStats {
+    /// `n0` of the per-round result
+    pub fn n0(&self) -> Option<f64> {
+        n0(self.ev, self.std_dev)
+    }
+
+    /// Expected win and standard deviation over an hour of `rounds_per_hour` rounds at `bet` per round
+    pub fn per_hour(&self, bet: f64, rounds_per_hour: f64) -> RoundStats {
+        RoundStats { ev: self.ev * bet * rounds_per_hour, std_dev: self.std_dev * bet * rounds_per_hour.sqrt() }
+    }
+}
+
+/// Card distribution dealt from `rules`: a fresh shoe's composition and size,
+/// or the infinite-deck probabilities with no size
+pub fn rules_composition(rules: &RuleSet) -> ([f64; 10], Option<f64>) {
+    match rules.num_decks {
+        Some(num_decks) => {
+            let shoe = FiniteShoe::with_deck_type(num_decks, rules.deck_type);
+            (shoe.probabilities(), Some(shoe.remaining() as f64))
+        }
+        None => (rules.deck_type.probabilities(), None),
+    }
+}
+
+/// Expected value and standard deviation of a full round dealt from `card_probs`
+/// (indexed by value - 2). With `shoe_cards` set, the round is dealt without replacement
+/// from a shoe of that size, so e.g. a ten in hand makes a dealer natural less likely.
+/// Naturals are settled exactly; every other starting hand uses its best action's
+/// sampled moments, so the standard deviation needs a solve without exact stands
+/// (`SolverConfig::exact_stands`) or antithetic pairs.
+pub fn round_stats(rules: &RuleSet, strategy: &StateStats, card_probs: &[f64; 10], shoe_cards: Option<f64>) -> RoundStats {
+    weighted_round(rules, strategy, card_probs, shoe_cards, |_, actions| get_best_action(actions).map(|(best, _)| best))
+}
+
+/// `round_stats` with the action of each starting hand chosen by `played`. Hands it has no action for
+/// are left out, like states missing from `strategy`.
+fn weighted_round(
+    rules: &RuleSet,
+    strategy: &StateStats,
+    card_probs: &[f64; 10],
+    shoe_cards: Option<f64>,
+    played: impl Fn(&PlayerState, &ActionTable) -> Option<Action>,
+) -> RoundStats {
+    deal_round(rules, card_probs, shoe_cards, |state| {
+        let actions = strategy.get(state)?;
+        let stats = &actions[played(state, actions)?.index()];
+        Some((stats.ev(), stats.sum_x_squared / stats.n as f64))
+    })
+}
+
+/// Exact expected win per round dealt from `card_probs` (with `shoe_cards` as in `round_stats`), each
+/// starting hand played on its best `exact::action_evs`. Those draw from `card_probs` with replacement
+/// after the deal, so this is the EV of one composition rather than of a whole shoe played down.
+pub fn exact_round_ev(rules: &RuleSet, card_probs: &[f64; 10], shoe_cards: Option<f64>) -> f64 {
+    let settle = |state: &PlayerState| {
+        let best = action_evs(rules, state, card_probs).into_iter().flatten().max_by(f64::total_cmp)?;
+        Some((best, best * best))
+    };
+    deal_round(rules, card_probs, shoe_cards, settle).ev
+}
+
+/// `exact_round_ev` with each starting hand played on the action that is best from `strategy_probs`
+/// instead of from `card_probs`, for a composition played on another's strategy
+pub fn fixed_strategy_round_ev(
+    rules: &RuleSet,
+    card_probs: &[f64; 10],
+    shoe_cards: Option<f64>,
+    strategy_probs: &[f64; 10],
+) -> f64 {
+    let settle = |state: &PlayerState| {
+        let planned = action_evs(rules, state, strategy_probs);
+        let (best, _) =
+            planned.iter().enumerate().filter_map(|(idx, ev)| Some((idx, (*ev)?))).max_by(|a, b| a.1.total_cmp(&b.1))?;
+        let ev = action_evs(rules, state, card_probs)[best]?;
+        Some((ev, ev * ev))
+    };
+    deal_round(rules, card_probs, shoe_cards, settle).ev
+}
+
+/// Mean and standard deviation of a round dealt from `card_probs`, naturals settled exactly and every
+/// other starting hand by `settle`'s mean and second moment (`None` leaves the hand out)
+fn deal_round(
+    rules: &RuleSet,
+    card_probs: &[f64; 10],
+    shoe_cards: Option<f64>,
+    settle: impl Fn(&PlayerState) -> Option<(f64, f64)>,
+) -> RoundStats {
+    // Chance of drawing `card` after the cards in `dealt` have left the shoe
+    let prob = |card: u8, dealt: &[u8]| match shoe_cards {
+        Some(cards) => {
+            let seen = dealt.iter().filter(|&&c| c == card).count() as f64;
+            let left = card_probs[(card - 2) as usize] * cards - seen;
+            (left / (cards - dealt.len() as f64)).max(0.0)
+        }
+        None => card_probs[(card - 2) as usize],
+    };
+    let mut mean = 0.0;
+    let mut second_moment = 0.0;
+
+    for first in 2..=11u8 {
+        for second in 2..=11u8 {
+            for upcard in 2..=11u8 {
+                let p = prob(first, &[]) * prob(upcard, &[first]) * prob(second, &[first, upcard]);
+                let hand = Hand::from_cards(first, second);
+
+                if first + second == 21 {
+                    let dealer_natural = match upcard {
+                        10 => prob(11, &[first, upcard, second]),
+                        11 => prob(10, &[first, upcard, second]),
+                        _ => 0.0,
+                    };
+                    let payout = rules.blackjack_payout;
+                    mean += p * (1.0 - dealer_natural) * payout;
+                    second_moment += p * (1.0 - dealer_natural) * payout * payout;
+                    continue;
+                }
+
+                let state = PlayerState::from_hand(&hand, upcard);
+                let settled = if first == 10 && second == 10 {
+                    // Two tens are the pair 10,10 only when their ranks match
+                    let matched = matched_tens(rules, card_probs, shoe_cards);
+                    match (settle(&state), settle(&PlayerState::new_unlike_tens(upcard))) {
+                        (Some(pair), Some(unlike)) => Some((
+                            matched * pair.0 + (1.0 - matched) * unlike.0,
+                            matched * pair.1 + (1.0 - matched) * unlike.1,
+                        )),
+                        (pair, unlike) => pair.or(unlike),
+                    }
+                } else {
+                    settle(&state)
+                };
+                let Some((ev, ev_squared)) = settled else { continue };
+                mean += p * ev;
+                second_moment += p * ev_squared;
+            }
+        }
+    }
+
+    RoundStats { ev: mean, std_dev: (second_moment - mean * mean).max(0.0).sqrt() }
+}
+
+/// Chance two tens dealt from `card_probs` (with `shoe_cards` as in `round_stats`) share a rank, the ten
+/// ranks split as in a deck of `rules.deck_type`
+fn matched_tens(rules: &RuleSet, card_probs: &[f64; 10], shoe_cards: Option<f64>) -> f64 {
+    let ranks = rules.deck_type.ten_ranks().map(f64::from);
+    let shares = ranks.map(|count| count / ranks.iter().sum::<f64>());
+    match shoe_cards {
+        Some(cards) => {
+            let tens = card_probs[10 - 2] * cards;
+            if tens <= 1.0 { return 1.0; }
+            shares.iter().map(|share| share * tens * (share * tens - 1.0)).sum::<f64>() / (tens * (tens - 1.0))
+        }
+        None => shares.iter().map(|share| share * share).sum(),
+    }
+}
+
+/// Chance the player's first two cards are a natural, dealt from `card_probs` (with `shoe_cards` as in
+/// `round_stats`): 4.83% from a single deck, where the ace leaves 16 tens in 51 cards, against 4.75% on 8 decks
+pub fn natural_probability(card_probs: &[f64; 10], shoe_cards: Option<f64>) -> f64 {
+    let (aces, tens) = (card_probs[11 - 2], card_probs[10 - 2]);
+    let without_replacement = shoe_cards.map_or(1.0, |cards| cards / (cards - 1.0));
+    2.0 * aces * tens * without_replacement
+}
+
+/// Even money on a player natural against a dealer ace, per initial bet
+#[derive(Debug, Clone, Copy)]
+pub struct EvenMoney {
+    /// Taking even money: a guaranteed 1:1 win
+    pub take: f64,
+    /// Declining: the natural's payout, or a push when the dealer has blackjack too
+    pub decline: f64,
+    /// Chance the dealer's hole card is a ten
+    pub dealer_blackjack: f64,
+}
+
+impl EvenMoney {
+    /// Taking even money has the higher EV; declining wins ties
+    pub fn should_take(&self) -> bool {
+        self.take > self.decline
+    }
+}
+
+/// Even money against a dealer ace, dealt from `card_probs` (with `shoe_cards` as in `round_stats`;
+/// the player's ace and ten and the dealer's ace have left the shoe). Taking it is insurance on a
+/// natural, so declining is right unless tens make up more than a third of the unseen cards at 3:2.
+pub fn even_money(rules: &RuleSet, card_probs: &[f64; 10], shoe_cards: Option<f64>) -> EvenMoney {
+    let tens = card_probs[10 - 2];
+    let dealer_blackjack = match shoe_cards {
+        Some(cards) => ((tens * cards - 1.0) / (cards - 3.0)).max(0.0),
+        None => tens,
+    };
+    EvenMoney { take: 1.0, decline: (1.0 - dealer_blackjack) * rules.blackjack_payout, dealer_blackjack }
+}
+
+/// House edge of the solved strategy under `rules`: minus the expected win per unit bet,
+/// with every starting hand weighted by how often the rules' deck deals it
+pub fn house_edge(rules: &RuleSet, strategy: &StateStats) -> f64 {
+    let (card_probs, shoe_cards) = rules_composition(rules);
+    -round_stats(rules, strategy, &card_probs, shoe_cards).ev
+}
+
+/// Expected value per round of playing `table`'s action as the first decision of every
+/// state it sets, and the best solved action elsewhere. Each action's EV is taken from `solved`,
+/// so later decisions follow the playout the solve used; actions never simulated for a state
+/// (e.g. Split on a non-pair) fall back to the best action.
+pub fn strategy_ev(rules: &RuleSet, solved: &StateStats, table: &StrategyTable) -> f64 {
+    let (card_probs, shoe_cards) = rules_composition(rules);
+    let played = |state: &PlayerState, actions: &ActionTable| match table.get(state) {
+        Some((action, _)) if actions[action.index()].n > 0 => Some(action),
+        _ => get_best_action(actions).map(|(best, _)| best),
+    };
+    weighted_round(rules, solved, &card_probs, shoe_cards, played).ev
+}
+
+/// Expected loss per hand, in initial bets, of playing `table` instead of the solved best actions.
+/// Both sides use the same sampled EVs, so the solved chart itself costs exactly zero.
+pub fn strategy_cost(rules: &RuleSet, solved: &StateStats, table: &StrategyTable) -> f64 {
+    let (card_probs, shoe_cards) = rules_composition(rules);
+    round_stats(rules, solved, &card_probs, shoe_cards).ev - strategy_ev(rules, solved, table)
+}
+
+/// Solve every state under `rules`, then return the cost per hand of playing `table`
+pub fn evaluate_strategy(table: &StrategyTable, rules: &RuleSet) -> f64 {
+    let solved = solve_with_rules(&generate_all_states(), rules, Progress::Quiet);
+    strategy_cost(rules, &solved, table)
+}
+
+/// How many hands splitting each pair ends as, over `rounds` deals against every upcard, by split card
+/// (2-11). More than two takes resplits (`RuleSet::max_splits` above 1); aces always split once.
+pub fn split_hand_distribution(rules: &RuleSet, rounds: u32) -> Vec<(u8, SplitHandCounts)> {
+    (2..=11u8)
+        .into_par_iter()
+        .map(|card| {
+            let total = if card == 11 { 12 } else { card * 2 };
+            let states = (2..=11).map(|upcard| PlayerState::new(total, upcard, card == 11, true));
+            let mut counts = SplitHandCounts::default();
+            match rules.num_decks {
+                Some(num_decks) => {
+                    let shoe = FiniteShoe::with_deck_type(num_decks, rules.deck_type);
+                    let mut engine = BlackjackEngine::with_rules(shoe, rules.clone());
+                    states.for_each(|state| counts.merge(&engine.simulate_split_hands(&state, rounds)));
+                }
+                None => {
+                    let deck = InfiniteDeck::with_deck_type(rules.deck_type);
+                    let mut engine = BlackjackEngine::with_rules(deck, rules.clone());
+                    states.for_each(|state| counts.merge(&engine.simulate_split_hands(&state, rounds)));
+                }
+            }
+            (card, counts)
+        })
+        .collect()
+}
+
+/// Win, loss and push counts of each sampled state's best action over `rounds` fresh deals,
+/// in the order of `strategy`
+pub fn outcome_frequencies(
+    rules: &RuleSet,
+    strategy: &StateStats,
+    rounds: u32,
+) -> Vec<(PlayerState, Action, OutcomeCounts)> {
+    let best: Vec<(PlayerState, Action)> =
+        strategy.iter().filter_map(|(state, actions)| Some((*state, get_best_action(actions)?.0))).collect();
+    best.into_par_iter()
+        .map(|(state, action)| {
+            // Outcomes need every round settled against the dealer's actual hand
+            let counts = match rules.num_decks {
+                Some(num_decks) => {
+                    let shoe = FiniteShoe::with_deck_type(num_decks, rules.deck_type);
+                    let mut engine = BlackjackEngine::with_rules(shoe, rules.clone()).without_exact_stands();
+                    engine.simulate_outcomes(&state, action, rounds)
+                }
+                None => {
+                    let deck = InfiniteDeck::with_deck_type(rules.deck_type);
+                    let mut engine = BlackjackEngine::with_rules(deck, rules.clone()).without_exact_stands();
+                    engine.simulate_outcomes(&state, action, rounds)
+                }
+            };
+            (state, action, counts)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{solve_with_rules_and_playout, SolverConfig};
+    use crate::playout::BasicStrategy;
+
+    #[test]
+    fn the_solved_chart_evaluates_to_the_solved_house_edge() {
+        let rules = RuleSet { num_decks: None, ..RuleSet::default() };
+        let config = SolverConfig { target_sem: 0.05, seed: Some(3), ..SolverConfig::default() };
+        let solved =
+            solve_with_rules_and_playout(&generate_all_states(), &rules, &BasicStrategy, &config, Progress::Quiet);
+
+        let optimal = StrategyTable::from_stats(&solved);
+        let edge = house_edge(&rules, &solved);
+        assert!((strategy_ev(&rules, &solved, &optimal) + edge).abs() < 1e-12);
+        assert_eq!(strategy_cost(&rules, &solved, &optimal), 0.0);
+
+        // The exact chart differs only on decisions too close for this SEM to tell apart
+        let exact = strategy_cost(&rules, &solved, &StrategyTable::exact(&rules));
+        assert!((0.0..0.002).contains(&exact), "exact chart costs {}", exact);
+    }
+
+    #[test]
+    fn a_dealer_standing_on_16_lowers_the_house_edge() {
+        let edge = |dealer_stands_on| {
+            let rules = RuleSet { num_decks: None, dealer_stands_on, ..RuleSet::default() };
+            -exact_round_ev(&rules, &rules.deck_type.probabilities(), None)
+        };
+        let (sixteen, seventeen) = (edge(16), edge(17));
+        // Standing on 16 forgoes the hits that would have made 17 to 21, well over a point to the player
+        assert!(seventeen - sixteen > 0.01, "S16 edge {} vs S17 {}", sixteen, seventeen);
+    }
+}
@@ -0,0 +1,124 @@
+//! Bankroll risk: probability of losing a bankroll at a given bet size.
+
+use crate::deck::FiniteShoe;
+use crate::rules::RuleSet;
+use crate::session::{simulate_session, simulate_session_to_target, BetSizing};
+use crate::solver::StateStats;
+use rayon::prelude::*;
+
+/// Longest session played while waiting for ruin or the target
+pub const MAX_SESSION_ROUNDS: u32 = 1_000_000;
+
+/// Standard normal CDF (Abramowitz & Stegun 7.1.26, |error| < 1.5e-7)
+pub fn normal_cdf(x: f64) -> f64 {
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * z);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erfc = poly * (-z * z).exp();
+    if x >= 0.0 { 1.0 - 0.5 * erfc } else { 0.5 * erfc }
+}
+
+/// Probability of losing `bankroll` within `rounds` rounds of flat `bet`s.
+/// Diffusion approximation: the bankroll moves as Brownian motion with drift
+/// `ev_per_round * bet` and standard deviation `std_per_round * bet` per round.
+pub fn risk_of_ruin(ev_per_round: f64, std_per_round: f64, bankroll: f64, bet: f64, rounds: u32) -> f64 {
+    if bankroll <= 0.0 { return 1.0; }
+
+    let drift = ev_per_round * bet * rounds as f64;
+    let spread = std_per_round * bet * (rounds as f64).sqrt();
+    if spread <= 0.0 {
+        return if bankroll + drift <= 0.0 { 1.0 } else { 0.0 };
+    }
+
+    // First-passage probability: direct paths plus paths reflected off the ruin barrier.
+    // A NaN from an overflowing reflection term only arises when ruin is certain.
+    let variance = (std_per_round * bet).powi(2);
+    let reflection = (-2.0 * ev_per_round * bet * bankroll / variance).exp();
+    let ruin = normal_cdf((-bankroll - drift) / spread) + reflection * normal_cdf((-bankroll + drift) / spread);
+    ruin.min(1.0)
+}
+
+/// Probability of ever losing `bankroll` playing flat `bet`s with no time limit.
+/// Exponential approximation `exp(-2 * ev * bankroll / (std^2 * bet))`, certain without an edge.
+pub fn lifetime_risk_of_ruin(ev_per_round: f64, std_per_round: f64, bankroll: f64, bet: f64) -> f64 {
+    if bankroll <= 0.0 || ev_per_round <= 0.0 { return 1.0; }
+    if std_per_round <= 0.0 { return 0.0; }
+    (-2.0 * ev_per_round * bankroll / (std_per_round * std_per_round * bet)).exp().min(1.0)
+}
+
+/// Monte Carlo counterpart of `risk_of_ruin`: the fraction of `trials` flat-bet sessions
+/// through a `num_decks` shoe that go broke within `rounds` rounds
+pub fn simulated_risk_of_ruin(
+    rules: &RuleSet,
+    strategy: &StateStats,
+    num_decks: u8,
+    bankroll: f64,
+    bet: f64,
+    rounds: u32,
+    trials: u32,
+) -> f64 {
+    let sizing = BetSizing::Flat(bet);
+    let ruined = (0..trials)
+        .into_par_iter()
+        .filter(|_| {
+            let shoe = FiniteShoe::with_deck_type(num_decks, rules.deck_type);
+            simulate_session(rules, shoe, strategy, &sizing, bankroll, rounds, None).ruined
+        })
+        .count();
+    ruined as f64 / trials as f64
+}
+
+/// Fraction of `trials` flat-bet sessions that go broke before the bankroll grows to `target`.
+/// Sessions still undecided after `MAX_SESSION_ROUNDS` count as surviving.
+pub fn simulated_ruin_before_target(
+    rules: &RuleSet,
+    strategy: &StateStats,
+    num_decks: u8,
+    bankroll: f64,
+    bet: f64,
+    target: f64,
+    trials: u32,
+) -> f64 {
+    let sizing = BetSizing::Flat(bet);
+    let ruined = (0..trials)
+        .into_par_iter()
+        .filter(|_| {
+            let shoe = FiniteShoe::with_deck_type(num_decks, rules.deck_type);
+            simulate_session_to_target(rules, shoe, strategy, &sizing, bankroll, target, MAX_SESSION_ROUNDS).ruined
+        })
+        .count();
+    ruined as f64 / trials as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{round_stats, rules_composition};
+    use crate::solve_with_config;
+    use crate::solver::SolverConfig;
+
+    #[test]
+    fn ruin_rises_as_the_bankroll_shrinks() {
+        let bankrolls = [400.0, 200.0, 100.0, 50.0, 25.0];
+        let within = bankrolls.map(|bankroll| risk_of_ruin(-0.005, 1.15, bankroll, 1.0, 10_000));
+        assert!(within.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", within);
+        let lifetime = bankrolls.map(|bankroll| lifetime_risk_of_ruin(0.01, 1.15, bankroll, 1.0));
+        assert!(lifetime.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", lifetime);
+        assert_eq!(risk_of_ruin(0.01, 1.15, 0.0, 1.0, 100), 1.0);
+    }
+
+    #[test]
+    fn the_diffusion_matches_simulated_sessions() {
+        let rules = RuleSet::default();
+        let config = SolverConfig { target_sem: 0.02, seed: Some(1), ..SolverConfig::default() }.plain_sampled();
+        let solved = solve_with_config(&rules, &config);
+        let (card_probs, shoe_cards) = rules_composition(&rules);
+        let round = round_stats(&rules, &solved, &card_probs, shoe_cards);
+
+        // About a one-in-four chance of losing 30 units in 500 rounds; 2000 sessions are good to about 1%
+        let analytic = risk_of_ruin(round.ev, round.std_dev, 30.0, 1.0, 500);
+        let simulated = simulated_risk_of_ruin(&rules, &solved, 8, 30.0, 1.0, 500, 2000);
+        assert!((0.1..0.4).contains(&analytic), "analytic {}", analytic);
+        assert!((analytic - simulated).abs() < 0.05, "analytic {} simulated {}", analytic, simulated);
+    }
+}
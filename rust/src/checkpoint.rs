@@ -0,0 +1,112 @@
+//! Checkpoints of an in-progress solve, so an interrupted run can resume where it stopped.
+//! Plain text: the rules, the last finished iteration, then every sampled action's raw sums.
+
+use crate::deck::PlayerState;
+use crate::engine::{Action, ActionStats};
+use crate::solver::ActionTable;
+use std::fmt::Write;
+
+/// First line of every checkpoint file
+const HEADER: &str = "blackjack_solver checkpoint 1";
+
+/// Statistics of a solve after `iteration` finished iterations
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    /// `RuleSet` summary the statistics were sampled under
+    pub rules: String,
+    pub iteration: u32,
+    pub stats: Vec<(PlayerState, ActionTable)>,
+}
+
+impl Checkpoint {
+    /// One tab-separated line per sampled action: state, action, `n`, `sum_x`, `sum_x_squared`
+    /// and the two Kahan error terms. Floats print with enough digits to read back exactly.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\nrules\t{}\niteration\t{}\n", HEADER, self.rules, self.iteration);
+        for (state, table) in &self.stats {
+            for (action, stats) in Action::ALL.iter().zip(table).filter(|(_, stats)| stats.n > 0) {
+                writeln!(
+                    text,
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                    state, action, stats.n, stats.sum_x, stats.sum_x_squared, stats.sum_x_error,
+                    stats.sum_x_squared_error
+                )
+                .unwrap();
+            }
+        }
+        text
+    }
+
+    /// Parse the output of `to_text`
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().enumerate();
+        if lines.next().map(|(_, line)| line) != Some(HEADER) { return Err("not a solver checkpoint".to_string()); }
+        let mut field = |name: &str| {
+            lines
+                .next()
+                .and_then(|(_, line)| line.strip_prefix(name)?.strip_prefix('\t'))
+                .map(str::to_string)
+                .ok_or_else(|| format!("missing '{}' line", name))
+        };
+        let rules = field("rules")?;
+        let iteration = field("iteration")?.parse().map_err(|_| "invalid iteration".to_string())?;
+
+        let mut stats: Vec<(PlayerState, ActionTable)> = Vec::new();
+        for (number, line) in lines {
+            let err = |msg: &str| format!("line {}: {}", number + 1, msg);
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [state, action, n, sum_x, sum_x_squared, sum_x_error, sum_x_squared_error] = fields[..] else {
+                return Err(err("expected 7 tab-separated fields"));
+            };
+            let state: PlayerState = state.parse().map_err(|e: String| err(&e))?;
+            let action: Action = action.parse().map_err(|e: String| err(&e))?;
+            let float = |raw: &str| raw.parse::<f64>().map_err(|_| err("invalid number"));
+            let action_stats = ActionStats {
+                n: n.parse().map_err(|_| err("invalid hand count"))?,
+                sum_x: float(sum_x)?,
+                sum_x_squared: float(sum_x_squared)?,
+                sum_x_error: float(sum_x_error)?,
+                sum_x_squared_error: float(sum_x_squared_error)?,
+            };
+
+            let idx = match stats.iter().position(|(seen, _)| *seen == state) {
+                Some(idx) => idx,
+                None => {
+                    stats.push((state, Default::default()));
+                    stats.len() - 1
+                }
+            };
+            stats[idx].1[action.index()] = action_stats;
+        }
+        Ok(Checkpoint { rules, iteration, stats })
+    }
+
+    /// Pool another run's statistics into these. Merging is exact, so independent runs (unseeded,
+    /// or with different seeds) pool into the statistics of one run as long as all of them together.
+    /// Iterations add up.
+    pub fn merge(&mut self, other: &Checkpoint) -> Result<(), String> {
+        if self.rules != other.rules {
+            return Err(format!("cannot merge runs under different rules ({} and {})", self.rules, other.rules));
+        }
+        for (state, table) in &other.stats {
+            match self.stats.iter_mut().find(|(seen, _)| seen == state) {
+                Some((_, totals)) => totals.iter_mut().zip(table).for_each(|(total, stats)| total.merge(stats)),
+                None => self.stats.push((*state, table.clone())),
+            }
+        }
+        self.iteration += other.iteration;
+        Ok(())
+    }
+
+    /// Write to `path` through a temporary file, so an interruption mid-write keeps the previous checkpoint
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let temp = format!("{}.tmp", path);
+        std::fs::write(&temp, self.to_text())?;
+        std::fs::rename(&temp, path)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| format!("cannot read '{}': {}", path, err))?;
+        Checkpoint::from_text(&text).map_err(|err| format!("{}: {}", path, err))
+    }
+}
@@ -0,0 +1,539 @@
+//! Command-line options for the solver binary.
+
+use blackjack_solver::checkpoint::Checkpoint;
+use blackjack_solver::config::Config;
+#[cfg(feature = "charts")]
+use blackjack_solver::heatmap::HeatmapMetric;
+use blackjack_solver::session::Wonging;
+use blackjack_solver::{ContinuationPolicy, DeckType, PlayerState, RuleSet, SolverConfig, StrategyTable};
+use std::str::FromStr;
+
+pub const USAGE: &str = "\
+Usage: blackjack_solver [COMMAND] [OPTIONS]
+
+Commands:
+  solve                Solve and report the strategy chart [default]
+  quiz                 Solve, then practise the chart on randomly dealt hands
+  query                Solve a single state, e.g. 'query --total 16 --dealer 10'
+  merge <FILES>...     Pool the checkpoints of independent runs and report the combined chart
+
+Options:
+  --config <FILE>      Load [rules] and [solver] settings from a TOML file; flags override it
+  --decks <N>          Decks in the shoe, or 'inf' for an infinite deck [default: 8]
+  --spanish            Play Spanish 21: 10-spots removed, player 21 always wins, 21 bonuses paid
+  --double-exposure    Deal both dealer cards face up, ties lose; solves a chart per dealer total
+  --compare-decks      Re-solve with 1, 2, 6 and 8 decks and report each house edge
+  --true-count <TC>    Solve the chart with the shoe held at a Hi-Lo true count
+  --optimal-playout    Finish hands with exact infinite-deck play instead of basic thresholds
+  --continuation <SPEC> Thresholds for play after the first decision, e.g. 'soft=19,stiff=13'
+                       (keys: hard, soft, stiff, weak, double-hard, double-soft)
+  --antithetic         Sample hands in mirrored antithetic pairs (needs --decks inf)
+  --importance         Importance sample each state's draws toward its decisive cards (needs --decks inf)
+  --indices            Re-solve across true counts and report index plays
+  --kelly              Re-solve across true counts and report Kelly bet sizing
+  --kelly-fraction <F> Kelly multiplier for the recommended bet [default: 0.5]
+  --ruin               Report risk of ruin for the solved strategy
+  --spread <RAMP>      Simulate a count bet spread, e.g. '1:2,2:4,3:8' (TC:units)
+  --penetration <F>    Fraction of the shoe dealt before reshuffling for --spread [default: 0.75]
+  --hands <N>          Hands played for --spread [default: 10000000]
+  --rounds-per-hour <N> Report EV and SD per hour at --bet per round; also paces --spread [default: 100]
+                       (--hands-per-hour sets the pace for --spread alone)
+  --wong-in <TC>       Also simulate back-counting with --spread: flat bets from this true count on
+  --wong-out <TC>      True count below which --wong-in stops betting until it is reached again [default: 0]
+  --bankroll <UNITS>   Bankroll for --ruin and --kelly [default: 1000]
+  --bet <UNITS>        Flat bet for --ruin and --rounds-per-hour, minimum bet for --kelly [default: 10]
+  --rounds <N>         Rounds per session for --ruin [default: 10000]
+  --target <UNITS>     Bankroll goal for --ruin's play-until-ruin-or-target check [default: 2x bankroll]
+  --no-progress        Print progress lines every few iterations instead of a live bar
+  --progress-json <FILE> Write progress as newline-delimited JSON to FILE ('-' for stdout)
+  --evaluate <CSV>     Report the cost per hand of playing a chart from a CSV file
+                       (header 'hand,2,...,10,A', rows H16 / S18 / P8 / PA)
+  --close-threshold <EV> EV gap below which a decision is listed as close [default: 0.02]
+  --close-limit <N>    Close decisions listed [default: 25]
+  --close-sort <KEY>   Order close decisions by 'diff', 'state' or 'ev' (best EV first) [default: diff]
+  --full               Add every action's EV, hand count and SEM for each state to the report,
+                       and how often each best action wins, loses and pushes
+  --side-bets          Add the Perfect Pairs and 21+3 side bet odds and EVs for the shoe to the report
+  --validate           Compare every simulated EV with the exact infinite-deck one and list the largest
+                       differences, flagging those beyond 4 SEM (needs --decks inf)
+  --curves             Add Hit and Stand EVs of hard 8-20 per upcard, plotted in ASCII with the crossovers marked
+  --eor                Add the effect on the player's EV of removing one card of each rank from a single deck
+  --format <FMT>       Save the report as 'md' (strategy_output.md) or 'html', a color-coded
+                       chart with the rules and house edge (strategy_output.html) [default: md].
+                       With the 'charts' feature, 'png' saves a heatmap of the best EV and 'png-gap'
+                       of the EV gap to the runner-up (strategy_output.png)
+  --emit-rust <FILE>   Write the solved chart as a self-contained Rust lookup function
+  --threads <N>        Worker threads for the simulation [default: all cores]
+  --seed <N>           Seed the simulation so the solve can be reproduced
+  --checkpoint <FILE>  Save the solve's statistics to FILE every few iterations
+  --checkpoint-every <N> Iterations between checkpoints [default: 10]
+  --resume <FILE>      Continue the solve saved in a checkpoint (and keep checkpointing to it)
+  --total <HAND>       Hand for query: a total (16, s18) or cards (A,7, 8,8)
+  --dealer <CARD>      Dealer upcard for query (2-10 or A)
+  --soft               Read query's --total as a soft total
+  --pair               Read query's --total as a pair (16 is 8,8; --soft --pair 12 is A,A)
+  -h, --help           Print this help";
+
+/// What the binary does with the solved chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Solve,
+    Quiz,
+    /// Solve only `Options::query`
+    Query,
+    /// Report `Options::merged` instead of solving
+    Merge,
+}
+
+/// Order of the close-decisions list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseSort {
+    /// Smallest EV gap first
+    Diff,
+    /// Chart order: hard, soft, then pairs
+    State,
+    /// Highest best-action EV first
+    Ev,
+}
+
+impl FromStr for CloseSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "diff" => Ok(CloseSort::Diff),
+            "state" => Ok(CloseSort::State),
+            "ev" => Ok(CloseSort::Ev),
+            _ => Err(format!("unknown sort '{}'", s)),
+        }
+    }
+}
+
+/// File format the chart is saved in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// `strategy_output.md` with every report section
+    Markdown,
+    /// `strategy_output.html`, a color-coded chart
+    Html,
+    /// `strategy_output.png`, a heatmap colored by the given metric
+    #[cfg(feature = "charts")]
+    Heatmap(HeatmapMetric),
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "md" | "markdown" => Ok(ReportFormat::Markdown),
+            "html" => Ok(ReportFormat::Html),
+            #[cfg(feature = "charts")]
+            "png" => Ok(ReportFormat::Heatmap(HeatmapMetric::BestEv)),
+            #[cfg(feature = "charts")]
+            "png-gap" => Ok(ReportFormat::Heatmap(HeatmapMetric::Gap)),
+            _ => Err(format!("unknown format '{}'", s)),
+        }
+    }
+}
+
+/// Parsed command-line options
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub command: Command,
+    /// State asked about by `query`
+    pub query: Option<PlayerState>,
+    /// Checkpoints pooled by `merge`
+    pub merged: Option<Checkpoint>,
+    /// Table rules: `--config`, then `--decks`, `--spanish` and `--double-exposure`
+    pub rules: RuleSet,
+    /// Solver settings: `--config`, then `--threads`, `--seed` and the checkpoint flags
+    pub solver: SolverConfig,
+    pub compare_decks: bool,
+    pub true_count: Option<f64>,
+    pub antithetic: bool,
+    pub optimal_playout: bool,
+    /// Threshold continuation play replacing the basic policy
+    pub continuation: Option<ContinuationPolicy>,
+    pub index_plays: bool,
+    pub kelly: bool,
+    pub kelly_fraction: f64,
+    pub risk_of_ruin: bool,
+    /// `(min_true_count, units)` steps of the bet spread to simulate
+    pub spread: Option<Vec<(f64, f64)>>,
+    pub penetration: f64,
+    pub hands: u64,
+    pub rounds_per_hour: f64,
+    /// Report the flat-bet EV and SD per hour
+    pub hourly: bool,
+    /// Back-counting range compared against flat betting alongside the spread
+    pub wonging: Option<Wonging>,
+    pub bankroll: f64,
+    pub bet: f64,
+    pub rounds: u32,
+    pub target: Option<f64>,
+    pub no_progress: bool,
+    pub progress_json: Option<String>,
+    /// Chart loaded from `--evaluate`
+    pub evaluate: Option<StrategyTable>,
+    pub emit_rust: Option<String>,
+    pub full: bool,
+    pub side_bets: bool,
+    /// Report Hit against Stand over the hard totals for each upcard
+    pub curves: bool,
+    /// Report each rank's effect of removal
+    pub eor: bool,
+    /// Report the simulated EVs' differences from the exact solution
+    pub validate: bool,
+    pub close_threshold: f64,
+    pub close_limit: usize,
+    pub close_sort: CloseSort,
+    pub format: ReportFormat,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            command: Command::Solve,
+            query: None,
+            merged: None,
+            rules: RuleSet::default(),
+            solver: SolverConfig::default(),
+            compare_decks: false,
+            true_count: None,
+            antithetic: false,
+            optimal_playout: false,
+            continuation: None,
+            index_plays: false,
+            kelly: false,
+            kelly_fraction: 0.5,
+            risk_of_ruin: false,
+            spread: None,
+            penetration: 0.75,
+            hands: 10_000_000,
+            rounds_per_hour: 100.0,
+            hourly: false,
+            wonging: None,
+            bankroll: 1000.0,
+            bet: 10.0,
+            rounds: 10_000,
+            target: None,
+            no_progress: false,
+            progress_json: None,
+            evaluate: None,
+            emit_rust: None,
+            full: false,
+            side_bets: false,
+            curves: false,
+            eor: false,
+            validate: false,
+            close_threshold: 0.02,
+            close_limit: 25,
+            close_sort: CloseSort::Diff,
+            format: ReportFormat::Markdown,
+        }
+    }
+}
+
+/// Parse options from the arguments following the program name.
+/// `Ok(None)` means help was requested.
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter().peekable();
+    let (mut config, mut num_decks, mut spanish, mut double_exposure) = (None, None, false, false);
+    let (mut threads, mut seed, mut importance) = (None, None, false);
+    let (mut checkpoint, mut checkpoint_every, mut resume) = (None, None, None::<String>);
+    let (mut total, mut dealer, mut soft, mut pair) = (None::<String>, None::<String>, false, false);
+    let (mut wong_in, mut wong_out) = (None, None);
+    let mut merge_files = Vec::new();
+
+    if let Some(command) = args.next_if(|arg| !arg.starts_with('-')) {
+        options.command = match command.as_str() {
+            "solve" => Command::Solve,
+            "quiz" => Command::Quiz,
+            "query" => Command::Query,
+            "merge" => Command::Merge,
+            _ => return Err(format!("unknown command '{}'", command)),
+        };
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => config = Some(config_toml(&arg, args.next())?),
+            "--decks" => num_decks = Some(decks(&arg, args.next())?),
+            "--spanish" => spanish = true,
+            "--double-exposure" => double_exposure = true,
+            "--compare-decks" => options.compare_decks = true,
+            "--true-count" => options.true_count = Some(value(&arg, args.next())?),
+            "--antithetic" => options.antithetic = true,
+            "--importance" => importance = true,
+            "--optimal-playout" => options.optimal_playout = true,
+            "--continuation" => options.continuation = Some(policy(&arg, args.next())?),
+            "--indices" => options.index_plays = true,
+            "--kelly" => options.kelly = true,
+            "--kelly-fraction" => options.kelly_fraction = value(&arg, args.next())?,
+            "--ruin" => options.risk_of_ruin = true,
+            "--spread" => options.spread = Some(ramp(&arg, args.next())?),
+            "--penetration" => options.penetration = value(&arg, args.next())?,
+            "--hands" => options.hands = value(&arg, args.next())?,
+            "--rounds-per-hour" => {
+                options.rounds_per_hour = value(&arg, args.next())?;
+                options.hourly = true;
+            }
+            "--hands-per-hour" => options.rounds_per_hour = value(&arg, args.next())?,
+            "--wong-in" => wong_in = Some(value(&arg, args.next())?),
+            "--wong-out" => wong_out = Some(value(&arg, args.next())?),
+            "--bankroll" => options.bankroll = value(&arg, args.next())?,
+            "--bet" => options.bet = value(&arg, args.next())?,
+            "--rounds" => options.rounds = value(&arg, args.next())?,
+            "--target" => options.target = Some(value(&arg, args.next())?),
+            "--threads" => threads = Some(value(&arg, args.next())?),
+            "--seed" => seed = Some(value(&arg, args.next())?),
+            "--checkpoint" => checkpoint = Some(value(&arg, args.next())?),
+            "--checkpoint-every" => checkpoint_every = Some(value(&arg, args.next())?),
+            "--resume" => resume = Some(value(&arg, args.next())?),
+            "--total" => total = Some(value(&arg, args.next())?),
+            "--dealer" => dealer = Some(value(&arg, args.next())?),
+            "--soft" => soft = true,
+            "--pair" => pair = true,
+            "--no-progress" => options.no_progress = true,
+            "--progress-json" => options.progress_json = Some(value(&arg, args.next())?),
+            "--full" => options.full = true,
+            "--side-bets" => options.side_bets = true,
+            "--curves" => options.curves = true,
+            "--eor" => options.eor = true,
+            "--validate" => options.validate = true,
+            "--close-threshold" => options.close_threshold = value(&arg, args.next())?,
+            "--close-limit" => options.close_limit = value(&arg, args.next())?,
+            "--close-sort" => options.close_sort = value(&arg, args.next())?,
+            "--format" => options.format = value(&arg, args.next())?,
+            "--emit-rust" => options.emit_rust = Some(value(&arg, args.next())?),
+            "--evaluate" => options.evaluate = Some(strategy_csv(&arg, args.next())?),
+            "-h" | "--help" => return Ok(None),
+            _ if options.command == Command::Merge && !arg.starts_with('-') => merge_files.push(arg),
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+
+    let config = config.unwrap_or_default();
+    options.rules = config.rules;
+    options.solver = config.solver;
+    if let Some(num_decks) = num_decks {
+        options.rules.num_decks = num_decks;
+    }
+    if spanish {
+        options.rules.deck_type = DeckType::Spanish;
+        options.rules.spanish_bonuses = true;
+    }
+    if double_exposure {
+        options.rules.double_exposure = true;
+    }
+    if threads.is_some() {
+        options.solver.threads = threads;
+    }
+    if seed.is_some() {
+        options.solver.seed = seed;
+    }
+    if importance {
+        options.solver.importance_sampling = true;
+    }
+    if let Some(path) = resume {
+        let saved = Checkpoint::load(&path)?;
+        if saved.rules != options.rules.to_string() {
+            return Err(format!("{} was solved under different rules ({})", path, saved.rules));
+        }
+        options.solver.resume = Some(saved);
+        options.solver.checkpoint.get_or_insert(path);
+    }
+    if checkpoint.is_some() {
+        options.solver.checkpoint = checkpoint;
+    }
+    if let Some(every) = checkpoint_every {
+        options.solver.checkpoint_interval = every;
+    }
+    // Kelly sizing, risk of ruin and the hourly summary read the per-hand variance from the solved statistics,
+    // which exact stands and importance sampling shrink
+    if options.kelly || options.risk_of_ruin || options.hourly {
+        if importance || options.antithetic {
+            return Err("--kelly, --ruin and --rounds-per-hour need plain sampling, without --importance or --antithetic"
+                .to_string());
+        }
+        options.solver = options.solver.plain_sampled();
+    }
+    match (wong_in, wong_out) {
+        (Some(wong_in_tc), wong_out_tc) => {
+            let wong_out_tc = wong_out_tc.unwrap_or(0.0);
+            if wong_out_tc > wong_in_tc {
+                return Err("--wong-out cannot be above --wong-in".to_string());
+            }
+            options.wonging = Some(Wonging { wong_in_tc, wong_out_tc });
+        }
+        (None, Some(_)) => return Err("--wong-out needs --wong-in".to_string()),
+        (None, None) => {}
+    }
+
+    if options.command == Command::Query {
+        let (Some(total), Some(dealer)) = (total, dealer) else {
+            return Err("query needs --total and --dealer".to_string());
+        };
+        options.query = Some(query_state(&total, &dealer, soft, pair)?);
+    }
+
+    if options.solver.checkpoint_interval == 0 {
+        return Err("--checkpoint-every must be at least 1".to_string());
+    }
+    if options.command == Command::Merge {
+        let mut files = merge_files.iter();
+        let first = files.next().ok_or("merge needs at least one checkpoint file")?;
+        let mut merged = Checkpoint::load(first)?;
+        for path in files {
+            merged.merge(&Checkpoint::load(path)?).map_err(|err| format!("{}: {}", path, err))?;
+        }
+        if merged.rules != options.rules.to_string() {
+            return Err(format!("the runs were solved under {}; pass the same rules to merge", merged.rules));
+        }
+        options.merged = Some(merged);
+    }
+
+    if threads == Some(0) {
+        return Err("--threads must be at least 1".to_string());
+    }
+    if !(0.0..1.0).contains(&options.penetration) {
+        return Err("--penetration must be between 0 and 1".to_string());
+    }
+    if options.wonging.is_some() && options.spread.is_none() {
+        return Err("--wong-in is simulated alongside --spread".to_string());
+    }
+    if options.optimal_playout && (options.antithetic || options.true_count.is_some()) {
+        return Err("--optimal-playout cannot be combined with --antithetic or --true-count".to_string());
+    }
+    if options.continuation.is_some() && (options.optimal_playout || options.true_count.is_some()) {
+        return Err("--continuation cannot be combined with --optimal-playout or --true-count".to_string());
+    }
+    if options.antithetic && (options.rules.num_decks.is_some() || options.true_count.is_some()) {
+        return Err("--antithetic mirrors infinite-deck draws and needs --decks inf".to_string());
+    }
+    if options.validate && (options.rules.num_decks.is_some() || options.true_count.is_some()) {
+        return Err("--validate compares against the exact infinite-deck solution and needs --decks inf".to_string());
+    }
+    if importance && (options.rules.num_decks.is_some() || options.true_count.is_some() || options.antithetic) {
+        return Err("--importance tilts infinite-deck draws and needs --decks inf without --antithetic".to_string());
+    }
+
+    // The double exposure chart is indexed by the dealer's total, which the other analyses don't know
+    let chart_only = options.command == Command::Solve
+        && !(options.compare_decks || options.index_plays || options.kelly || options.risk_of_ruin || options.hourly)
+        && !(options.side_bets || options.curves || options.validate || options.eor)
+        && options.true_count.is_none()
+        && options.spread.is_none()
+        && options.evaluate.is_none()
+        && options.emit_rust.is_none()
+        && options.format == ReportFormat::Markdown
+        && !(options.antithetic || options.optimal_playout || options.continuation.is_some())
+        && !options.solver.importance_sampling
+        && options.solver.checkpoint.is_none();
+    if options.rules.double_exposure && !chart_only {
+        return Err("double exposure only solves its chart; drop the other analysis, playout and checkpoint options"
+            .to_string());
+    }
+
+    Ok(Some(options))
+}
+
+/// Parse a deck count, where `inf` selects the infinite deck
+fn decks(flag: &str, raw: Option<String>) -> Result<Option<u8>, String> {
+    match raw.as_deref() {
+        Some("inf") => Ok(None),
+        Some("0") => Err(format!("{} must be at least 1", flag)),
+        _ => value(flag, raw).map(Some),
+    }
+}
+
+/// Parse a bet ramp of comma-separated `true_count:units` steps, sorted by count
+fn ramp(flag: &str, raw: Option<String>) -> Result<Vec<(f64, f64)>, String> {
+    let raw = raw.ok_or_else(|| format!("{} requires a value", flag))?;
+    let invalid = || format!("invalid value '{}' for {}", raw, flag);
+
+    let mut steps = raw
+        .split(',')
+        .map(|step| {
+            let (tc, units) = step.split_once(':').ok_or_else(invalid)?;
+            Ok((tc.trim().parse().map_err(|_| invalid())?, units.trim().parse().map_err(|_| invalid())?))
+        })
+        .collect::<Result<Vec<(f64, f64)>, String>>()?;
+    steps.sort_by(|a, b| a.0.total_cmp(&b.0));
+    Ok(steps)
+}
+
+/// Load the rules and solver settings in the TOML file named after `flag`
+fn config_toml(flag: &str, raw: Option<String>) -> Result<Config, String> {
+    let path = raw.ok_or_else(|| format!("{} requires a value", flag))?;
+    let text = std::fs::read_to_string(&path).map_err(|err| format!("cannot read '{}': {}", path, err))?;
+    Config::from_toml(&text).map_err(|err| format!("{}: {}", path, err))
+}
+
+/// State of a query hand: `--soft` and `--pair` qualify a bare total, cards speak for themselves
+fn query_state(total: &str, dealer: &str, soft: bool, pair: bool) -> Result<PlayerState, String> {
+    let hand = match total.parse::<u8>() {
+        Ok(12) if soft && pair => "A,A".to_string(),
+        Ok(n) if pair && n % 2 == 0 => format!("{0},{0}", n / 2),
+        Ok(n) if pair => return Err(format!("--pair needs an even total, got {}", n)),
+        Ok(n) if soft => format!("s{}", n),
+        _ => total.to_string(),
+    };
+    format!("{} vs {}", hand, dealer).parse()
+}
+
+/// Parse continuation thresholds, reporting the offending setting
+fn policy(flag: &str, raw: Option<String>) -> Result<ContinuationPolicy, String> {
+    let raw = raw.ok_or_else(|| format!("{} requires a value", flag))?;
+    raw.parse().map_err(|err| format!("{} for {}", err, flag))
+}
+
+/// Load the chart in the CSV file named after `flag`
+fn strategy_csv(flag: &str, raw: Option<String>) -> Result<StrategyTable, String> {
+    let path = raw.ok_or_else(|| format!("{} requires a value", flag))?;
+    let text = std::fs::read_to_string(&path).map_err(|err| format!("cannot read '{}': {}", path, err))?;
+    StrategyTable::from_csv(&text).map_err(|err| format!("{}: {}", path, err))
+}
+
+/// Parse the value following `flag`
+fn value<T: FromStr>(flag: &str, raw: Option<String>) -> Result<T, String> {
+    let raw = raw.ok_or_else(|| format!("{} requires a value", flag))?;
+    raw.parse().map_err(|_| format!("invalid value '{}' for {}", raw, flag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        parse_args(args.iter().map(|arg| arg.to_string())).map(|options| options.expect("not --help"))
+    }
+
+    #[test]
+    fn variance_readers_solve_plain_sampled() {
+        for flag in ["--kelly", "--ruin", "--rounds-per-hour"] {
+            let args: &[&str] = if flag == "--rounds-per-hour" { &[flag, "80"] } else { &[flag] };
+            let solver = parse(args).unwrap().solver;
+            assert!(!solver.exact_stands && !solver.importance_sampling, "{}", flag);
+        }
+        assert!(parse(&[]).unwrap().solver.exact_stands);
+    }
+
+    #[test]
+    fn no_progress_asks_for_progress_lines() {
+        assert!(parse(&["--no-progress"]).unwrap().no_progress);
+        assert!(!parse(&[]).unwrap().no_progress);
+    }
+
+    #[test]
+    fn variance_readers_reject_importance_and_antithetic() {
+        for extra in ["--importance", "--antithetic"] {
+            for flag in ["--kelly", "--ruin"] {
+                let err = parse(&["--decks", "inf", flag, extra]).unwrap_err();
+                assert!(err.contains("plain sampling"), "{} {}: {}", flag, extra, err);
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Rust source for a solved chart, to embed in another program without a data file.
+//! The generated module is self-contained: its own `Action`, `PlayerState` and a static table.
+
+use crate::deck::{PlayerState, NUM_STATES};
+use crate::engine::Action;
+use crate::rules::RuleSet;
+use crate::strategy::StrategyTable;
+
+/// Dealer upcards per chart row
+const ROW_LEN: usize = 10;
+
+/// Module body shared by every generated chart, after the table
+const LOOKUP_FN: &str = r#"
+/// Best action for `state`, or `None` outside the chart.
+/// `dealer_upcard` is 2-11 (11 = ace); soft 12 and hard 4 are only reachable as the pairs A,A and 2,2.
+pub fn lookup(state: PlayerState) -> Option<Action> {
+    let row = match (state.is_pair, state.is_soft) {
+        (true, false) if state.unlike_tens && state.total == 20 => 35,
+        _ if state.unlike_tens => return None,
+        (true, true) if state.total == 12 => 34,
+        (true, false) if (4..=20).contains(&state.total) && state.total % 2 == 0 => 25 + (state.total / 2 - 2) as usize,
+        (false, false) if (5..=21).contains(&state.total) => (state.total - 5) as usize,
+        (false, true) if (13..=20).contains(&state.total) => 17 + (state.total - 13) as usize,
+        _ => return None,
+    };
+    if !(2..=11).contains(&state.dealer_upcard) { return None; }
+    CHART[row][(state.dealer_upcard - 2) as usize]
+}
+"#;
+
+/// Rust source of a `lookup(state: PlayerState) -> Option<Action>` function for `table`,
+/// with `rules` recorded in the module docs. States missing from `table` look up as `None`.
+pub fn rust_lookup_source(rules: &RuleSet, table: &StrategyTable) -> String {
+    let mut output = String::new();
+    output.push_str("//! Solved blackjack strategy chart, generated by blackjack_solver.\n");
+    output.push_str(&format!("//! Rules: {}.\n\n", rules));
+
+    output.push_str("/// Player action\n#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n");
+    output.push_str("pub enum Action {\n    Hit,\n    Stand,\n    Double,\n    Split,\n    Surrender,\n}\n\n");
+    output.push_str("/// Starting hand against a dealer upcard\n#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n");
+    output.push_str("pub struct PlayerState {\n    pub total: u8,\n    pub dealer_upcard: u8,\n");
+    output.push_str("    pub is_soft: bool,\n    pub is_pair: bool,\n");
+    output.push_str("    /// Two tens of different ranks (10,J) rather than the pair 10,10\n    pub unlike_tens: bool,\n}\n\n");
+
+    let rows: Vec<(Vec<&str>, String)> = (0..NUM_STATES / ROW_LEN)
+        .map(|row| {
+            let cells = (0..ROW_LEN)
+                .map(|col| match PlayerState::from_index(row * ROW_LEN + col).and_then(|state| table.get(&state)) {
+                    Some((action, _)) => action.symbol(),
+                    None => "X",
+                })
+                .collect();
+            let label = PlayerState::from_index(row * ROW_LEN).map(|state| state.to_string()).unwrap_or_default();
+            (cells, label.trim_end_matches(" vs 2").to_string())
+        })
+        .collect();
+
+    // Only the cells in use are declared, so the module compiles without dead-code warnings
+    let used = |symbol: &str| rows.iter().any(|(cells, _)| cells.contains(&symbol));
+    for action in Action::ALL.into_iter().filter(|action| used(action.symbol())) {
+        output.push_str(&format!("const {}: Option<Action> = Some(Action::{});\n", action.symbol(), action));
+    }
+    if used("X") { output.push_str("const X: Option<Action> = None;\n"); }
+
+    output.push_str("\n/// Rows: hard 5-21, soft 13-20, pairs 2,2-A,A, unlike tens 10,J; columns: dealer 2-10, A\n");
+    output.push_str(&format!("static CHART: [[Option<Action>; {}]; {}] = [\n", ROW_LEN, rows.len()));
+    for (cells, label) in &rows {
+        output.push_str(&format!("    [{}], // {}\n", cells.join(", "), label));
+    }
+    output.push_str("];\n");
+    output.push_str(LOOKUP_FN);
+    output
+}
@@ -0,0 +1,240 @@
+//! Config files: table rules under `[rules]` and solver settings under `[solver]`.
+//! Reads the TOML subset such files need: tables, `key = value` lines, comments, strings, numbers, booleans
+//! and arrays of numbers.
+
+use crate::deck::DeckType;
+use crate::rules::RuleSet;
+use crate::solver::SolverConfig;
+use std::str::FromStr;
+
+/// Table rules and solver settings loaded from one file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    pub rules: RuleSet,
+    pub solver: SolverConfig,
+}
+
+/// Table a key belongs to
+#[derive(Clone, Copy)]
+enum Section {
+    Rules,
+    Solver,
+}
+
+impl Config {
+    /// Settings from TOML text. Keys before any table header are rules, so a bare rules file loads too;
+    /// missing keys keep the defaults.
+    ///
+    /// `[rules]` keys: `num_decks` (an integer, or `"inf"`), `deck_type` (`"standard"` or `"spanish"`),
+    /// `hit_soft_17`, `dealer_stands_on` (12 to 21), `double_on` (`"any"`, `"9-11"`, `"10-11"` or `"none"`), `das`,
+    /// `peek`, `obo`, `blackjack_payout`, `charlie` (card count, 3 or more, 0 for none), `late_surrender`,
+    /// `early_surrender`, `surrender_vs` (an array of upcards, 11 for an ace), `double_fraction`, `spanish_bonuses`,
+    /// `push_22`, `double_exposure`, `max_splits` (1 to 3) and `split_mixed_tens`. Each rule must pass
+    /// `RuleSet::validate`.
+    ///
+    /// `[solver]` keys: `target_sem`, `batch_size`, `max_iterations`, `seed`, `threads`, `checkpoint`
+    /// (a file path), `checkpoint_interval`, `exact_stands` and `importance_sampling`.
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let mut config = Config::default();
+        let mut section = Section::Rules;
+        for (number, line) in text.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() { continue; }
+            let err = |msg: String| format!("line {}: {} in '{}'", number + 1, msg, line);
+
+            if let Some(table) = line.strip_prefix('[').and_then(|table| table.strip_suffix(']')) {
+                section = match table.trim() {
+                    "rules" => Section::Rules,
+                    "solver" => Section::Solver,
+                    other => return Err(err(format!("unknown table '{}'", other))),
+                };
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(|| err("expected 'key = value'".to_string()))?;
+            let (key, value) = (key.trim(), value.trim());
+            match section {
+                // Each rule is checked as it is set, so an out-of-range value is reported on its own line
+                Section::Rules => set_rule(&mut config.rules, key, value).and_then(|_| config.rules.validate()),
+                Section::Solver => set_solver(&mut config.solver, key, value),
+            }
+            .map_err(err)?;
+        }
+        Ok(config)
+    }
+}
+
+fn set_rule(rules: &mut RuleSet, key: &str, value: &str) -> Result<(), String> {
+    match key {
+        "num_decks" if value == "\"inf\"" => rules.num_decks = None,
+        "num_decks" => rules.num_decks = Some(whole(value)?),
+        "deck_type" => {
+            rules.deck_type = match text(value)? {
+                "standard" => DeckType::Standard,
+                "spanish" => DeckType::Spanish,
+                _ => return Err("expected \"standard\" or \"spanish\"".to_string()),
+            }
+        }
+        "hit_soft_17" => rules.hit_soft_17 = flag(value)?,
+        "dealer_stands_on" => rules.dealer_stands_on = whole(value)?,
+        "double_on" => rules.double_on = text(value)?.parse()?,
+        "das" | "double_after_split" => rules.double_after_split = flag(value)?,
+        "peek" => rules.peek = flag(value)?,
+        "obo" => rules.obo = flag(value)?,
+        "blackjack_payout" => rules.blackjack_payout = number(value)?,
+        "charlie" => rules.charlie = Some(whole(value)?).filter(|&cards| cards > 0),
+        "split_mixed_tens" => rules.split_mixed_tens = flag(value)?,
+        "max_splits" => rules.max_splits = whole(value)?,
+        "late_surrender" | "surrender_allowed" => rules.late_surrender = flag(value)?,
+        "early_surrender" => rules.early_surrender = flag(value)?,
+        "surrender_vs" => rules.surrender_vs = Some(upcards(value)?),
+        "double_fraction" => rules.double_fraction = number(value)?,
+        "spanish_bonuses" => rules.spanish_bonuses = flag(value)?,
+        "push_22" => rules.push_22 = flag(value)?,
+        "double_exposure" => rules.double_exposure = flag(value)?,
+        other => return Err(format!("unknown rule '{}'", other)),
+    }
+    Ok(())
+}
+
+fn set_solver(solver: &mut SolverConfig, key: &str, value: &str) -> Result<(), String> {
+    match key {
+        "target_sem" => match number(value)? {
+            sem if sem > 0.0 => solver.target_sem = sem,
+            _ => return Err("target_sem must be positive".to_string()),
+        },
+        "batch_size" => solver.batch_size = positive(value)?,
+        "max_iterations" => solver.max_iterations = positive(value)?,
+        "seed" => solver.seed = Some(whole(value)?),
+        "threads" => solver.threads = Some(positive(value)?),
+        "checkpoint" => solver.checkpoint = Some(text(value)?.to_string()),
+        "checkpoint_interval" => solver.checkpoint_interval = positive(value)?,
+        "exact_stands" => solver.exact_stands = flag(value)?,
+        "importance_sampling" => solver.importance_sampling = flag(value)?,
+        other => return Err(format!("unknown solver setting '{}'", other)),
+    }
+    Ok(())
+}
+
+/// `line` up to its first `#` outside a string, so `checkpoint = "runs/#2.ckpt"` keeps its value
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn text(value: &str) -> Result<&str, String> {
+    value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).ok_or_else(|| "expected a string".to_string())
+}
+
+/// Array of dealer upcards, e.g. `[9, 10, 11]`, with 1 or 11 for an ace
+fn upcards(value: &str) -> Result<Vec<u8>, String> {
+    let items = value.strip_prefix('[').and_then(|v| v.strip_suffix(']'));
+    let items = items.ok_or_else(|| "expected an array".to_string())?;
+    items
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| whole::<u8>(item).map(|card| if card == 1 { 11 } else { card }))
+        .collect()
+}
+
+fn flag(value: &str) -> Result<bool, String> {
+    value.parse().map_err(|_| "expected true or false".to_string())
+}
+
+fn number(value: &str) -> Result<f64, String> {
+    value.parse().map_err(|_| "expected a number".to_string())
+}
+
+fn whole<T: FromStr>(value: &str) -> Result<T, String> {
+    value.replace('_', "").parse().map_err(|_| "expected a whole number".to_string())
+}
+
+fn positive<T: FromStr + Default + PartialEq>(value: &str) -> Result<T, String> {
+    let n = whole(value)?;
+    if n == T::default() { Err("expected at least 1".to_string()) } else { Ok(n) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The annotated sample of the README's config section
+    const SAMPLE: &str = r#"
+[rules]
+num_decks = 6          # or "inf"
+hit_soft_17 = true
+dealer_stands_on = 17  # lowest total the dealer stands on, 12 to 21
+double_on = "any"      # or "9-11", "10-11", "none"; also limits doubles after splits
+das = true
+max_splits = 1         # up to 3 resplits a matching card to four hands (aces split once)
+split_mixed_tens = true # any two tens split (10,K); false needs matching ranks
+peek = true            # US hole card; false is ENHC
+obo = false            # ENHC losing only the original bet to a dealer natural
+blackjack_payout = 1.5
+charlie = 0            # e.g. 5 for a five-card Charlie
+late_surrender = true  # false removes surrender from the chart
+early_surrender = false # surrender before the dealer checks for a natural, losing only half
+surrender_vs = [9, 10, 11] # upcards surrender is offered against (11 is an ace); omit for all
+double_fraction = 1.0  # extra bet on a double; 0.5 doubles for half
+spanish_bonuses = false # player 21 always wins, 5+ card and 6-7-8 / 7-7-7 bonuses
+push_22 = false        # a dealer 22 pushes (Free Bet, Blackjack Switch)
+double_exposure = false # both dealer cards face up, ties lose
+
+[solver]
+target_sem = 0.005     # SEM every state-action pair converges to
+batch_size = 10000     # hands per pending state each iteration
+max_iterations = 1000
+seed = 42              # reproducible on any thread count; omit for a fresh run
+threads = 4            # omit to use every core
+exact_stands = true    # settle stood hands at their exact EV on an infinite deck
+importance_sampling = false # tilt each state's first player card, see --importance
+"#;
+
+    #[test]
+    fn the_readme_sample_loads() {
+        let config = Config::from_toml(SAMPLE).unwrap();
+        let rules = RuleSet {
+            num_decks: Some(6),
+            hit_soft_17: true,
+            peek: true,
+            surrender_vs: Some(vec![9, 10, 11]),
+            ..RuleSet::default()
+        };
+        assert_eq!(config.rules, rules);
+        assert_eq!(config.solver.target_sem, 0.005);
+        assert_eq!((config.solver.seed, config.solver.threads), (Some(42), Some(4)));
+    }
+
+    #[test]
+    fn the_shipped_configs_load() {
+        for text in [
+            include_str!("../configs/atlantic_city.toml"),
+            include_str!("../configs/evolution_live.toml"),
+            include_str!("../configs/vegas_strip.toml"),
+        ] {
+            assert!(Config::from_toml(text).is_ok(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn a_hash_inside_a_string_is_not_a_comment() {
+        let config = Config::from_toml("[solver]\ncheckpoint = \"runs/#2.ckpt\"  # second run").unwrap();
+        assert_eq!(config.solver.checkpoint.as_deref(), Some("runs/#2.ckpt"));
+    }
+
+    #[test]
+    fn rules_out_of_range_are_reported_on_their_line() {
+        assert_eq!(RuleSet::from_toml("charlie = 0").unwrap().charlie, None);
+        let err = "line 1: charlie must be at least 3 cards in 'charlie = 2'";
+        assert_eq!(RuleSet::from_toml("charlie = 2"), Err(err.to_string()));
+        assert!(RuleSet::from_toml("das = true\nmax_splits = 4").unwrap_err().starts_with("line 2: max_splits"));
+        assert!(RuleSet::from_toml("surrender_vs = [1, 12]").is_err());
+    }
+}
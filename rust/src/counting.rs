@@ -0,0 +1,271 @@
+//! Count-conditioned solving with the Hi-Lo true count.
+//! Re-solves the chart across a range of true counts to find index plays.
+
+use crate::analysis::{exact_round_ev, fixed_strategy_round_ev, round_stats};
+use crate::deck::{FiniteShoe, PlayerState};
+use crate::engine::Action;
+use crate::playout::BasicStrategy;
+use crate::progress::Progress;
+use crate::rules::RuleSet;
+use crate::session::BetSizing;
+use crate::solver::{get_best_action, solve_with_playout, SolverConfig, StateStats};
+use tracing::info;
+
+/// Decks in the shoe used for count-conditioned solves when the rules play an infinite deck
+pub const SHOE_DECKS: u8 = 8;
+
+/// Range of true counts solved when searching for index plays
+pub const MIN_TRUE_COUNT: i32 = -6;
+pub const MAX_TRUE_COUNT: i32 = 6;
+
+/// Strategy deviation: `deviation` replaces `basic` once the true count passes `index`
+#[derive(Debug, Clone, Copy)]
+pub struct IndexPlay {
+    pub state: PlayerState,
+    pub basic: Action,
+    pub deviation: Action,
+    pub index: f64,
+    /// Deviation applies at or above the index (otherwise at or below)
+    pub above: bool,
+}
+
+/// Player edge and Kelly-optimal bet at one true count
+#[derive(Debug, Clone, Copy)]
+pub struct KellyBet {
+    pub true_count: i32,
+    /// Expected win per unit bet (negative is a house edge)
+    pub advantage: f64,
+    /// Per-round variance per unit bet
+    pub variance: f64,
+    /// Fraction of bankroll to wager: advantage / variance, zero without an edge
+    pub kelly_fraction: f64,
+}
+
+impl KellyBet {
+    /// Fractional Kelly (e.g. 0.5 for half Kelly) capped at `max_fraction` of bankroll
+    pub fn scaled_fraction(&self, multiplier: f64, max_fraction: f64) -> f64 {
+        (self.kelly_fraction * multiplier).min(max_fraction)
+    }
+}
+
+/// Advantage and Kelly fraction at each solved true count.
+/// Rounds are weighted by the count-adjusted shoe, so richer shoes deal more naturals. The variance is
+/// the solves' second moment, so they have to be plain sampled (`solve_kelly_counts`).
+pub fn kelly_table(rules: &RuleSet, solves: &[(i32, StateStats)]) -> Vec<KellyBet> {
+    solves
+        .iter()
+        .map(|(tc, stats)| {
+            let shoe = count_shoe(rules, *tc as f64);
+            let round = round_stats(rules, stats, &shoe.probabilities(), Some(shoe.remaining() as f64));
+            let variance = round.std_dev * round.std_dev;
+            let kelly_fraction = if round.ev > 0.0 { round.ev / variance } else { 0.0 };
+            KellyBet { true_count: *tc, advantage: round.ev, variance, kelly_fraction }
+        })
+        .collect()
+}
+
+/// Count-based Kelly ramp for `simulate_session`, betting `min_bet` without an edge
+pub fn kelly_ramp(table: &[KellyBet], multiplier: f64, max_fraction: f64, min_bet: f64) -> BetSizing {
+    let ramp = table
+        .iter()
+        .map(|bet| (bet.true_count as f64, bet.scaled_fraction(multiplier, max_fraction)))
+        .collect();
+    BetSizing::Kelly { min_bet, ramp }
+}
+
+/// Solve every state with each hand dealt from a shoe at the given Hi-Lo true count
+pub fn solve_at_true_count(
+    states: &[PlayerState],
+    rules: &RuleSet,
+    true_count: f64,
+    config: &SolverConfig,
+    progress: Progress,
+) -> StateStats {
+    solve_with_playout(states, rules, || count_shoe(rules, true_count), &BasicStrategy, config, progress)
+}
+
+/// Solve every state with the shoe held at each true count in range.
+/// Each count is announced unless `progress` is quiet; the solves themselves run quietly.
+pub fn solve_true_counts(
+    states: &[PlayerState],
+    rules: &RuleSet,
+    config: &SolverConfig,
+    progress: Progress,
+) -> Vec<(i32, StateStats)> {
+    (MIN_TRUE_COUNT..=MAX_TRUE_COUNT)
+        .map(|tc| {
+            if !progress.is_quiet() {
+                info!("Solving at true count {:+}", tc);
+            }
+            (tc, solve_at_true_count(states, rules, tc as f64, config, Progress::Quiet))
+        })
+        .collect()
+}
+
+/// Effect of removal: EV with one card of each rank out of a single deck minus the full deck's, indexed by
+/// `Card::rank` - 2 (2-10, J, Q, K, A), every starting hand played on its full-deck best action.
+pub fn effect_of_removal(rules: &RuleSet) -> [f64; 13] {
+    let deck = FiniteShoe::with_deck_type(1, rules.deck_type);
+    let full = deck.probabilities();
+    let full_ev = exact_round_ev(rules, &full, Some(deck.remaining() as f64));
+    let counts = deck.rank_counts();
+    std::array::from_fn(|idx| {
+        if counts[idx] == 0 { return 0.0; }
+        let mut shoe = FiniteShoe::with_deck_type(1, rules.deck_type);
+        shoe.remove_rank(idx as u8 + 2);
+        fixed_strategy_round_ev(rules, &shoe.probabilities(), Some(shoe.remaining() as f64), &full) - full_ev
+    })
+}
+
+/// `solve_true_counts` with `SolverConfig::plain_sampled`, for `kelly_table`: exact stands and importance
+/// weights leave the EVs unbiased but shrink each hand's second moment, overstating every Kelly fraction
+pub fn solve_kelly_counts(
+    states: &[PlayerState],
+    rules: &RuleSet,
+    config: &SolverConfig,
+    progress: Progress,
+) -> Vec<(i32, StateStats)> {
+    solve_true_counts(states, rules, &config.plain_sampled(), progress)
+}
+
+/// Decks in the shoe that true counts are measured against
+pub fn shoe_decks(rules: &RuleSet) -> u8 {
+    rules.num_decks.unwrap_or(SHOE_DECKS)
+}
+
+/// Shoe of the rules' deck type held at `true_count`
+fn count_shoe(rules: &RuleSet, true_count: f64) -> FiniteShoe {
+    FiniteShoe::with_deck_type(shoe_decks(rules), rules.deck_type).held_at_true_count(true_count)
+}
+
+/// Find where each state's optimal action flips away from its TC 0 play.
+/// Sorted by distance of the index from zero, most frequently used first.
+pub fn find_index_plays(states: &[PlayerState], solves: &[(i32, StateStats)]) -> Vec<IndexPlay> {
+    let Some(zero) = solves.iter().position(|(tc, _)| *tc == 0) else {
+        return Vec::new();
+    };
+
+    let mut plays = Vec::new();
+    for state in states {
+        let Some(actions) = solves[zero].1.get(state) else { continue };
+        let Some((basic, _)) = get_best_action(actions) else { continue };
+
+        let upward: Vec<&(i32, StateStats)> = solves[zero..].iter().collect();
+        if let Some((deviation, index)) = crossover(state, basic, &upward) {
+            plays.push(IndexPlay { state: *state, basic, deviation, index, above: true });
+        }
+        let downward: Vec<&(i32, StateStats)> = solves[..=zero].iter().rev().collect();
+        if let Some((deviation, index)) = crossover(state, basic, &downward) {
+            plays.push(IndexPlay { state: *state, basic, deviation, index, above: false });
+        }
+    }
+
+    plays.sort_by(|a, b| a.index.abs().partial_cmp(&b.index.abs()).unwrap());
+    plays
+}
+
+/// Lowest true count at which splitting 10,10 beats standing, per dealer upcard 2-11, or `None` if it
+/// never does in range. As with index plays, the split must also win one count higher to be reported.
+/// This is the ten pair (two tens of one rank), which can always be split, not the unlike tens
+/// `PlayerState::new_unlike_tens`; with `RuleSet::split_mixed_tens` off the split tens are never resplit.
+pub fn ten_split_counts(solves: &[(i32, StateStats)]) -> Vec<(u8, Option<i32>)> {
+    let split_wins = |stats: &StateStats, upcard: u8| {
+        stats.get(&PlayerState::new(20, upcard, false, true)).is_some_and(|actions| {
+            let split = &actions[Action::Split.index()];
+            split.n > 0 && split.ev() > actions[Action::Stand.index()].ev()
+        })
+    };
+    (2..=11)
+        .map(|upcard| {
+            let flip = solves.windows(2).find(|pair| split_wins(&pair[0].1, upcard) && split_wins(&pair[1].1, upcard));
+            (upcard, flip.map(|pair| pair[0].0))
+        })
+        .collect()
+}
+
+/// Index play of a single state nearest to TC 0, solving only that state across the count
+/// range with `config`. Returns the action below the threshold, the action above it, and the threshold.
+pub fn index_for(state: &PlayerState, rules: &RuleSet, config: &SolverConfig) -> Option<(Action, Action, f64)> {
+    let states = [*state];
+    let solves = solve_true_counts(&states, rules, config, Progress::Quiet);
+    let play = find_index_plays(&states, &solves).into_iter().next()?;
+    if play.above {
+        Some((play.basic, play.deviation, play.index))
+    } else {
+        Some((play.deviation, play.basic, play.index))
+    }
+}
+
+/// Walk away from TC 0 to the first count where another action wins, and interpolate
+/// the EV crossover against the previous count. The flip must still hold one count
+/// further out so sampling noise on near-ties isn't reported as an index.
+fn crossover(state: &PlayerState, basic: Action, solves: &[&(i32, StateStats)]) -> Option<(Action, f64)> {
+    for i in 1..solves.len() {
+        let (tc, stats) = solves[i];
+        let (best, _) = get_best_action(stats.get(state)?)?;
+        if best == basic {
+            continue;
+        }
+        if let Some((_, next)) = solves.get(i + 1) {
+            if get_best_action(next.get(state)?)?.0 != best {
+                continue;
+            }
+        }
+
+        let (prev_tc, prev_stats) = solves[i - 1];
+        let gap = |s: &StateStats| -> Option<f64> {
+            let actions = s.get(state)?;
+            Some(actions[best.index()].ev() - actions[basic.index()].ev())
+        };
+        let (g0, g1) = (gap(prev_stats)?, gap(stats)?);
+        let t = if g1 > g0 { (-g0 / (g1 - g0)).clamp(0.0, 1.0) } else { 1.0 };
+        return Some((best, *prev_tc as f64 + t * (*tc - *prev_tc) as f64));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::generate_all_states;
+
+    #[test]
+    fn kelly_variance_is_the_plain_per_round_variance() {
+        let rules = RuleSet::default();
+        let config = SolverConfig { target_sem: 0.02, seed: Some(1), ..SolverConfig::default() }.plain_sampled();
+        let solved = solve_at_true_count(&generate_all_states(), &rules, 0.0, &config, Progress::Quiet);
+        let bet = kelly_table(&rules, &[(0, solved)])[0];
+        // A round of blackjack has a variance of about 1.3 squared units; exact stands would halve it
+        assert!((1.15..1.45).contains(&bet.variance), "variance {}", bet.variance);
+    }
+
+    #[test]
+    fn index_for_finds_the_classic_hi_lo_indices() {
+        let rules = RuleSet { late_surrender: false, ..RuleSet::default() };
+        let config = SolverConfig { target_sem: 0.005, seed: Some(3), ..SolverConfig::default() };
+
+        // Hard 16 vs 10 stands from a true count of about 0
+        let (below, above, index) = index_for(&PlayerState::new(16, 10, false, false), &rules, &config).unwrap();
+        assert_eq!((below, above), (Action::Hit, Action::Stand));
+        assert!(index.abs() < 2.5, "16 vs 10 index {}", index);
+
+        // 10,10 vs 5 is split from about +5
+        let (below, above, index) = index_for(&PlayerState::new(20, 5, false, true), &rules, &config).unwrap();
+        assert_eq!((below, above), (Action::Stand, Action::Split));
+        assert!((3.5..6.5).contains(&index), "10,10 vs 5 index {}", index);
+    }
+
+    #[test]
+    fn removals_match_the_hi_lo_signs_and_sum_to_about_zero_over_a_deck() {
+        let rules = RuleSet::default();
+        let effects = effect_of_removal(&rules);
+        // 2 through 6 out help the player; every ten and the ace out hurt
+        assert!(effects[..5].iter().all(|&e| e > 0.0), "{:?}", effects);
+        assert!(effects[8..].iter().all(|&e| e < 0.0), "{:?}", effects);
+
+        let counts = FiniteShoe::with_deck_type(1, rules.deck_type).rank_counts();
+        let total: f64 = effects.iter().zip(counts).map(|(e, n)| e * n as f64).sum();
+        // The deal's card-by-card draws and the replayed later decisions leave a small remainder
+        assert!(total.abs() < 0.005, "removals sum to {}", total);
+    }
+}
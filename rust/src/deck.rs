@@ -1,16 +1,57 @@
 //! Deck/Shoe management for Blackjack simulation.
 //! Optimized for speed with fixed-size arrays and fast RNG.
 
+use crate::rules::RuleSet;
 use fastrand::Rng;
+use std::fmt;
+use std::str::FromStr;
 
-/// Maximum cards in a hand (5 cards + safety margin)
-pub const MAX_HAND_SIZE: usize = 12;
+/// Maximum cards in a hand: 21 aces and the card that busts them.
+/// H17 dealers and long playouts can pass a dozen cards on rare draws.
+pub const MAX_HAND_SIZE: usize = 22;
 
-/// Fixed-size hand to avoid heap allocations
+/// Fixed-size hand to avoid heap allocations.
+/// The best total is kept up to date on every push.
 #[derive(Clone, Copy)]
 pub struct Hand {
     cards: [u8; MAX_HAND_SIZE],
     len: u8,
+    /// Best total, counting `soft_aces` aces as 11
+    total: u8,
+    /// Aces still counted as 11
+    soft_aces: u8,
+}
+
+/// Best total of a hand, whether an ace is still counted as 11, and whether it is bust or a natural
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandValue {
+    pub total: u8,
+    pub is_soft: bool,
+    /// Over 21
+    pub is_bust: bool,
+    /// 21 on two cards; a split hand's two-card 21 is marked too, so callers settling one treat it as 21
+    pub is_blackjack: bool,
+}
+
+impl HandValue {
+    /// 17 with an ace still counted as 11, e.g. A,6 or A,A,5
+    #[inline(always)]
+    pub fn is_soft_17(self) -> bool {
+        self.total == 17 && self.is_soft
+    }
+
+    /// 17 with every ace counted as 1, e.g. 10,7 or A,6,K
+    #[inline(always)]
+    pub fn is_hard_17(self) -> bool {
+        self.total == 17 && !self.is_soft
+    }
+
+    /// Whether the dealer stops drawing on this total: above `stands_on`, a hard `stands_on`,
+    /// and a soft `stands_on` unless `hit_soft` (S17 and H17 at the usual threshold of 17)
+    #[inline(always)]
+    pub fn dealer_stands(self, stands_on: u8, hit_soft: bool) -> bool {
+        self.total > stands_on || (self.total == stands_on && !(self.is_soft && hit_soft))
+    }
 }
 
 impl Hand {
@@ -19,15 +60,16 @@ impl Hand {
         Hand {
             cards: [0; MAX_HAND_SIZE],
             len: 0,
+            total: 0,
+            soft_aces: 0,
         }
     }
 
     #[inline(always)]
     pub fn from_cards(c1: u8, c2: u8) -> Self {
         let mut h = Hand::new();
-        h.cards[0] = c1;
-        h.cards[1] = c2;
-        h.len = 2;
+        h.push(c1);
+        h.push(c2);
         h
     }
 
@@ -35,6 +77,25 @@ impl Hand {
     pub fn push(&mut self, card: u8) {
         self.cards[self.len as usize] = card;
         self.len += 1;
+        self.total += card;
+        self.soft_aces += (card == 11) as u8;
+
+        // Convert aces from 11 to 1 as needed; an ace on a soft 21 (A,10 then A) needs two
+        while self.total > 21 && self.soft_aces > 0 {
+            self.total -= 10;
+            self.soft_aces -= 1;
+        }
+    }
+
+    /// Current best total in O(1)
+    #[inline(always)]
+    pub fn value(&self) -> HandValue {
+        HandValue {
+            total: self.total,
+            is_soft: self.soft_aces > 0,
+            is_bust: self.total > 21,
+            is_blackjack: self.len == 2 && self.total == 21,
+        }
     }
 
     #[inline(always)]
@@ -42,6 +103,11 @@ impl Hand {
         self.len as usize
     }
 
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     #[inline(always)]
     pub fn cards(&self) -> &[u8] {
         &self.cards[..self.len as usize]
@@ -52,10 +118,12 @@ impl Hand {
         self.cards[0]
     }
 
+    /// Two cards of the same rank, the only hand that may be split
     #[inline(always)]
-    pub fn second(&self) -> u8 {
-        self.cards[1]
+    pub fn is_pair(&self) -> bool {
+        self.len == 2 && self.cards[0] == self.cards[1]
     }
+
 }
 
 impl Default for Hand {
@@ -64,28 +132,164 @@ impl Default for Hand {
     }
 }
 
+/// Source of cards for the simulation engine
+pub trait Deck {
+    /// Draw the next card value (2-11)
+    fn draw(&mut self) -> u8;
+
+    /// Restore the starting composition before an independent hand
+    #[inline(always)]
+    fn reset(&mut self) {}
+
+    /// Take a card already dealt out of the deck (no effect on an infinite deck)
+    #[inline(always)]
+    fn remove(&mut self, _card: u8) {}
+
+    /// Restart the random stream from `seed` so runs can be reproduced (no effect on a scripted deck)
+    #[inline(always)]
+    fn seed(&mut self, _seed: u64) {}
+
+    /// Consecutive hands (between `reset`s) come in correlated pairs. The engine then records each
+    /// pair's mean as one sample, so `n` counts pairs and the SEM reflects the correlation.
+    #[inline(always)]
+    fn pairs_hands(&self) -> bool {
+        false
+    }
+
+    /// Probability of each card value (indexed by value - 2) when every draw is independent of
+    /// the cards already dealt, as from an infinite deck. The engine then settles stood hands exactly.
+    #[inline(always)]
+    fn card_probs(&self) -> Option<[f64; 10]> {
+        None
+    }
+
+    /// Cards of each value left (indexed by value - 2) when every draw is taken without replacement from
+    /// them, as from a `FiniteShoe`. The engine can then settle stood hands exactly for the cards left.
+    #[inline(always)]
+    fn composition(&self) -> Option<[u16; 10]> {
+        None
+    }
+
+    /// Draw the player's first card (each hand's second draw, after the dealer's hole card) from `proposal`,
+    /// indexed by value - 2, for importance sampling (no effect on decks that always draw their own distribution)
+    #[inline(always)]
+    fn tilt(&mut self, _proposal: &[f64; 10]) {}
+
+    /// Likelihood ratio of the cards drawn since the last `reset`: their chance under the deck's own
+    /// distribution over their chance as drawn. The engine weights each result by it; 1 when untilted.
+    #[inline(always)]
+    fn likelihood(&self) -> f64 {
+        1.0
+    }
+}
+
+/// Draw probabilities of an infinite deck dealing from `lookup`
+fn lookup_probs(lookup: &[u8]) -> [f64; 10] {
+    let mut probs = [0.0; 10];
+    for &card in lookup {
+        probs[(card - 2) as usize] += 1.0 / lookup.len() as f64;
+    }
+    probs
+}
+
+/// Cards decoded per refill of the infinite deck's draw buffer
+const DRAW_BUFFER: usize = 256;
+
+/// Cards decoded from each 64-bit random word (13^8 uses ~30 of the 64 bits)
+const CARDS_PER_WORD: usize = 8;
+
 /// Infinite deck with fast RNG
-/// Uses lookup table for O(1) card drawing
+/// Uses lookup table for O(1) card drawing, decoded in buffered blocks
 pub struct InfiniteDeck {
     rng: Rng,
+    deck_type: DeckType,
+    lookup: &'static [u8],
+    buffer: [u8; DRAW_BUFFER],
+    pos: usize,
 }
 
 // Lookup table: maps random value 0-12 to card value
 // 0-7 -> 2-9, 8-11 -> 10, 12 -> 11 (Ace)
 const CARD_LOOKUP: [u8; 13] = [2, 3, 4, 5, 6, 7, 8, 9, 10, 10, 10, 10, 11];
 
+// Spanish deck lookup: maps random value 0-11 to card value, one fewer ten rank
+const SPANISH_CARD_LOOKUP: [u8; 12] = [2, 3, 4, 5, 6, 7, 8, 9, 10, 10, 10, 11];
+
+/// Rank-to-value lookup table of a deck type
+#[inline(always)]
+fn card_lookup(deck_type: DeckType) -> &'static [u8] {
+    match deck_type {
+        DeckType::Standard => &CARD_LOOKUP,
+        DeckType::Spanish => &SPANISH_CARD_LOOKUP,
+    }
+}
+
 impl InfiniteDeck {
     #[inline(always)]
     pub fn new() -> Self {
+        Self::with_deck_type(DeckType::Standard)
+    }
+
+    /// Infinite deck drawing with the rank frequencies of `deck_type`
+    #[inline(always)]
+    pub fn with_deck_type(deck_type: DeckType) -> Self {
         InfiniteDeck {
             rng: Rng::new(),
+            deck_type,
+            lookup: card_lookup(deck_type),
+            buffer: [0; DRAW_BUFFER],
+            pos: DRAW_BUFFER,
+        }
+    }
+
+    #[inline(always)]
+    pub fn deck_type(&self) -> DeckType {
+        self.deck_type
+    }
+
+    /// Decode a full block of cards in one tight loop.
+    /// Each 64-bit word yields several cards: multiplying by the rank count moves the next
+    /// index into the high half and leaves the unused entropy in the low half.
+    #[inline(never)]
+    fn refill(&mut self) {
+        let ranks = self.lookup.len() as u128;
+        for chunk in self.buffer.chunks_exact_mut(CARDS_PER_WORD) {
+            let mut bits = self.rng.u64(..);
+            for card in chunk {
+                let wide = bits as u128 * ranks;
+                *card = self.lookup[(wide >> 64) as usize];
+                bits = wide as u64;
+            }
         }
+        self.pos = 0;
     }
 
-    /// Draw a random card - O(1) with lookup table
+    /// Draw a card with its rank and suit, for analyses that need more than the value.
+    /// Drawn from the same stream as `draw` but outside its buffer, so the fast path is unchanged.
+    pub fn draw_card(&mut self) -> Card {
+        let ranks = self.deck_type.ranks();
+        Card { rank: ranks[self.rng.usize(..ranks.len())], suit: Suit::ALL[self.rng.usize(..Suit::ALL.len())] }
+    }
+}
+
+impl Deck for InfiniteDeck {
+    /// Draw a random card - O(1) from the pre-decoded buffer
     #[inline(always)]
-    pub fn draw(&mut self) -> u8 {
-        CARD_LOOKUP[self.rng.usize(0..13)]
+    fn draw(&mut self) -> u8 {
+        if self.pos == DRAW_BUFFER { self.refill(); }
+        let card = self.buffer[self.pos];
+        self.pos += 1;
+        card
+    }
+
+    /// Reseed and drop the cards decoded from the old stream
+    fn seed(&mut self, seed: u64) {
+        self.rng.seed(seed);
+        self.pos = DRAW_BUFFER;
+    }
+
+    fn card_probs(&self) -> Option<[f64; 10]> {
+        Some(lookup_probs(self.lookup))
     }
 }
 
@@ -95,80 +299,1132 @@ impl Default for InfiniteDeck {
     }
 }
 
-/// Calculate hand value - optimized with early exit
+/// Ranks remembered per hand for the mirrored replay
+const ANTITHETIC_DRAWS: usize = 32;
+
+/// Infinite deck dealing hands in antithetic pairs.
+/// The first hand of each pair records its rank draws; after the next `reset` the second
+/// hand replays them mirrored (rank `r` becomes `ranks - 1 - r`, so low cards turn into
+/// tens and aces), which makes the pair's results negatively correlated.
+/// Draws past the recorded stream are fresh random ranks.
+pub struct AntitheticDeck {
+    rng: Rng,
+    lookup: &'static [u8],
+    draws: [u8; ANTITHETIC_DRAWS],
+    len: usize,
+    pos: usize,
+    mirror: bool,
+}
+
+impl AntitheticDeck {
+    pub fn new() -> Self {
+        Self::with_deck_type(DeckType::Standard)
+    }
+
+    pub fn with_deck_type(deck_type: DeckType) -> Self {
+        AntitheticDeck {
+            rng: Rng::new(),
+            lookup: card_lookup(deck_type),
+            draws: [0; ANTITHETIC_DRAWS],
+            len: 0,
+            pos: 0,
+            // The first reset starts a recorded hand
+            mirror: true,
+        }
+    }
+}
+
+impl Deck for AntitheticDeck {
+    #[inline(always)]
+    fn draw(&mut self) -> u8 {
+        let ranks = self.lookup.len() as u8;
+        let rank = if self.mirror {
+            let rank = if self.pos < self.len { ranks - 1 - self.draws[self.pos] } else { self.rng.u8(0..ranks) };
+            self.pos += 1;
+            rank
+        } else {
+            let rank = self.rng.u8(0..ranks);
+            if self.len < ANTITHETIC_DRAWS {
+                self.draws[self.len] = rank;
+                self.len += 1;
+            }
+            rank
+        };
+        self.lookup[rank as usize]
+    }
+
+    /// Alternate between recording a hand and replaying it mirrored
+    #[inline(always)]
+    fn reset(&mut self) {
+        if self.mirror { self.len = 0; }
+        self.mirror = !self.mirror;
+        self.pos = 0;
+    }
+
+    /// Reseed; the next hand starts a new pair
+    fn seed(&mut self, seed: u64) {
+        self.rng.seed(seed);
+        self.len = 0;
+        self.pos = 0;
+        self.mirror = true;
+    }
+
+    #[inline(always)]
+    fn pairs_hands(&self) -> bool {
+        true
+    }
+
+    fn card_probs(&self) -> Option<[f64; 10]> {
+        Some(lookup_probs(self.lookup))
+    }
+}
+
+impl Default for AntitheticDeck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Infinite deck for importance sampling. The player's first card (each hand's second draw, after the
+/// dealer's hole card) comes from the proposal set by `tilt`, and `likelihood` carries the ratio that
+/// weights the hand back to the true distribution; every other card is an `InfiniteDeck` draw. A proposal
+/// leaning toward the draws that decide the result (`exact::hit_proposal`) lowers the variance.
+pub struct ImportanceDeck {
+    deck: InfiniteDeck,
+    /// Stream of the tilted draws
+    rng: Rng,
+    /// Cumulative proposal, for inverse-CDF draws
+    proposal_cdf: [f64; 10],
+    /// True over proposal probability of each value
+    ratios: [f64; 10],
+    likelihood: f64,
+    /// Cards drawn since the last `reset`
+    drawn: usize,
+}
+
+impl ImportanceDeck {
+    pub fn new() -> Self {
+        Self::with_deck_type(DeckType::Standard)
+    }
+
+    /// Untilted deck of `deck_type`
+    pub fn with_deck_type(deck_type: DeckType) -> Self {
+        let mut deck = ImportanceDeck {
+            deck: InfiniteDeck::with_deck_type(deck_type),
+            rng: Rng::new(),
+            proposal_cdf: [0.0; 10],
+            ratios: [1.0; 10],
+            likelihood: 1.0,
+            drawn: 0,
+        };
+        deck.tilt(&deck_type.probabilities());
+        deck
+    }
+}
+
+impl Deck for ImportanceDeck {
+    #[inline(always)]
+    fn draw(&mut self) -> u8 {
+        self.drawn += 1;
+        if self.drawn != 2 { return self.deck.draw(); }
+        let u = self.rng.f64();
+        let idx = self.proposal_cdf.iter().position(|&c| u < c).unwrap_or(9);
+        self.likelihood = self.ratios[idx];
+        idx as u8 + 2
+    }
+
+    #[inline(always)]
+    fn reset(&mut self) {
+        self.likelihood = 1.0;
+        self.drawn = 0;
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.deck.seed(seed);
+        self.rng.seed(!seed);
+        self.reset();
+    }
+
+    fn card_probs(&self) -> Option<[f64; 10]> {
+        self.deck.card_probs()
+    }
+
+    /// Every value the deck holds must stay possible under `proposal`, or the estimate is biased
+    fn tilt(&mut self, proposal: &[f64; 10]) {
+        let probs = self.deck.deck_type().probabilities();
+        assert!(
+            probs.iter().zip(proposal).all(|(&p, &q)| p == 0.0 || q > 0.0),
+            "proposal leaves out a value the deck holds"
+        );
+        let total: f64 = proposal.iter().sum();
+        let mut sum = 0.0;
+        for (idx, &q) in proposal.iter().enumerate() {
+            sum += q / total;
+            self.proposal_cdf[idx] = sum;
+            self.ratios[idx] = if q > 0.0 { probs[idx] * total / q } else { 0.0 };
+        }
+        self.proposal_cdf[9] = 1.0;
+    }
+
+    #[inline(always)]
+    fn likelihood(&self) -> f64 {
+        self.likelihood
+    }
+}
+
+impl Default for ImportanceDeck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Draws remembered per round for replay across actions
+const REPLAY_DRAWS: usize = 64;
+
+/// Deck that records one round's draws from `inner` and deals the same stream again to
+/// every action compared in that round (common random numbers). Draws past the recorded
+/// stream come fresh from `inner` and are recorded for the following replays.
+pub struct ReplayDeck<D: Deck> {
+    inner: D,
+    draws: [u8; REPLAY_DRAWS],
+    /// `inner`'s likelihood after each recorded draw
+    likelihoods: [f64; REPLAY_DRAWS],
+    len: usize,
+    pos: usize,
+}
+
+impl<D: Deck> ReplayDeck<D> {
+    pub fn new(inner: D) -> Self {
+        ReplayDeck { inner, draws: [0; REPLAY_DRAWS], likelihoods: [1.0; REPLAY_DRAWS], len: 0, pos: 0 }
+    }
+
+    /// Forget the recorded stream and reset `inner` for a new round
+    #[inline(always)]
+    pub fn start_round(&mut self) {
+        self.inner.reset();
+        self.len = 0;
+        self.pos = 0;
+    }
+
+    /// Deal the recorded stream again from its first card
+    #[inline(always)]
+    pub fn replay(&mut self) {
+        self.pos = 0;
+    }
+
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+}
+
+impl<D: Deck> Deck for ReplayDeck<D> {
+    #[inline(always)]
+    fn draw(&mut self) -> u8 {
+        let card = if self.pos < self.len {
+            self.draws[self.pos]
+        } else {
+            let card = self.inner.draw();
+            if self.len < REPLAY_DRAWS {
+                self.draws[self.len] = card;
+                self.likelihoods[self.len] = self.inner.likelihood();
+                self.len += 1;
+            }
+            card
+        };
+        self.pos += 1;
+        card
+    }
+
+    #[inline(always)]
+    fn reset(&mut self) {
+        self.start_round();
+    }
+
+    #[inline(always)]
+    fn remove(&mut self, card: u8) {
+        self.inner.remove(card);
+    }
+
+    /// Reseed `inner` and forget the recorded stream
+    fn seed(&mut self, seed: u64) {
+        self.inner.seed(seed);
+        self.len = 0;
+        self.pos = 0;
+    }
+
+    #[inline(always)]
+    fn pairs_hands(&self) -> bool {
+        self.inner.pairs_hands()
+    }
+
+    fn card_probs(&self) -> Option<[f64; 10]> {
+        self.inner.card_probs()
+    }
+
+    /// Composition as of the stream dealt since the last `replay`: recorded cards not yet dealt again are
+    /// put back into `inner`'s
+    fn composition(&self) -> Option<[u16; 10]> {
+        let mut counts = self.inner.composition()?;
+        for &card in &self.draws[self.pos.min(self.len)..self.len] {
+            counts[(card - 2) as usize] += 1;
+        }
+        Some(counts)
+    }
+
+    fn tilt(&mut self, proposal: &[f64; 10]) {
+        self.inner.tilt(proposal);
+    }
+
+    /// Likelihood of the stream dealt since the last `replay`, as `inner` recorded it
+    #[inline(always)]
+    fn likelihood(&self) -> f64 {
+        match self.pos {
+            0 => 1.0,
+            pos if pos <= self.len => self.likelihoods[pos - 1],
+            _ => self.inner.likelihood(),
+        }
+    }
+}
+
+/// Deck dealing a fixed sequence of card values, for deterministic replays of the engine.
+/// `simulate_action` draws the dealer hole card first, then player cards, then dealer hits.
+/// Panics if more cards are drawn than were scripted.
+pub struct ScriptedDeck {
+    cards: Vec<u8>,
+    pos: usize,
+}
+
+impl ScriptedDeck {
+    pub fn new(cards: &[u8]) -> Self {
+        ScriptedDeck { cards: cards.to_vec(), pos: 0 }
+    }
+
+    /// Scripted cards not yet drawn
+    pub fn remaining(&self) -> usize {
+        self.cards.len() - self.pos
+    }
+}
+
+impl Deck for ScriptedDeck {
+    fn draw(&mut self) -> u8 {
+        let Some(&card) = self.cards.get(self.pos) else {
+            panic!("scripted deck exhausted after {} cards", self.pos);
+        };
+        self.pos += 1;
+        card
+    }
+}
+
+/// Cards of each value in a single deck, indexed by value - 2 (2-9, 10, A)
+const DECK_COMPOSITION: [u16; 10] = [4, 4, 4, 4, 4, 4, 4, 4, 16, 4];
+
+/// Spanish deck composition: the four 10-spot cards are removed, face cards are kept
+const SPANISH_DECK_COMPOSITION: [u16; 10] = [4, 4, 4, 4, 4, 4, 4, 4, 12, 4];
+
+/// Which cards make up each deck in the shoe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeckType {
+    /// Standard 52-card deck
+    #[default]
+    Standard,
+    /// 48-card Spanish 21 deck without the 10-spot cards. The game's payouts are
+    /// `RuleSet::spanish_bonuses`.
+    Spanish,
+}
+
+impl DeckType {
+    /// Cards of each value in one deck, indexed by value - 2
+    pub fn composition(self) -> [u16; 10] {
+        match self {
+            DeckType::Standard => DECK_COMPOSITION,
+            DeckType::Spanish => SPANISH_DECK_COMPOSITION,
+        }
+    }
+
+    pub fn cards_per_deck(self) -> u32 {
+        self.composition().iter().map(|&c| c as u32).sum()
+    }
+
+    /// Chance of drawing each value from an infinite deck of this type, indexed by value - 2
+    pub fn probabilities(self) -> [f64; 10] {
+        let cards = self.cards_per_deck() as f64;
+        self.composition().map(|c| c as f64 / cards)
+    }
+
+    /// Ten-value cards of each rank (10, J, Q, K) in one deck
+    pub fn ten_ranks(self) -> [u16; 4] {
+        match self {
+            DeckType::Standard => [4, 4, 4, 4],
+            DeckType::Spanish => [0, 4, 4, 4],
+        }
+    }
+
+    /// Card ranks in each suit of a deck, as `Card::rank`
+    pub fn ranks(self) -> &'static [u8] {
+        match self {
+            DeckType::Standard => &[2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14],
+            DeckType::Spanish => &[2, 3, 4, 5, 6, 7, 8, 9, 11, 12, 13, 14],
+        }
+    }
+}
+
+/// Suit of a card; hearts and diamonds are red
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Suit {
+    Hearts,
+    Diamonds,
+    Clubs,
+    Spades,
+}
+
+impl Suit {
+    pub const ALL: [Suit; 4] = [Suit::Hearts, Suit::Diamonds, Suit::Clubs, Suit::Spades];
+
+    #[inline(always)]
+    pub fn is_red(self) -> bool {
+        matches!(self, Suit::Hearts | Suit::Diamonds)
+    }
+}
+
+/// A card with its suit, for side bets and other analyses the value alone can't settle.
+/// The engine deals values only (`Deck::draw`); ranks run 2-10, then J, Q, K as 11-13 and the ace as 14.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Card {
+    pub rank: u8,
+    pub suit: Suit,
+}
+
+impl Card {
+    /// Blackjack value the engine plays the card as (2-11)
+    #[inline(always)]
+    pub fn value(self) -> u8 {
+        rank_value(self.rank)
+    }
+}
+
+/// Blackjack value of a `Card::rank`
 #[inline(always)]
-pub fn hand_value(hand: &Hand) -> (u8, bool) {
-    let cards = hand.cards();
-    let mut total: u16 = 0;
-    let mut aces: u8 = 0;
+pub(crate) fn rank_value(rank: u8) -> u8 {
+    match rank {
+        11..=13 => 10,
+        14 => 11,
+        rank => rank,
+    }
+}
+
+/// e.g. "Qh", "10s", "Ad"
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rank = rank_name(self.rank);
+        let suit = match self.suit {
+            Suit::Hearts => 'h',
+            Suit::Diamonds => 'd',
+            Suit::Clubs => 'c',
+            Suit::Spades => 's',
+        };
+        f.pad(&format!("{}{}", rank, suit))
+    }
+}
+
+/// Shoe of suited cards shuffled and dealt in order, for analyses that need suits (flushes,
+/// suited pairs). Slower than `FiniteShoe`, which tracks only how many of each rank are left.
+pub struct SuitedShoe {
+    rng: Rng,
+    /// Every card of the shoe: dealt cards first, then those left in dealing order
+    cards: Vec<Card>,
+    next: usize,
+}
+
+impl SuitedShoe {
+    pub fn new(num_decks: u8) -> Self {
+        Self::with_deck_type(num_decks, DeckType::Standard)
+    }
+
+    /// Freshly shuffled shoe of `num_decks` decks of `deck_type`
+    pub fn with_deck_type(num_decks: u8, deck_type: DeckType) -> Self {
+        let deck = deck_type.ranks().iter().flat_map(|&rank| Suit::ALL.map(|suit| Card { rank, suit }));
+        let cards: Vec<Card> = (0..num_decks).flat_map(|_| deck.clone()).collect();
+        let mut shoe = SuitedShoe { rng: Rng::new(), cards, next: 0 };
+        shoe.shuffle();
+        shoe
+    }
+
+    /// Deal the next card, reshuffling the whole shoe once it runs out
+    pub fn draw_card(&mut self) -> Card {
+        if self.next == self.cards.len() { self.shuffle(); }
+        self.next += 1;
+        self.cards[self.next - 1]
+    }
+
+    /// Take a specific card out of the shoe (e.g. one already dealt face up). False if none is left.
+    pub fn remove_card(&mut self, card: Card) -> bool {
+        let Some(offset) = self.cards[self.next..].iter().position(|&left| left == card) else { return false };
+        self.cards.swap(self.next, self.next + offset);
+        self.next += 1;
+        true
+    }
+
+    /// Put every card back and shuffle
+    pub fn shuffle(&mut self) {
+        self.rng.shuffle(&mut self.cards);
+        self.next = 0;
+    }
+
+    /// Cards left in the shoe
+    #[inline(always)]
+    pub fn remaining(&self) -> usize {
+        self.cards.len() - self.next
+    }
+
+    /// Cards left in the shoe, in the order they will be dealt
+    pub fn remaining_cards(&self) -> &[Card] {
+        &self.cards[self.next..]
+    }
+}
+
+/// Deals values for the engine. `reset` reshuffles the full shoe, so each hand starts fresh.
+impl Deck for SuitedShoe {
+    #[inline(always)]
+    fn draw(&mut self) -> u8 {
+        self.draw_card().value()
+    }
 
-    for &card in cards {
-        total += card as u16;
-        aces += (card == 11) as u8;
+    fn reset(&mut self) {
+        self.shuffle();
     }
 
-    // Convert aces from 11 to 1 as needed
-    while total > 21 && aces > 0 {
-        total -= 10;
-        aces -= 1;
+    fn remove(&mut self, card: u8) {
+        if let Some(offset) = self.cards[self.next..].iter().position(|left| left.value() == card) {
+            self.cards.swap(self.next, self.next + offset);
+            self.next += 1;
+        }
     }
 
-    (total as u8, aces > 0)
+    fn seed(&mut self, seed: u64) {
+        self.rng.seed(seed);
+    }
 }
 
-/// Check if hand is a natural blackjack
+/// Hi-Lo tag of a card value: 2-6 count +1, 7-9 count 0, tens and aces -1
 #[inline(always)]
-pub fn is_blackjack(hand: &Hand) -> bool {
-    hand.len() == 2 && {
-        let (total, _) = hand_value(hand);
-        total == 21
+pub fn hi_lo_tag(card: u8) -> i32 {
+    match card {
+        2..=6 => 1,
+        10 | 11 => -1,
+        _ => 0,
+    }
+}
+
+/// Finite multi-deck shoe drawn without replacement.
+/// Keeps a Hi-Lo running count of every card taken out of the shoe.
+pub struct FiniteShoe {
+    rng: Rng,
+    num_decks: u8,
+    deck_type: DeckType,
+    counts: [u16; 10],
+    /// Ten-value cards left by rank (10, J, Q, K); they sum to the tens in `counts`
+    tens: [u16; 4],
+    remaining: u32,
+    running_count: i32,
+    // Snapshot restored by `reset`
+    start_counts: [u16; 10],
+    start_tens: [u16; 4],
+    start_remaining: u32,
+    start_running_count: i32,
+    /// Fraction of the shoe dealt before the cut card comes out
+    cut_card: Option<f64>,
+}
+
+impl FiniteShoe {
+    pub fn new(num_decks: u8) -> Self {
+        Self::with_deck_type(num_decks, DeckType::Standard)
+    }
+
+    /// Fresh shoe of `num_decks` decks of `deck_type`
+    pub fn with_deck_type(num_decks: u8, deck_type: DeckType) -> Self {
+        let counts = deck_type.composition().map(|c| c * num_decks as u16);
+        let tens = deck_type.ten_ranks().map(|c| c * num_decks as u16);
+        let remaining = deck_type.cards_per_deck() * num_decks as u32;
+        FiniteShoe {
+            rng: Rng::new(),
+            num_decks,
+            deck_type,
+            counts,
+            tens,
+            remaining,
+            running_count: 0,
+            start_counts: counts,
+            start_tens: tens,
+            start_remaining: remaining,
+            start_running_count: 0,
+            cut_card: None,
+        }
+    }
+
+    /// This shoe with the cut card placed after `penetration` of it (e.g. 0.75) is dealt.
+    /// `shuffle_at_cut_card` then reshuffles once the cut card comes out.
+    pub fn with_cut_card(mut self, penetration: f64) -> Self {
+        self.cut_card = Some(penetration);
+        self
+    }
+
+    /// Penetration the cut card sits at, if one is placed
+    #[inline(always)]
+    pub fn cut_card(&self) -> Option<f64> {
+        self.cut_card
+    }
+
+    /// Shoe whose remaining cards sit at the given Hi-Lo true count.
+    /// Low cards (tens and aces for negative counts) are removed until the count is reached,
+    /// and `reset` returns to that composition rather than a fresh shoe.
+    pub fn at_true_count(num_decks: u8, true_count: f64) -> Self {
+        FiniteShoe::new(num_decks).held_at_true_count(true_count)
+    }
+
+    /// This shoe with cards removed as in `at_true_count`
+    pub fn held_at_true_count(self, true_count: f64) -> Self {
+        let mut shoe = self;
+        let removal: &[u8] = if true_count >= 0.0 { &[2, 3, 4, 5, 6] } else { &[10, 10, 10, 10, 11] };
+
+        let mut i = 0;
+        while (true_count > 0.0 && shoe.true_count() < true_count)
+            || (true_count < 0.0 && shoe.true_count() > true_count)
+        {
+            let card = removal[i % removal.len()];
+            if shoe.counts[(card - 2) as usize] == 0 { break; }
+            shoe.remove(card);
+            i += 1;
+        }
+
+        shoe.start_counts = shoe.counts;
+        shoe.start_tens = shoe.tens;
+        shoe.start_remaining = shoe.remaining;
+        shoe.start_running_count = shoe.running_count;
+        shoe
+    }
+
+    /// Take a specific card out of the shoe (e.g. one already dealt face up).
+    /// A ten comes from whichever ten rank has the most cards left; see `remove_rank`.
+    #[inline(always)]
+    pub fn remove(&mut self, card: u8) {
+        if card == 10 {
+            let (most, _) = self.tens.iter().enumerate().max_by_key(|&(i, &count)| (count, 3 - i)).unwrap();
+            self.remove_rank(10 + most as u8);
+        } else {
+            self.take(card);
+        }
+    }
+
+    /// Take a card of the given `Card::rank` (2-10, 11-13 for J/Q/K, 14 for an ace) out of the shoe
+    #[inline(always)]
+    pub fn remove_rank(&mut self, rank: u8) {
+        if let 10..=13 = rank {
+            let ten = (rank - 10) as usize;
+            if self.tens[ten] == 0 { return; }
+            self.tens[ten] -= 1;
+        }
+        self.take(rank_value(rank));
+    }
+
+    /// Count one card of `card`'s value out of the shoe; the ten ranks are kept by the caller
+    #[inline(always)]
+    fn take(&mut self, card: u8) {
+        let idx = (card - 2) as usize;
+        if self.counts[idx] == 0 { return; }
+        self.counts[idx] -= 1;
+        self.remaining -= 1;
+        self.running_count += hi_lo_tag(card);
+    }
+
+    /// Put every card back and zero the count
+    pub fn shuffle(&mut self) {
+        self.counts = self.deck_type.composition().map(|c| c * self.num_decks as u16);
+        self.tens = self.deck_type.ten_ranks().map(|c| c * self.num_decks as u16);
+        self.remaining = self.shoe_size();
+        self.running_count = 0;
+        self.start_counts = self.counts;
+        self.start_tens = self.tens;
+        self.start_remaining = self.remaining;
+        self.start_running_count = 0;
+    }
+
+    #[inline(always)]
+    pub fn num_decks(&self) -> u8 {
+        self.num_decks
+    }
+
+    #[inline(always)]
+    pub fn deck_type(&self) -> DeckType {
+        self.deck_type
+    }
+
+    /// Cards in the full shoe
+    #[inline(always)]
+    pub fn shoe_size(&self) -> u32 {
+        self.deck_type.cards_per_deck() * self.num_decks as u32
+    }
+
+    /// Cards left in the shoe
+    #[inline(always)]
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// Fraction of the full shoe dealt since the last shuffle
+    #[inline(always)]
+    pub fn penetration(&self) -> f64 {
+        1.0 - self.remaining as f64 / self.shoe_size() as f64
+    }
+
+    /// Shuffle if the cut card has come out, between rounds. Returns whether it did, so callers
+    /// can reset anything kept per shoe; the shoe's own running count restarts at zero.
+    #[inline(always)]
+    pub fn shuffle_at_cut_card(&mut self) -> bool {
+        let due = self.cut_card.is_some_and(|penetration| self.penetration() >= penetration);
+        if due { self.shuffle(); }
+        due
+    }
+
+    #[inline(always)]
+    pub fn decks_remaining(&self) -> f64 {
+        self.remaining as f64 / self.deck_type.cards_per_deck() as f64
+    }
+
+    #[inline(always)]
+    pub fn running_count(&self) -> i32 {
+        self.running_count
+    }
+
+    /// Cards of each value left in the shoe, indexed by value - 2
+    #[inline(always)]
+    pub fn counts(&self) -> [u16; 10] {
+        self.counts
+    }
+
+    /// Cards of each rank left in the shoe, indexed by `Card::rank` - 2 (2-10, J, Q, K, A)
+    pub fn rank_counts(&self) -> [u16; 13] {
+        let mut ranks = [0; 13];
+        ranks[..8].copy_from_slice(&self.counts[..8]);
+        ranks[8..12].copy_from_slice(&self.tens);
+        ranks[12] = self.counts[9];
+        ranks
+    }
+
+    /// Draw a card and return its `Card::rank`, so the ten ranks can be told apart
+    #[inline(always)]
+    pub fn draw_rank(&mut self) -> u8 {
+        if self.remaining == 0 { self.shuffle(); }
+
+        let mut r = self.rng.u32(0..self.remaining);
+        for (idx, &count) in self.counts.iter().enumerate() {
+            if r < count as u32 {
+                let rank = match idx {
+                    8 => 10 + self.ten_rank_at(r),
+                    9 => 14,
+                    _ => idx as u8 + 2,
+                };
+                self.remove_rank(rank);
+                return rank;
+            }
+            r -= count as u32;
+        }
+        unreachable!("remaining card total out of sync with counts")
+    }
+
+    /// Offset from 10 of the ten rank holding the `r`-th ten left in the shoe
+    #[inline(always)]
+    fn ten_rank_at(&self, mut r: u32) -> u8 {
+        for (ten, &count) in self.tens.iter().enumerate() {
+            if r < count as u32 { return ten as u8; }
+            r -= count as u32;
+        }
+        unreachable!("ten count out of sync with ten ranks")
+    }
+
+    /// Chance of drawing each value next, indexed by value - 2
+    pub fn probabilities(&self) -> [f64; 10] {
+        self.counts.map(|count| count as f64 / self.remaining as f64)
+    }
+
+    /// Running count divided by the number of decks left in the shoe
+    #[inline(always)]
+    pub fn true_count(&self) -> f64 {
+        if self.remaining == 0 { 0.0 } else { self.running_count as f64 / self.decks_remaining() }
+    }
+}
+
+impl Deck for FiniteShoe {
+    /// Draw without replacement, weighted by the cards left in the shoe.
+    /// An exhausted shoe is reshuffled before drawing.
+    #[inline(always)]
+    fn draw(&mut self) -> u8 {
+        rank_value(self.draw_rank())
+    }
+
+    #[inline(always)]
+    fn reset(&mut self) {
+        self.counts = self.start_counts;
+        self.tens = self.start_tens;
+        self.remaining = self.start_remaining;
+        self.running_count = self.start_running_count;
+    }
+
+    #[inline(always)]
+    fn remove(&mut self, card: u8) {
+        FiniteShoe::remove(self, card);
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.rng.seed(seed);
+    }
+
+    #[inline(always)]
+    fn composition(&self) -> Option<[u16; 10]> {
+        Some(self.counts)
     }
 }
 
-/// Check if hand is busted
+/// Two cards can be split: equal values, and for tens equal ranks too unless `RuleSet::split_mixed_tens`
 #[inline(always)]
-pub fn is_bust(hand: &Hand) -> bool {
-    let (total, _) = hand_value(hand);
-    total > 21
+pub fn is_splittable(first: Card, second: Card, rules: &RuleSet) -> bool {
+    first.value() == second.value() && (first.rank == second.rank || (first.value() == 10 && rules.split_mixed_tens))
 }
 
+/// Number of distinct solver states (36 hand rows x 10 dealer upcards)
+pub const NUM_STATES: usize = 360;
+
 /// Player state for strategy lookup
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerState {
     pub total: u8,
     pub dealer_upcard: u8,
     pub is_soft: bool,
     pub is_pair: bool,
+    /// Two ten-value cards of different ranks (10,J), split only under `RuleSet::split_mixed_tens`.
+    /// The pair 10,10 is two tens of one rank, which can always be split.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub unlike_tens: bool,
 }
 
 impl PlayerState {
     #[inline(always)]
     pub fn new(total: u8, dealer_upcard: u8, is_soft: bool, is_pair: bool) -> Self {
-        PlayerState { total, dealer_upcard, is_soft, is_pair }
+        PlayerState { total, dealer_upcard, is_soft, is_pair, unlike_tens: false }
+    }
+
+    /// Two ten-value cards of different ranks (10,J) against `dealer_upcard`
+    #[inline(always)]
+    pub fn new_unlike_tens(dealer_upcard: u8) -> Self {
+        PlayerState { unlike_tens: true, ..PlayerState::new(20, dealer_upcard, false, true) }
+    }
+
+    /// Dense index over the generated states: hard 5-21, soft 13-20, pairs 2,2-A,A, then the unlike
+    /// tens, ten dealer upcards per row. `None` for states the solver never generates.
+    #[inline(always)]
+    pub fn index(&self) -> Option<usize> {
+        let row = match (self.is_pair, self.is_soft) {
+            (true, false) if self.unlike_tens && self.total == 20 => 35,
+            _ if self.unlike_tens => return None,
+            (true, true) if self.total == 12 => 25 + 9,
+            (true, false) if (4..=20).contains(&self.total) && self.total.is_multiple_of(2) => 25 + (self.total / 2 - 2) as usize,
+            (false, false) if (5..=21).contains(&self.total) => (self.total - 5) as usize,
+            (false, true) if (13..=20).contains(&self.total) => 17 + (self.total - 13) as usize,
+            _ => return None,
+        };
+        if !(2..=11).contains(&self.dealer_upcard) { return None; }
+        Some(row * 10 + (self.dealer_upcard - 2) as usize)
+    }
+
+    /// Inverse of `index`: the state stored at a dense index below `NUM_STATES`
+    pub fn from_index(idx: usize) -> Option<Self> {
+        if idx >= NUM_STATES { return None; }
+        let (row, dealer_upcard) = (idx / 10, (idx % 10) as u8 + 2);
+        let state = match row {
+            0..=16 => PlayerState::new(row as u8 + 5, dealer_upcard, false, false),
+            17..=24 => PlayerState::new((row - 17) as u8 + 13, dealer_upcard, true, false),
+            34 => PlayerState::new(12, dealer_upcard, true, true),
+            35 => PlayerState::new_unlike_tens(dealer_upcard),
+            _ => PlayerState::new(((row - 25) as u8 + 2) * 2, dealer_upcard, false, true),
+        };
+        Some(state)
+    }
+
+    /// State of a dealt two-card hand. A hand holds values, not ranks, so two tens are the pair 10,10.
+    #[inline(always)]
+    pub fn from_hand(hand: &Hand, dealer_upcard: u8) -> Self {
+        let HandValue { total, is_soft, .. } = hand.value();
+        PlayerState::new(total, dealer_upcard, is_soft, hand.is_pair())
     }
 }
 
-/// Generate starting hand for a state
-#[inline(always)]
-pub fn get_hand_for_state(total: u8, is_soft: bool, is_pair: bool) -> Hand {
-    if is_pair {
-        if is_soft {
-            Hand::from_cards(11, 11) // A,A
+/// Short card name: "A" for an ace, the pip value otherwise
+pub(crate) fn card_name(card: u8) -> String {
+    if card == 11 { "A".to_string() } else { card.to_string() }
+}
+
+/// Short name of a `Card::rank`: "J", "Q", "K", "A" or the pip value
+pub(crate) fn rank_name(rank: u8) -> String {
+    match rank {
+        11 => "J".to_string(),
+        12 => "Q".to_string(),
+        13 => "K".to_string(),
+        14 => "A".to_string(),
+        rank => rank.to_string(),
+    }
+}
+
+/// Card from "A", "T", "J", "Q", "K" or a value 2-11 (11 and "A" are both aces)
+fn parse_card(s: &str) -> Result<u8, String> {
+    match s.trim() {
+        "A" | "a" => Ok(11),
+        "T" | "t" | "J" | "j" | "Q" | "q" | "K" | "k" => Ok(10),
+        raw => raw
+            .parse::<u8>()
+            .ok()
+            .filter(|card| (2..=11).contains(card))
+            .ok_or_else(|| format!("invalid card '{}'", raw)),
+    }
+}
+
+/// `Card::rank` of a ten-value card named "10", "T", "J", "Q" or "K" (lower case), `None` for any other card
+fn ten_rank(name: &str) -> Option<u8> {
+    match name.trim() {
+        "10" | "t" => Some(10),
+        "j" => Some(11),
+        "q" => Some(12),
+        "k" => Some(13),
+        _ => None,
+    }
+}
+
+/// Comma-separated cards, e.g. "A,7", "T,6" or "2,3,5"
+impl FromStr for Hand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut hand = Hand::new();
+        for card in s.split(',') {
+            if hand.len() == MAX_HAND_SIZE { return Err(format!("more than {} cards in '{}'", MAX_HAND_SIZE, s)); }
+            hand.push(parse_card(card)?);
+        }
+        Ok(hand)
+    }
+}
+
+/// Strategy-chart names: "Hard 16 vs 10", "A,7 vs A", "8,8 vs 6" and "10,J vs 6" for the unlike tens
+impl fmt::Display for PlayerState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let dealer = card_name(self.dealer_upcard);
+        let name = if self.unlike_tens {
+            format!("10,J vs {}", dealer)
+        } else if self.is_pair {
+            let card = if self.is_soft { 11 } else { self.total / 2 };
+            format!("{},{} vs {}", card_name(card), card_name(card), dealer)
+        } else if self.is_soft {
+            format!("A,{} vs {}", self.total - 11, dealer)
         } else {
-            let card = total / 2;
-            Hand::from_cards(card, card)
-        }
-    } else if is_soft {
-        Hand::from_cards(11, total - 11)
-    } else if total <= 11 {
-        if total >= 4 {
-            Hand::from_cards(2, total - 2)
+            format!("Hard {} vs {}", self.total, dealer)
+        };
+        f.pad(&name)
+    }
+}
+
+impl PlayerState {
+    /// State of a hand typed the way a player would against `dealer_upcard`.
+    /// Cards ("A,7", "8,8", "T,6", "2,3,5") give the exact soft/pair status. A bare total is hard
+    /// ("16", or "h16" / "Hard 16"), and soft with an "s" / "Soft" prefix ("s18", "Soft 18");
+    /// hard 4 and soft 12 can only be 2,2 and A,A, so they are read as those pairs. Any other
+    /// pair must be given as cards: "20" is hard 20, "T,T" or "K,K" the pair of tens, "10,J" or "K,Q"
+    /// the unlike tens.
+    pub fn parse_hand(hand: &str, dealer_upcard: u8) -> Result<Self, String> {
+        let hand = hand.trim();
+        let lower = hand.to_ascii_lowercase();
+        let total = |raw: &str| raw.trim().parse::<u8>().map_err(|_| format!("invalid hand '{}'", hand));
+
+        let unlike_tens = lower
+            .split_once(',')
+            .and_then(|(first, second)| ten_rank(first).zip(ten_rank(second)))
+            .is_some_and(|(first, second)| first != second);
+
+        let state = if unlike_tens {
+            PlayerState::new_unlike_tens(dealer_upcard)
+        } else if hand.contains(',') {
+            PlayerState::from_hand(&hand.parse::<Hand>()?, dealer_upcard)
+        } else if let Some(raw) = lower.strip_prefix("soft").or_else(|| lower.strip_prefix('s')) {
+            let total = total(raw)?;
+            PlayerState::new(total, dealer_upcard, true, total == 12)
         } else {
-            Hand::from_cards(total, 0) // edge case
+            let total = total(lower.strip_prefix("hard").or_else(|| lower.strip_prefix('h')).unwrap_or(&lower))?;
+            PlayerState::new(total, dealer_upcard, false, total == 4)
+        };
+
+        if state.index().is_none() { return Err(format!("'{}' vs {} is not a solved state", hand, card_name(dealer_upcard))); }
+        Ok(state)
+    }
+}
+
+/// Parses "<hand> vs <upcard>": the `Display` form, or any hand `parse_hand` accepts.
+/// Only states in the solver's state space are accepted.
+impl FromStr for PlayerState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (hand, dealer) = s.split_once(" vs ").ok_or_else(|| format!("expected '<hand> vs <upcard>', got '{}'", s))?;
+        PlayerState::parse_hand(hand, parse_card(dealer)?)
+    }
+}
+
+/// Generate starting hand for a state: two cards where two can make the total, otherwise
+/// three: a hard 20 that isn't a pair (10,8,2), a hard 21 (10,9,2) or a soft 21 that isn't a
+/// natural (A,5,5).
+///
+/// Panics on a total no hand of that kind can hold: hard below 4, soft below 12, above 21,
+/// or a pair other than A,A and 2,2 through 10,10.
+#[inline(always)]
+pub fn get_hand_for_state(total: u8, is_soft: bool, is_pair: bool) -> Hand {
+    match (is_pair, is_soft, total) {
+        (true, true, 12) | (false, true, 12) => Hand::from_cards(11, 11),
+        (true, false, 4..=20) if total.is_multiple_of(2) => Hand::from_cards(total / 2, total / 2),
+        (false, true, 13..=20) => Hand::from_cards(11, total - 11),
+        (false, true, 21) => {
+            let mut hand = Hand::from_cards(11, 5);
+            hand.push(5);
+            hand
         }
-    } else if total <= 19 {
-        Hand::from_cards(10, total - 10)
-    } else {
-        Hand::from_cards(10, 10) // 20
+        (false, false, 4..=11) => Hand::from_cards(2, total - 2),
+        (false, false, 12..=19) => Hand::from_cards(10, total - 10),
+        (false, false, 20) => {
+            let mut hand = Hand::from_cards(10, 8);
+            hand.push(2);
+            hand
+        }
+        (false, false, 21) => {
+            let mut hand = Hand::from_cards(10, 9);
+            hand.push(2);
+            hand
+        }
+        _ => panic!("no hand is total {} (soft: {}, pair: {})", total, is_soft, is_pair),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::generate_all_states;
+
+    #[test]
+    fn every_state_round_trips_through_its_name() {
+        for state in generate_all_states() {
+            let name = state.to_string();
+            assert_eq!(name.parse::<PlayerState>(), Ok(state), "{}", name);
+        }
+    }
+
+    #[test]
+    fn every_state_deals_a_hand_of_its_total_softness_and_pairing() {
+        let states = generate_all_states();
+        assert_eq!(states.len(), NUM_STATES);
+        for state in states {
+            let hand = get_hand_for_state(state.total, state.is_soft, state.is_pair);
+            let value = hand.value();
+            assert_eq!((value.total, value.is_soft), (state.total, state.is_soft), "{} dealt {:?}", state, hand.cards());
+            assert_eq!(hand.is_pair(), state.is_pair, "{} dealt {:?}", state, hand.cards());
+            assert!(!value.is_bust && !value.is_blackjack, "{} dealt {:?}", state, hand.cards());
+        }
+    }
+
+    #[test]
+    fn incremental_values_match_a_recount_of_the_cards() {
+        let mut rng = Rng::with_seed(596);
+        for _ in 0..10_000 {
+            let mut hand = Hand::from_cards(rng.u8(2..=11), rng.u8(2..=11));
+            loop {
+                let cards = hand.cards();
+                let hard: u8 = cards.iter().map(|&card| if card == 11 { 1 } else { card }).sum();
+                let soft = cards.contains(&11) && hard + 10 <= 21;
+                let total = if soft { hard + 10 } else { hard };
+                let expected = HandValue {
+                    total,
+                    is_soft: soft,
+                    is_bust: total > 21,
+                    is_blackjack: cards.len() == 2 && total == 21,
+                };
+                assert_eq!(hand.value(), expected, "{:?}", cards);
+                if expected.is_bust || hand.len() == MAX_HAND_SIZE { break; }
+                hand.push(rng.u8(2..=11));
+            }
+        }
+    }
+
+    #[test]
+    fn an_eight_deck_shoe_holds_128_tens_across_four_ranks() {
+        let shoe = FiniteShoe::new(8);
+        let ranks = shoe.rank_counts();
+        assert_eq!(ranks[8..12], [32, 32, 32, 32]);
+        assert_eq!(ranks[8..12].iter().sum::<u16>(), 128);
+        assert_eq!(shoe.counts()[8], 128);
+        assert_eq!(ranks.iter().map(|&n| n as u32).sum::<u32>(), 416);
+
+        // Spanish 21 removes the 10-spots and keeps the court cards
+        let spanish = FiniteShoe::with_deck_type(8, DeckType::Spanish);
+        assert_eq!(spanish.rank_counts()[8..12], [0, 32, 32, 32]);
+    }
+
+    #[test]
+    fn dealing_2_5_k_a_nets_a_zero_count() {
+        let mut shoe = FiniteShoe::new(1);
+        shoe.remove_rank(2);
+        shoe.remove_rank(5);
+        assert_eq!(shoe.running_count(), 2);
+        assert!((shoe.true_count() - 2.0 * 52.0 / 50.0).abs() < 1e-12, "{}", shoe.true_count());
+
+        shoe.remove_rank(13);
+        shoe.remove_rank(14);
+        assert_eq!(shoe.running_count(), 0);
+        assert_eq!(shoe.true_count(), 0.0);
+        assert_eq!(shoe.remaining(), 48);
+    }
+
+    #[test]
+    fn unlike_tens_are_their_own_state() {
+        let unlike = PlayerState::new_unlike_tens(6);
+        let pair = PlayerState::new(20, 6, false, true);
+        assert_eq!(unlike.to_string(), "10,J vs 6");
+        assert_eq!(PlayerState::parse_hand("K,Q", 6), Ok(unlike));
+        assert_eq!(PlayerState::parse_hand("10,J", 6), Ok(unlike));
+        assert_eq!(PlayerState::parse_hand("K,K", 6), Ok(pair));
+        assert_eq!(PlayerState::parse_hand("T,10", 6), Ok(pair));
+
+        assert_eq!(unlike.index(), Some(354));
+        assert_ne!(unlike.index(), pair.index());
+        assert_eq!(PlayerState::from_index(354), Some(unlike));
+        assert_eq!(PlayerState { unlike_tens: true, ..PlayerState::new(18, 6, false, true) }.index(), None);
+    }
+
+    #[test]
+    fn states_display_as_chart_names() {
+        assert_eq!(PlayerState::new(16, 10, false, false).to_string(), "Hard 16 vs 10");
+        assert_eq!(PlayerState::new(18, 9, true, false).to_string(), "A,7 vs 9");
+        assert_eq!(PlayerState::new(16, 6, false, true).to_string(), "8,8 vs 6");
+        assert_eq!(PlayerState::new(12, 6, true, true).to_string(), "A,A vs 6");
+        assert_eq!(PlayerState::new(20, 11, false, true).to_string(), "10,10 vs A");
+        assert_eq!(PlayerState::new(12, 11, false, false).to_string(), "Hard 12 vs A");
+        // Padding applies to the whole name, as in the chart columns
+        assert_eq!(format!("{:<16}|", PlayerState::new(19, 11, true, false)), "A,8 vs A        |");
+    }
+
+    #[test]
+    fn parse_hand_reads_cards_and_totals() {
+        let parse = |hand| PlayerState::parse_hand(hand, 6);
+        assert_eq!(parse("A,7"), Ok(PlayerState::new(18, 6, true, false)));
+        assert_eq!(parse("8,8"), Ok(PlayerState::new(16, 6, false, true)));
+        assert_eq!(parse("T,6"), Ok(PlayerState::new(16, 6, false, false)));
+        assert_eq!(parse("16"), Ok(PlayerState::new(16, 6, false, false)));
+        assert_eq!(parse("s18"), parse("A,7"));
+        assert_eq!(parse("Soft 12"), parse("A,A"));
+        assert!(parse("22").is_err());
+        assert!(parse("X,7").is_err());
     }
 }
@@ -1,10 +1,18 @@
 //! Monte Carlo Blackjack simulation engine.
 //! Optimized for speed with inlined functions and no heap allocations.
 
-use crate::deck::{hand_value, is_blackjack, is_bust, get_hand_for_state, Hand, InfiniteDeck, PlayerState};
+use crate::deck::{
+    get_hand_for_state, Deck, Hand, HandValue, InfiniteDeck, PlayerState, ReplayDeck, NUM_STATES,
+};
+use crate::exact;
+use crate::playout::{BasicStrategy, PlayoutStrategy};
+use crate::rules::RuleSet;
+use std::fmt;
+use std::str::FromStr;
 
 /// Possible player actions
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Action {
     Hit,
     Stand,
@@ -13,7 +21,20 @@ pub enum Action {
     Surrender,
 }
 
+/// Number of distinct actions
+pub const NUM_ACTIONS: usize = 5;
+
 impl Action {
+    /// Every action, ordered by `index`
+    pub const ALL: [Action; NUM_ACTIONS] = [Action::Hit, Action::Stand, Action::Double, Action::Split, Action::Surrender];
+
+    /// Dense index for per-action arrays
+    #[inline(always)]
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// One-letter code used in the compact charts; parses back like the full name
     #[inline(always)]
     pub fn symbol(&self) -> &'static str {
         match self {
@@ -25,34 +46,93 @@ impl Action {
         }
     }
 
-    pub fn valid_actions(is_pair: bool) -> &'static [Action] {
-        if is_pair {
-            &[Action::Hit, Action::Stand, Action::Double, Action::Surrender, Action::Split]
-        } else {
-            &[Action::Hit, Action::Stand, Action::Double, Action::Surrender]
+    /// Whether `rules` offer this action on the starting hand `state`: Double only on the totals
+    /// `RuleSet::double_on` allows, Split only on pairs (the unlike tens under `RuleSet::split_mixed_tens`),
+    /// Surrender only against upcards it is offered against
+    #[inline(always)]
+    pub fn is_allowed(&self, state: &PlayerState, rules: &RuleSet) -> bool {
+        match self {
+            Action::Hit | Action::Stand => true,
+            Action::Double => rules.double_on.allows(state.total, state.is_soft),
+            Action::Split => state.is_pair && (!state.unlike_tens || rules.split_mixed_tens),
+            Action::Surrender => rules.surrender_allowed_vs(state.dealer_upcard),
         }
     }
+
+    /// Actions `rules` offer on the starting hand `state`, in `index` order (see `is_allowed`)
+    pub fn valid_actions<'r>(state: &PlayerState, rules: &'r RuleSet) -> impl Iterator<Item = Action> + 'r {
+        let state = *state;
+        Action::ALL.into_iter().filter(move |action| action.is_allowed(&state, rules))
+    }
+}
+
+/// Full action name: "Hit", "Stand", "Double", "Split" or "Surrender"
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Action::Hit => "Hit",
+            Action::Stand => "Stand",
+            Action::Double => "Double",
+            Action::Split => "Split",
+            Action::Surrender => "Surrender",
+        };
+        f.pad(name)
+    }
 }
 
-/// Statistics for a single action
-#[derive(Debug, Clone, Default)]
+/// Parses the full name or the chart symbol, ignoring case
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        Action::ALL
+            .into_iter()
+            .find(|action| s.eq_ignore_ascii_case(action.symbol()) || s.eq_ignore_ascii_case(&action.to_string()))
+            .ok_or_else(|| format!("invalid action '{}'", s))
+    }
+}
+
+/// Statistics for a single action.
+/// Sums are Kahan-compensated so low-order bits survive millions of hands.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ActionStats {
     pub n: u64,
     pub sum_x: f64,
     pub sum_x_squared: f64,
+    /// Rounding error not yet folded into `sum_x` / `sum_x_squared`
+    pub(crate) sum_x_error: f64,
+    pub(crate) sum_x_squared_error: f64,
+}
+
+/// Add `x` to `sum`, carrying the rounding error in `error` (Kahan summation)
+#[inline(always)]
+fn compensated_add(sum: &mut f64, error: &mut f64, x: f64) {
+    let y = x - *error;
+    let t = *sum + y;
+    *error = (t - *sum) - y;
+    *sum = t;
 }
 
 impl ActionStats {
     #[inline(always)]
     pub fn new() -> Self {
-        ActionStats { n: 0, sum_x: 0.0, sum_x_squared: 0.0 }
+        ActionStats::default()
     }
 
     #[inline(always)]
     pub fn update(&mut self, result: f64) {
         self.n += 1;
-        self.sum_x += result;
-        self.sum_x_squared += result * result;
+        compensated_add(&mut self.sum_x, &mut self.sum_x_error, result);
+        compensated_add(&mut self.sum_x_squared, &mut self.sum_x_squared_error, result * result);
+    }
+
+    /// Record a result drawn with likelihood ratio `weight` (`Deck::likelihood`). The weighted result
+    /// is an unbiased sample of the untilted one, so `ev` and `sem` keep their meaning.
+    #[inline(always)]
+    pub fn update_weighted(&mut self, result: f64, weight: f64) {
+        self.update(weight * result);
     }
 
     #[inline(always)]
@@ -74,50 +154,254 @@ impl ActionStats {
     #[inline(always)]
     pub fn merge(&mut self, other: &ActionStats) {
         self.n += other.n;
-        self.sum_x += other.sum_x;
-        self.sum_x_squared += other.sum_x_squared;
+        compensated_add(&mut self.sum_x, &mut self.sum_x_error, other.sum_x - other.sum_x_error);
+        compensated_add(
+            &mut self.sum_x_squared, &mut self.sum_x_squared_error, other.sum_x_squared - other.sum_x_squared_error,
+        );
+    }
+}
+
+/// How a round ended for the player, judged by its net result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Push,
+    /// A winning natural, paid at `RuleSet::blackjack_payout`
+    Blackjack,
+}
+
+impl Outcome {
+    /// Outcome of a round that started from `initial_hand` and returned `result`.
+    /// A split that wins one hand and loses the other nets out to a push.
+    #[inline(always)]
+    pub fn of(initial_hand: &Hand, result: f64) -> Outcome {
+        if result > 0.0 {
+            if initial_hand.value().is_blackjack { Outcome::Blackjack } else { Outcome::Win }
+        } else if result < 0.0 {
+            Outcome::Loss
+        } else {
+            Outcome::Push
+        }
+    }
+}
+
+/// How many simulated rounds ended in each `Outcome`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OutcomeCounts {
+    pub wins: u64,
+    pub losses: u64,
+    pub pushes: u64,
+    pub blackjacks: u64,
+}
+
+impl OutcomeCounts {
+    #[inline(always)]
+    pub fn record(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Win => self.wins += 1,
+            Outcome::Loss => self.losses += 1,
+            Outcome::Push => self.pushes += 1,
+            Outcome::Blackjack => self.blackjacks += 1,
+        }
+    }
+
+    pub fn merge(&mut self, other: &OutcomeCounts) {
+        self.wins += other.wins;
+        self.losses += other.losses;
+        self.pushes += other.pushes;
+        self.blackjacks += other.blackjacks;
+    }
+
+    pub fn rounds(&self) -> u64 {
+        self.wins + self.losses + self.pushes + self.blackjacks
+    }
+
+    /// Fraction of the rounds that ended in `outcome`
+    pub fn fraction(&self, outcome: Outcome) -> f64 {
+        let count = match outcome {
+            Outcome::Win => self.wins,
+            Outcome::Loss => self.losses,
+            Outcome::Push => self.pushes,
+            Outcome::Blackjack => self.blackjacks,
+        };
+        if self.rounds() == 0 { 0.0 } else { count as f64 / self.rounds() as f64 }
+    }
+}
+
+/// Most hands one split can end as: the pair resplit up to three times (`RuleSet::max_splits`)
+pub const MAX_SPLIT_HANDS: usize = 4;
+
+/// How many simulated splits ended as each number of hands, 2 up to `MAX_SPLIT_HANDS`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SplitHandCounts {
+    /// Splits by hands played, indexed by hands - 2
+    pub counts: [u64; MAX_SPLIT_HANDS - 1],
+}
+
+impl SplitHandCounts {
+    #[inline(always)]
+    pub fn record(&mut self, hands: u32) {
+        self.counts[hands as usize - 2] += 1;
+    }
+
+    pub fn merge(&mut self, other: &SplitHandCounts) {
+        self.counts.iter_mut().zip(other.counts).for_each(|(count, other)| *count += other);
+    }
+
+    pub fn splits(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Fraction of the splits that ended as `hands` hands
+    pub fn fraction(&self, hands: usize) -> f64 {
+        if self.splits() == 0 || !(2..=MAX_SPLIT_HANDS).contains(&hands) { return 0.0; }
+        self.counts[hands - 2] as f64 / self.splits() as f64
+    }
+
+    /// Average hands per split, i.e. the initial bets a split puts out before any double
+    pub fn mean_hands(&self) -> f64 {
+        let hands: u64 = self.counts.iter().enumerate().map(|(idx, &count)| (idx as u64 + 2) * count).sum();
+        if self.splits() == 0 { 0.0 } else { hands as f64 / self.splits() as f64 }
+    }
+}
+
+/// Exact stand EV of every total (`exact::stand_evs`), indexed by dealer upcard - 2
+type StandEvTable = [[f64; 22]; 10];
+
+/// Slots of the shoe stand cache, a power of two. Both tables come to about 13 MB per engine, allocated
+/// zeroed so only the slots a solve touches are paged in.
+const SHOE_STAND_CACHE: usize = 1 << 16;
+
+/// Unseen cards by value and the dealer upcard; an upcard of 0 marks an empty slot
+type ShoeKey = ([u16; 10], u8);
+
+/// Exact stand EV of every total (`exact::shoe_stand_evs`) by the unseen cards and dealer upcard.
+/// Direct-mapped and allocated once: each composition hashes to one slot and replaces what was there,
+/// so memory stays bounded and a lookup never allocates.
+struct ShoeStandCache {
+    keys: Vec<ShoeKey>,
+    evs: Vec<[f64; 22]>,
+}
+
+impl ShoeStandCache {
+    fn new() -> Self {
+        ShoeStandCache { keys: vec![([0; 10], 0); SHOE_STAND_CACHE], evs: vec![[0.0; 22]; SHOE_STAND_CACHE] }
+    }
+
+    /// Stand EVs cached for `key`, computing them with `compute` on a miss
+    #[inline(always)]
+    fn get_or_insert(&mut self, key: ShoeKey, compute: impl FnOnce() -> [f64; 22]) -> &[f64; 22] {
+        // FNV-1a over the counts and upcard, folded to the slot bits
+        let mut hash = 0xcbf2_9ce4_8422_2325u64 ^ key.1 as u64;
+        for &count in &key.0 {
+            hash = (hash ^ count as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+        let slot = (hash ^ (hash >> 32)) as usize & (SHOE_STAND_CACHE - 1);
+        if self.keys[slot] != key {
+            self.evs[slot] = compute();
+            self.keys[slot] = key;
+        }
+        &self.evs[slot]
     }
 }
 
 /// Blackjack simulation engine - zero heap allocations in hot path
-pub struct BlackjackEngine {
-    deck: InfiniteDeck,
+pub struct BlackjackEngine<D: Deck = InfiniteDeck, P: PlayoutStrategy = BasicStrategy> {
+    deck: D,
+    rules: RuleSet,
+    playout: P,
+    /// Control variate for stood hands, when the deck draws independently (`Deck::card_probs`)
+    stand_evs: Option<StandEvTable>,
+    /// The same for a finite shoe (`Deck::composition`)
+    shoe_stands: Option<ShoeStandCache>,
+    /// Split hands are being played. Each plays its own dealer hand out of the shoe, so the next one's
+    /// composition is rarely seen twice and they skip `shoe_stands`.
+    splitting: bool,
+    /// Hands the last split ended as, 0 before any
+    split_hands: u32,
 }
 
 impl BlackjackEngine {
     #[inline(always)]
     pub fn new() -> Self {
-        BlackjackEngine { deck: InfiniteDeck::new() }
+        BlackjackEngine::with_rules(InfiniteDeck::new(), RuleSet::default())
     }
+}
 
-    /// Dealer plays according to S17 rules
+impl<D: Deck> BlackjackEngine<D> {
+    /// Engine drawing from any card source (e.g. a count-adjusted `FiniteShoe`)
     #[inline(always)]
-    fn dealer_play(&mut self, hand: &mut Hand) {
-        loop {
-            let (total, _) = hand_value(hand);
-            if total >= 17 { break; }
-            hand.push(self.deck.draw());
+    pub fn with_deck(deck: D) -> Self {
+        Self::with_rules(deck, RuleSet::default())
+    }
+
+    #[inline(always)]
+    pub fn with_rules(deck: D, rules: RuleSet) -> Self {
+        BlackjackEngine::with_playout(deck, rules, BasicStrategy)
+    }
+}
+
+impl<D: Deck, P: PlayoutStrategy> BlackjackEngine<D, P> {
+    /// Engine finishing hands with `playout` instead of the basic thresholds
+    #[inline(always)]
+    pub fn with_playout(deck: D, rules: RuleSet, playout: P) -> Self {
+        // Exposed hole cards and Spanish 21 bonuses settle hands in ways the exact stand EVs leave out
+        let stand_evs = deck
+            .card_probs()
+            .filter(|_| !(rules.double_exposure || rules.spanish_bonuses))
+            .map(|probs| std::array::from_fn(|i| exact::stand_evs(&rules, i as u8 + 2, &probs)));
+        BlackjackEngine { deck, rules, playout, stand_evs, shoe_stands: None, splitting: false, split_hands: 0 }
+    }
+
+    /// Settle stood hands from a finite shoe (`Deck::composition`) at their exact EV for the cards still
+    /// unseen, as `with_playout` does on an infinite deck. The player's cards don't depend on the hole
+    /// card, so the estimate stays unbiased. Each composition met is solved once and cached, which pays
+    /// off when the same starting hands are dealt over and over, as in a solve. Split hands still play
+    /// the dealer out: Stand becomes exact, and Hit and Double lose the dealer's share of their variance.
+    pub fn with_exact_shoe_stands(mut self) -> Self {
+        let settles_exactly = !(self.rules.double_exposure || self.rules.spanish_bonuses);
+        if self.stand_evs.is_none() && settles_exactly && self.deck.composition().is_some() {
+            self.shoe_stands = Some(ShoeStandCache::new());
         }
+        self
+    }
+
+    /// Play the dealer out against every stood hand, even when exact stand EVs are available.
+    /// The mean is the same, but each result keeps the dealer's share of the per-hand variance.
+    pub fn without_exact_stands(mut self) -> Self {
+        self.stand_evs = None;
+        self.shoe_stands = None;
+        self
     }
 
-    /// Play hand after hitting
     #[inline(always)]
-    fn play_hand_hit(&mut self, hand: &mut Hand, dealer_upcard: u8, dealer_hole: u8) -> f64 {
-        hand.push(self.deck.draw());
-        if is_bust(hand) { return -1.0; }
+    pub fn deck(&self) -> &D {
+        &self.deck
+    }
 
-        // Continue with basic strategy
-        loop {
-            let (total, is_soft) = hand_value(hand);
-            if total >= 17 { break; }
-            if is_soft && total >= 18 { break; }
-            if !is_soft && total >= 12 && dealer_upcard <= 6 { break; }
+    #[inline(always)]
+    pub fn deck_mut(&mut self) -> &mut D {
+        &mut self.deck
+    }
 
+    /// Dealer draws to `RuleSet::dealer_stands_on`, hitting a soft total there under `hit_soft_17`
+    #[inline(always)]
+    pub fn dealer_play(&mut self, hand: &mut Hand) {
+        while !hand.value().dealer_stands(self.rules.dealer_stands_on, self.rules.hit_soft_17) {
             hand.push(self.deck.draw());
-            if is_bust(hand) { return -1.0; }
         }
+    }
 
-        self.resolve_vs_dealer(hand, dealer_upcard, dealer_hole)
+    /// Play hand after hitting
+    #[inline(always)]
+    fn play_hand_hit(&mut self, hand: &mut Hand, dealer_upcard: u8, dealer_hole: u8) -> f64 {
+        hand.push(self.deck.draw());
+        if hand.value().is_bust { return -1.0; }
+        if self.is_charlie(hand) { return self.charlie_result(dealer_upcard, dealer_hole); }
+        self.play_out(hand, dealer_upcard, dealer_hole)
     }
 
     /// Play hand after standing
@@ -126,105 +410,205 @@ impl BlackjackEngine {
         self.resolve_vs_dealer(hand, dealer_upcard, dealer_hole)
     }
 
-    /// Play hand after doubling
+    /// Play hand after doubling for `double_fraction` of the original bet
     #[inline(always)]
     fn play_hand_double(&mut self, hand: &mut Hand, dealer_upcard: u8, dealer_hole: u8) -> f64 {
+        let stake = 1.0 + self.rules.double_fraction;
         hand.push(self.deck.draw());
-        if is_bust(hand) { return -2.0; }
-        self.resolve_vs_dealer(hand, dealer_upcard, dealer_hole) * 2.0
+        if hand.value().is_bust { return -stake; }
+        if self.is_charlie(hand) { return self.charlie_result(dealer_upcard, dealer_hole) * stake; }
+        // Bonuses are not paid on a doubled hand
+        self.resolve_vs_dealer(hand, dealer_upcard, dealer_hole).min(1.0) * stake
     }
 
-    /// Play hand after splitting
+    /// Play hand after splitting. Only pairs reach here: `valid_actions` offers
+    /// Split for pair states alone, and `simulate_action` asserts it in debug builds.
+    ///
+    /// Each split hand's second card settles its first decision in a fixed order: a matching card is
+    /// resplit while `RuleSet::max_splits` allows another hand, otherwise `play_out` doubles under DAS
+    /// where the playout asks for it, otherwise hits or stands. Split aces take one card each.
     #[inline(always)]
     fn play_hand_split(&mut self, split_card: u8, dealer_upcard: u8, dealer_hole: u8) -> f64 {
         let is_aces = split_card == 11;
+        let max_hands = if is_aces { 2 } else { self.rules.max_splits as u32 + 1 };
+        let mut hands = 2;
+        // Hands still holding only the split card
+        let mut waiting = 2;
         let mut total_result = 0.0;
+        self.splitting = true;
 
-        for _ in 0..2 {
-            let mut hand = Hand::from_cards(split_card, self.deck.draw());
+        while waiting > 0 {
+            waiting -= 1;
+            let card = self.deck.draw();
+            if self.resplits(split_card, card) && hands < max_hands {
+                hands += 1;
+                waiting += 2;
+                continue;
+            }
+            let mut hand = Hand::from_cards(split_card, card);
 
             let result = if is_aces {
                 self.resolve_vs_dealer(&hand, dealer_upcard, dealer_hole)
             } else {
-                self.play_split_hand(&mut hand, dealer_upcard, dealer_hole)
+                self.play_out(&mut hand, dealer_upcard, dealer_hole)
             };
             total_result += result;
         }
+        self.splitting = false;
+        self.split_hands = hands;
 
         total_result
     }
 
-    /// Play a single split hand with basic strategy (DAS allowed)
+    /// A split hand's second card splits it again: values match, and tens only with `RuleSet::split_mixed_tens`
+    /// (drawn values don't say whether a ten is the split rank, see `deck::is_splittable`)
     #[inline(always)]
-    fn play_split_hand(&mut self, hand: &mut Hand, dealer_upcard: u8, dealer_hole: u8) -> f64 {
-        let (total, is_soft) = hand_value(hand);
+    fn resplits(&self, split_card: u8, card: u8) -> bool {
+        card == split_card && (split_card != 10 || self.rules.split_mixed_tens)
+    }
 
-        // Check for DAS
-        if hand.len() == 2 {
-            let should_double = if !is_soft {
-                matches!(total, 9 | 10 | 11)
+    /// Finish a hand with the playout strategy and settle it
+    #[inline(always)]
+    fn play_out(&mut self, hand: &mut Hand, dealer_upcard: u8, dealer_hole: u8) -> f64 {
+        loop {
+            let action = if self.rules.double_exposure {
+                self.playout.next_action_exposed(hand, dealer_upcard, dealer_hole)
             } else {
-                matches!(total, 16 | 17 | 18)
+                self.playout.next_action(hand, dealer_upcard)
             };
-
-            if should_double {
-                hand.push(self.deck.draw());
-                if is_bust(hand) { return -2.0; }
-                return self.resolve_vs_dealer(hand, dealer_upcard, dealer_hole) * 2.0;
+            match action {
+                Action::Stand => break,
+                // Otherwise the hand hits, as a double past the first decision can't be placed
+                Action::Double if self.can_double_after_split(hand) => {
+                    return self.play_hand_double(hand, dealer_upcard, dealer_hole);
+                }
+                _ => {
+                    hand.push(self.deck.draw());
+                    if hand.value().is_bust { return -1.0; }
+                    if self.is_charlie(hand) { return self.charlie_result(dealer_upcard, dealer_hole); }
+                }
             }
         }
 
-        // Hit until threshold
-        loop {
-            let (total, is_soft) = hand_value(hand);
-            if is_soft && total >= 18 { break; }
-            if !is_soft {
-                if total >= 17 { break; }
-                if total >= 12 && dealer_upcard <= 6 { break; }
-            }
+        self.resolve_vs_dealer(hand, dealer_upcard, dealer_hole)
+    }
 
-            hand.push(self.deck.draw());
-            if is_bust(hand) { return -1.0; }
-        }
+    /// Hand in `play_out` may be doubled: only a split hand still on its two cards (never after a hit;
+    /// hit hands reach `play_out` with three), under DAS, on a total `RuleSet::double_on` allows
+    #[inline(always)]
+    fn can_double_after_split(&self, hand: &Hand) -> bool {
+        let HandValue { total, is_soft, .. } = hand.value();
+        hand.len() == 2 && self.rules.double_after_split && self.rules.double_on.allows(total, is_soft)
+    }
 
-        self.resolve_vs_dealer(hand, dealer_upcard, dealer_hole)
+    /// Non-busted hand has reached the Charlie card count and wins outright
+    #[inline(always)]
+    fn is_charlie(&self, hand: &Hand) -> bool {
+        self.rules.charlie.is_some_and(|cards| hand.len() >= cards as usize)
+    }
+
+    /// Result of a Charlie per unit staked: a win, unless the dealer has a natural. That only reaches here
+    /// under full ENHC, where it beats the Charlie like any other hand (see `resolve_vs_dealer`).
+    #[inline(always)]
+    fn charlie_result(&self, dealer_upcard: u8, dealer_hole: u8) -> f64 {
+        if Hand::from_cards(dealer_upcard, dealer_hole).value().is_blackjack { -1.0 } else { 1.0 }
     }
 
-    /// Resolve player hand vs dealer (ENHC rules)
+    /// Resolve player hand vs dealer. A dealer natural only reaches here under full ENHC (no OBO),
+    /// where it beats every hand for the full (doubled or split) wager. Under double exposure ties lose.
+    /// With Spanish 21 bonuses any other 21 wins outright, at its bonus payout. A dealer 22 pushes under `push_22`.
+    ///
+    /// When the deck draws independently, the hand is settled at its exact stand EV instead. That is the
+    /// control variate "realized minus exact stand result" with coefficient 1: the dealer's draws don't
+    /// depend on how the player reached the total, so the estimate stays unbiased while the dealer's
+    /// share of the variance drops out of every stand-terminating Stand, Hit, Double and Split.
+    /// `with_exact_shoe_stands` does the same on a finite shoe.
     #[inline(always)]
     fn resolve_vs_dealer(&mut self, player_hand: &Hand, dealer_upcard: u8, dealer_hole: u8) -> f64 {
-        let (player_total, _) = hand_value(player_hand);
+        let player_total = player_hand.value().total;
+        if let Some(stand_evs) = &self.stand_evs {
+            return stand_evs[(dealer_upcard - 2) as usize][player_total as usize];
+        }
+        if !self.splitting {
+            if let Some(stand_evs) = self.shoe_stand_evs(dealer_upcard, dealer_hole) {
+                return stand_evs[player_total as usize];
+            }
+        }
 
         // Check dealer blackjack (ENHC)
         let dealer_hand = Hand::from_cards(dealer_upcard, dealer_hole);
-        if is_blackjack(&dealer_hand) { return -1.0; }
+        if dealer_hand.value().is_blackjack { return -1.0; }
+        if self.rules.spanish_bonuses && player_total == 21 { return spanish_21_payout(player_hand); }
 
         // Dealer plays out
         let mut dealer = dealer_hand;
         self.dealer_play(&mut dealer);
-        let (dealer_total, _) = hand_value(&dealer);
+        let HandValue { total: dealer_total, is_bust: dealer_bust, .. } = dealer.value();
 
-        if is_bust(&dealer) {
-            1.0
-        } else if player_total > dealer_total {
+        if self.rules.push_22 && dealer_total == 22 {
+            0.0
+        } else if dealer_bust || player_total > dealer_total {
             1.0
-        } else if player_total < dealer_total {
+        } else if player_total < dealer_total || self.rules.double_exposure {
             -1.0
         } else {
             0.0
         }
     }
 
+    /// Exact stand EVs for the cards left in the shoe, counting the hole card the player hasn't seen
+    #[inline(always)]
+    fn shoe_stand_evs(&mut self, dealer_upcard: u8, dealer_hole: u8) -> Option<&[f64; 22]> {
+        let cache = self.shoe_stands.as_mut()?;
+        let mut counts = self.deck.composition()?;
+        counts[(dealer_hole - 2) as usize] += 1;
+        let rules = &self.rules;
+        Some(cache.get_or_insert((counts, dealer_upcard), || exact::shoe_stand_evs(rules, dealer_upcard, &counts)))
+    }
+
     /// Simulate a single hand with given action
     #[inline(always)]
     pub fn simulate_action(&mut self, initial_hand: &Hand, dealer_upcard: u8, action: Action) -> f64 {
         let dealer_hole = self.deck.draw();
+        self.simulate_action_against(initial_hand, dealer_upcard, dealer_hole, action)
+    }
 
+    /// `simulate_action`, along with how the round ended. Settling at exact stand EVs blurs
+    /// wins, losses and pushes together, so the outcome is only meaningful `without_exact_stands`.
+    #[inline(always)]
+    pub fn simulate_action_detailed(
+        &mut self,
+        initial_hand: &Hand,
+        dealer_upcard: u8,
+        action: Action,
+    ) -> (f64, Outcome) {
+        let result = self.simulate_action(initial_hand, dealer_upcard, action);
+        (result, Outcome::of(initial_hand, result))
+    }
+
+    /// `simulate_action` against a hole card already dealt, e.g. face up under double exposure
+    #[inline(always)]
+    pub fn simulate_action_against(
+        &mut self,
+        initial_hand: &Hand,
+        dealer_upcard: u8,
+        dealer_hole: u8,
+        action: Action,
+    ) -> f64 {
         // Check player blackjack
-        if initial_hand.len() == 2 && is_blackjack(initial_hand) {
+        if initial_hand.value().is_blackjack {
             let dealer = Hand::from_cards(dealer_upcard, dealer_hole);
-            if is_blackjack(&dealer) { return 0.0; }
-            return 1.5;
+            if dealer.value().is_blackjack { return if self.rules.double_exposure { -1.0 } else { 0.0 }; }
+            return self.rules.blackjack_payout;
+        }
+
+        // Early surrender is settled before the dealer's hand is looked at
+        if action == Action::Surrender && self.rules.early_surrender { return -0.5; }
+
+        // Peek: a dealer natural ends the round before any double or split is placed.
+        // OBO refunds whatever was added to the bet, which settles the same.
+        if self.rules.original_bets_only() && Hand::from_cards(dealer_upcard, dealer_hole).value().is_blackjack {
+            return -1.0;
         }
 
         match action {
@@ -238,25 +622,149 @@ impl BlackjackEngine {
                 self.play_hand_double(&mut hand, dealer_upcard, dealer_hole)
             }
             Action::Split => {
+                debug_assert!(initial_hand.is_pair(), "split of a non-pair hand {:?}", initial_hand.cards());
                 let split_card = initial_hand.first();
                 self.play_hand_split(split_card, dealer_upcard, dealer_hole)
             }
             Action::Surrender => {
                 let dealer = Hand::from_cards(dealer_upcard, dealer_hole);
-                if is_blackjack(&dealer) { -1.0 } else { -0.5 }
+                if dealer.value().is_blackjack { -1.0 } else { -0.5 }
             }
         }
     }
 
-    /// Simulate a batch of hands
+    /// Simulate a batch of hands. Antithetic pairs (`Deck::pairs_hands`) are recorded as one sample each,
+    /// and a hand left over at the end of an odd batch alone. Each result is weighted by `Deck::likelihood`.
     #[inline]
     pub fn simulate_batch(&mut self, state: &PlayerState, action: Action, batch_size: u32) -> ActionStats {
         let initial_hand = get_hand_for_state(state.total, state.is_soft, state.is_pair);
         let mut stats = ActionStats::new();
+        let paired = self.deck.pairs_hands();
+        let mut first = 0.0;
 
-        for _ in 0..batch_size {
+        for hand in 0..batch_size {
+            self.deck.reset();
+            // The dealt cards are no longer in a finite shoe
+            for &card in initial_hand.cards() {
+                self.deck.remove(card);
+            }
+            self.deck.remove(state.dealer_upcard);
             let result = self.simulate_action(&initial_hand, state.dealer_upcard, action);
-            stats.update(result);
+            let weight = self.deck.likelihood();
+            match (paired, hand % 2) {
+                (false, _) => stats.update_weighted(result, weight),
+                (true, 0) => first = weight * result,
+                _ => stats.update((first + weight * result) / 2.0),
+            }
+        }
+        if paired && batch_size % 2 == 1 { stats.update(first); }
+
+        stats
+    }
+
+    /// Split the pair `state` for `rounds` rounds, counting how many hands each split ended as. Rounds a
+    /// dealer natural ends before the split (peek or OBO) are left out.
+    pub fn simulate_split_hands(&mut self, state: &PlayerState, rounds: u32) -> SplitHandCounts {
+        let initial_hand = get_hand_for_state(state.total, state.is_soft, state.is_pair);
+        let mut counts = SplitHandCounts::default();
+        for _ in 0..rounds {
+            self.deck.reset();
+            for &card in initial_hand.cards() {
+                self.deck.remove(card);
+            }
+            self.deck.remove(state.dealer_upcard);
+            self.split_hands = 0;
+            self.simulate_action(&initial_hand, state.dealer_upcard, Action::Split);
+            if self.split_hands > 0 { counts.record(self.split_hands); }
+        }
+        counts
+    }
+
+    /// Play `rounds` rounds of `state` with `action`, counting how each ended (see `simulate_action_detailed`)
+    pub fn simulate_outcomes(&mut self, state: &PlayerState, action: Action, rounds: u32) -> OutcomeCounts {
+        let initial_hand = get_hand_for_state(state.total, state.is_soft, state.is_pair);
+        let mut counts = OutcomeCounts::default();
+        for _ in 0..rounds {
+            self.deck.reset();
+            for &card in initial_hand.cards() {
+                self.deck.remove(card);
+            }
+            self.deck.remove(state.dealer_upcard);
+            counts.record(self.simulate_action_detailed(&initial_hand, state.dealer_upcard, action).1);
+        }
+        counts
+    }
+}
+
+impl<D: Deck, P: PlayoutStrategy> BlackjackEngine<ReplayDeck<D>, P> {
+    /// Simulate a batch of rounds in which every action in `actions` is dealt the same
+    /// cards, so differences between their EVs are measured with much less noise.
+    /// Antithetic pairs and likelihood weights are recorded as in `simulate_batch`.
+    #[inline]
+    pub fn simulate_batch_common(
+        &mut self,
+        state: &PlayerState,
+        actions: &[Action],
+        batch_size: u32,
+    ) -> [ActionStats; NUM_ACTIONS] {
+        let initial_hand = get_hand_for_state(state.total, state.is_soft, state.is_pair);
+        let mut stats: [ActionStats; NUM_ACTIONS] = Default::default();
+        let paired = self.deck.pairs_hands();
+        let mut first = [0.0; NUM_ACTIONS];
+
+        for round in 0..batch_size {
+            self.deck.start_round();
+            for &card in initial_hand.cards() {
+                self.deck.remove(card);
+            }
+            self.deck.remove(state.dealer_upcard);
+
+            for &action in actions {
+                self.deck.replay();
+                let result = self.simulate_action(&initial_hand, state.dealer_upcard, action);
+                let weight = self.deck.likelihood();
+                let idx = action.index();
+                match (paired, round % 2) {
+                    (false, _) => stats[idx].update_weighted(result, weight),
+                    (true, 0) => first[idx] = weight * result,
+                    _ => stats[idx].update((first[idx] + weight * result) / 2.0),
+                }
+            }
+        }
+        if paired && batch_size % 2 == 1 {
+            for &action in actions {
+                stats[action.index()].update(first[action.index()]);
+            }
+        }
+
+        stats
+    }
+
+    /// `simulate_batch_common` with the dealer's hole card dealt face up too (double exposure)
+    #[inline]
+    pub fn simulate_batch_exposed(
+        &mut self,
+        state: &PlayerState,
+        dealer_hole: u8,
+        actions: &[Action],
+        batch_size: u32,
+    ) -> [ActionStats; NUM_ACTIONS] {
+        let initial_hand = get_hand_for_state(state.total, state.is_soft, state.is_pair);
+        let mut stats: [ActionStats; NUM_ACTIONS] = Default::default();
+
+        for _ in 0..batch_size {
+            self.deck.start_round();
+            for &card in initial_hand.cards() {
+                self.deck.remove(card);
+            }
+            self.deck.remove(state.dealer_upcard);
+            self.deck.remove(dealer_hole);
+
+            for &action in actions {
+                self.deck.replay();
+                let result = self.simulate_action_against(&initial_hand, state.dealer_upcard, dealer_hole, action);
+                stats[action.index()].update(result);
+            }
         }
 
         stats
@@ -267,9 +775,26 @@ impl Default for BlackjackEngine {
     fn default() -> Self { Self::new() }
 }
 
+/// Payout of a winning non-natural 21 under Spanish 21 bonuses
+#[inline(always)]
+pub fn spanish_21_payout(hand: &Hand) -> f64 {
+    let mut cards = [0u8; 3];
+    if hand.len() == 3 {
+        cards.copy_from_slice(hand.cards());
+        cards.sort_unstable();
+    }
+    match hand.len() {
+        5 => 1.5,
+        6 => 2.0,
+        n if n >= 7 => 3.0,
+        3 if cards == [6, 7, 8] || cards == [7, 7, 7] => 1.5,
+        _ => 1.0,
+    }
+}
+
 /// Generate all possible player states
 pub fn generate_all_states() -> Vec<PlayerState> {
-    let mut states = Vec::with_capacity(350);
+    let mut states = Vec::with_capacity(NUM_STATES);
 
     // Hard totals: 5-21
     for total in 5..=21 {
@@ -294,5 +819,289 @@ pub fn generate_all_states() -> Vec<PlayerState> {
         }
     }
 
+    // Unlike tens: 10,J
+    for dealer in 2..=11 {
+        states.push(PlayerState::new_unlike_tens(dealer));
+    }
+
     states
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::{DeckType, ScriptedDeck};
+    use crate::playout::ExposedStrategy;
+    use crate::rules::DoubleOn;
+
+    /// Five-card Charlie engine under full ENHC dealing `cards` in order, the dealer's hole card first
+    fn charlie_engine(cards: &[u8]) -> BlackjackEngine<ScriptedDeck> {
+        let rules = RuleSet { charlie: Some(5), peek: false, obo: false, ..RuleSet::default() };
+        scripted(rules, cards)
+    }
+
+    /// Engine under `rules` dealing `cards` in order, the dealer's hole card first
+    fn scripted(rules: RuleSet, cards: &[u8]) -> BlackjackEngine<ScriptedDeck> {
+        BlackjackEngine::with_rules(ScriptedDeck::new(cards), rules)
+    }
+
+    #[test]
+    fn actions_parse_in_any_case_from_distinct_one_letter_symbols() {
+        for action in Action::ALL {
+            assert_eq!(action.symbol().len(), 1, "{}", action);
+            assert_eq!(action.symbol().to_ascii_lowercase().parse(), Ok(action));
+            assert_eq!(format!(" {} ", action.to_string().to_ascii_uppercase()).parse(), Ok(action));
+        }
+        let mut symbols: Vec<&str> = Action::ALL.iter().map(Action::symbol).collect();
+        symbols.sort_unstable();
+        symbols.dedup();
+        assert_eq!(symbols.len(), Action::ALL.len());
+        assert!("".parse::<Action>().is_err());
+        assert!("HS".parse::<Action>().is_err());
+    }
+
+    #[test]
+    fn unlike_tens_split_only_when_mixed_tens_may() {
+        let pair = PlayerState::new(20, 6, false, true);
+        let unlike = PlayerState::new_unlike_tens(6);
+        for split_mixed_tens in [false, true] {
+            let rules = RuleSet { split_mixed_tens, ..RuleSet::default() };
+            assert!(Action::Split.is_allowed(&pair, &rules));
+            assert_eq!(Action::Split.is_allowed(&unlike, &rules), split_mixed_tens);
+            let split = exact::action_evs(&rules, &unlike, &rules.deck_type.probabilities())[Action::Split.index()];
+            assert_eq!(split.is_some(), split_mixed_tens);
+        }
+        assert!(generate_all_states().contains(&unlike));
+        assert_eq!(generate_all_states().len(), NUM_STATES);
+    }
+
+    #[test]
+    fn valid_actions_drop_what_the_rules_forbid() {
+        let actions = |rules: &RuleSet, state| Action::valid_actions(&state, rules).collect::<Vec<_>>();
+        let eleven = PlayerState::new(11, 6, false, false);
+        let sixteen = PlayerState::new(16, 10, false, false);
+        let eights = PlayerState::new(16, 10, false, true);
+        let all = RuleSet::default();
+        assert_eq!(actions(&all, eights), Action::ALL);
+        assert_eq!(actions(&all, sixteen), [Action::Hit, Action::Stand, Action::Double, Action::Surrender]);
+
+        // No DAS leaves the starting hand's actions alone; it only bars doubling the split hands
+        let no_das = RuleSet { double_after_split: false, ..RuleSet::default() };
+        assert_eq!(actions(&no_das, eights), Action::ALL);
+
+        let no_surrender = RuleSet { late_surrender: false, ..RuleSet::default() };
+        assert_eq!(actions(&no_surrender, eights), [Action::Hit, Action::Stand, Action::Double, Action::Split]);
+        assert_eq!(actions(&no_surrender, sixteen), [Action::Hit, Action::Stand, Action::Double]);
+
+        let no_double = RuleSet { double_on: DoubleOn::Never, late_surrender: false, ..RuleSet::default() };
+        assert_eq!(actions(&no_double, eleven), [Action::Hit, Action::Stand]);
+        assert_eq!(actions(&no_double, eights), [Action::Hit, Action::Stand, Action::Split]);
+        let reno = RuleSet { double_on: DoubleOn::NineToEleven, ..no_double };
+        assert_eq!(actions(&reno, eleven), [Action::Hit, Action::Stand, Action::Double]);
+        assert_eq!(actions(&reno, sixteen), [Action::Hit, Action::Stand]);
+    }
+
+    #[test]
+    fn a_split_11_is_doubled_only_under_das() {
+        // 4,4 splits; 4,7 doubles into 21 under DAS and otherwise hits to it, 4,10 stands, and the dealer's
+        // 10,6 draws a ten and busts against each hand
+        for (double_after_split, ev) in [(true, 3.0), (false, 2.0)] {
+            let rules = RuleSet { double_after_split, ..RuleSet::default() };
+            let mut engine = scripted(rules, &[10, 7, 10, 10, 10, 10]);
+            let split = engine.simulate_action(&Hand::from_cards(4, 4), 6, Action::Split);
+            assert_eq!(split, ev, "DAS {}", double_after_split);
+            assert_eq!(engine.deck().remaining(), 0);
+        }
+    }
+
+    #[test]
+    fn surrender_is_offered_only_against_the_surrender_vs_upcards() {
+        let rules = RuleSet { surrender_vs: Some(vec![9, 10, 11]), ..RuleSet::default() };
+        let without = RuleSet { late_surrender: false, ..rules.clone() };
+        for upcard in 2..=11 {
+            let state = PlayerState::new(16, upcard, false, false);
+            let offered = |rules| Action::valid_actions(&state, rules).any(|action| action == Action::Surrender);
+            assert_eq!(offered(&rules), upcard >= 9, "vs {}", upcard);
+            assert!(!offered(&without), "vs {}", upcard);
+        }
+    }
+
+    #[test]
+    fn every_action_round_trips_through_display_and_from_str() {
+        let names: Vec<String> = Action::ALL.iter().map(Action::to_string).collect();
+        assert_eq!(names, ["Hit", "Stand", "Double", "Split", "Surrender"]);
+        for action in Action::ALL {
+            assert_eq!(action.to_string().parse::<Action>(), Ok(action));
+            assert_eq!(action.symbol().parse::<Action>(), Ok(action));
+        }
+        assert!("Fold".parse::<Action>().is_err());
+    }
+
+    #[test]
+    fn compensated_sums_match_the_exact_sum() {
+        const N: u64 = 10_000_000;
+        let x = 0.1;
+        let mut naive = 0.0;
+        let mut stats = ActionStats::new();
+        let mut merged = ActionStats::new();
+        let mut batch = ActionStats::new();
+        for i in 1..=N {
+            naive += x;
+            stats.update(x);
+            batch.update(x);
+            if i % 10_000 == 0 {
+                merged.merge(&batch);
+                batch = ActionStats::new();
+            }
+        }
+        // N copies of x sum exactly to N * x, which the product rounds only once
+        let (sum, sum_squared) = (N as f64 * x, N as f64 * (x * x));
+        let ulp = |value: f64| value * f64::EPSILON;
+        assert!((naive - sum).abs() > 1e4 * ulp(sum), "naive sum {} did not drift from {}", naive, sum);
+        for stats in [&stats, &merged] {
+            assert_eq!(stats.n, N);
+            assert!((stats.sum_x - sum).abs() <= ulp(sum), "sum {} vs {}", stats.sum_x, sum);
+            assert!((stats.sum_x_squared - sum_squared).abs() <= ulp(sum_squared), "{}", stats.sum_x_squared);
+        }
+    }
+
+    #[test]
+    fn shoe_stand_cache_computes_each_composition_once() {
+        let rules = RuleSet::default();
+        let mut cache = ShoeStandCache::new();
+        let mut counts = [32u16; 10];
+        counts[8] = 128;
+        let exact = exact::shoe_stand_evs(&rules, 10, &counts);
+        assert_eq!(cache.get_or_insert((counts, 10), || exact), &exact);
+        assert_eq!(cache.get_or_insert((counts, 10), || unreachable!("cached")), &exact);
+
+        // Another upcard is another entry
+        let vs_six = *cache.get_or_insert((counts, 6), || exact::shoe_stand_evs(&rules, 6, &counts));
+        assert_ne!(vs_six, exact);
+    }
+
+    #[test]
+    fn a_stood_20_beats_a_dealer_17() {
+        // 10,7 stands on 17
+        let mut engine = scripted(RuleSet::default(), &[7]);
+        assert_eq!(engine.simulate_action(&Hand::from_cards(10, 10), 10, Action::Stand), 1.0);
+        assert_eq!(engine.deck().remaining(), 0);
+    }
+
+    #[test]
+    fn a_dealer_bust_pays_a_stood_12() {
+        // 6,10 hits a ten to 26
+        let mut engine = scripted(RuleSet::default(), &[10, 10]);
+        assert_eq!(engine.simulate_action(&Hand::from_cards(10, 2), 6, Action::Stand), 1.0);
+        assert_eq!(engine.deck().remaining(), 0);
+    }
+
+    #[test]
+    fn a_dealer_natural_takes_the_whole_double_only_under_full_enhc() {
+        let enhc = RuleSet { peek: false, obo: false, ..RuleSet::default() };
+        // 5,6 doubles to 21 against A with a ten in the hole
+        let mut engine = scripted(enhc.clone(), &[10, 10]);
+        assert_eq!(engine.simulate_action(&Hand::from_cards(5, 6), 11, Action::Double), -2.0);
+        for rules in [RuleSet { obo: true, ..enhc.clone() }, RuleSet { peek: true, ..enhc }] {
+            let mut engine = scripted(rules, &[10, 10]);
+            assert_eq!(engine.simulate_action(&Hand::from_cards(5, 6), 11, Action::Double), -1.0);
+        }
+    }
+
+    #[test]
+    fn a_dealer_natural_takes_both_split_hands_only_under_full_enhc() {
+        let enhc = RuleSet { peek: false, obo: false, ..RuleSet::default() };
+        // 8,8 splits to 8,10 and 8,9, both standing, against 10 with an ace in the hole
+        let mut engine = scripted(enhc.clone(), &[11, 10, 9]);
+        assert_eq!(engine.simulate_action(&Hand::from_cards(8, 8), 10, Action::Split), -2.0);
+        assert_eq!(engine.deck().remaining(), 0);
+        let mut engine = scripted(RuleSet { obo: true, ..enhc }, &[11, 10, 9]);
+        assert_eq!(engine.simulate_action(&Hand::from_cards(8, 8), 10, Action::Split), -1.0);
+    }
+
+    #[test]
+    fn a_five_card_21_pays_the_spanish_bonus() {
+        // 2,3 hits 4, 5 and 7 to a five-card 21 against 10,7
+        let cards = [7, 4, 5, 7];
+        let spanish = RuleSet { deck_type: DeckType::Spanish, spanish_bonuses: true, ..RuleSet::default() };
+        let mut engine = scripted(spanish.clone(), &cards);
+        assert_eq!(engine.simulate_action(&Hand::from_cards(2, 3), 10, Action::Hit), 1.5);
+        assert_eq!(engine.deck().remaining(), 0);
+
+        // Without the bonuses it is an even-money win over the dealer's 17
+        let mut engine = scripted(RuleSet { spanish_bonuses: false, ..spanish }, &cards);
+        assert_eq!(engine.simulate_action(&Hand::from_cards(2, 3), 10, Action::Hit), 1.0);
+    }
+
+    #[test]
+    fn a_dealer_22_pushes_only_under_push_22() {
+        // 10,8 stands against 6,10 drawing a 6 to 22
+        let mut engine = scripted(RuleSet { push_22: true, ..RuleSet::default() }, &[10, 6]);
+        assert_eq!(engine.simulate_action(&Hand::from_cards(10, 8), 6, Action::Stand), 0.0);
+        let mut engine = scripted(RuleSet { push_22: false, ..RuleSet::default() }, &[10, 6]);
+        assert_eq!(engine.simulate_action(&Hand::from_cards(10, 8), 6, Action::Stand), 1.0);
+    }
+
+    #[test]
+    fn double_exposure_stands_17_against_a_visible_16() {
+        let rules = RuleSet { double_exposure: true, ..RuleSet::default() };
+        let playout = ExposedStrategy { hit_soft_17: rules.hit_soft_17, dealer_stands_on: rules.dealer_stands_on };
+        // 10,2 hits a 5 to 17 and stands on it against the dealer's 10,6, which draws a ten and busts
+        let mut engine = BlackjackEngine::with_playout(ScriptedDeck::new(&[5, 10]), rules, playout);
+        assert_eq!(engine.simulate_action_against(&Hand::from_cards(10, 2), 10, 6, Action::Hit), 1.0);
+        assert_eq!(engine.deck().remaining(), 0);
+    }
+
+    #[test]
+    fn the_dealer_hits_soft_17_only_under_h17() {
+        // Dealer hand, H17, total after playing it out with a 4 to draw
+        let cases: [(&[u8], bool, u8); 6] = [
+            (&[11, 6], false, 17),
+            (&[11, 6], true, 21),
+            (&[11, 11, 5], false, 17),
+            (&[11, 11, 5], true, 21),
+            (&[10, 7], false, 17),
+            (&[10, 7], true, 17),
+        ];
+        for (cards, hit_soft_17, total) in cases {
+            let mut engine = scripted(RuleSet { hit_soft_17, ..RuleSet::default() }, &[4]);
+            let mut hand = Hand::from_cards(cards[0], cards[1]);
+            cards[2..].iter().for_each(|&card| hand.push(card));
+            engine.dealer_play(&mut hand);
+            assert_eq!(hand.value().total, total, "{:?} with H17 {}", cards, hit_soft_17);
+            assert_eq!(engine.deck().remaining(), usize::from(total == 17), "{:?}", cards);
+        }
+    }
+
+    #[test]
+    fn a_split_hand_that_has_hit_cannot_double() {
+        let rules = RuleSet { double_after_split: true, ..RuleSet::default() };
+        // 4,4 splits; 4,2 hits a 5 to a three-card 11, which would be doubled on two cards, and hits a ten
+        // to 21. 4,10 stands, and against each hand the dealer's 10,6 draws a ten and busts: two single wins.
+        let mut engine = scripted(rules, &[10, 2, 5, 10, 10, 10, 10]);
+        assert_eq!(engine.simulate_action(&Hand::from_cards(4, 4), 6, Action::Split), 2.0);
+        assert_eq!(engine.deck().remaining(), 0);
+    }
+
+    #[test]
+    fn enhc_dealer_natural_beats_a_charlie() {
+        // 2,3 hits to 2,3,2,2,2: five cards on 11 against a ten and an ace in the hole
+        let mut engine = charlie_engine(&[11, 2, 2, 2]);
+        assert_eq!(engine.simulate_action(&Hand::from_cards(2, 3), 10, Action::Hit), -1.0);
+        assert_eq!(engine.deck().remaining(), 0);
+    }
+
+    #[test]
+    fn charlie_wins_without_a_dealer_natural() {
+        let mut engine = charlie_engine(&[9, 2, 2, 2]);
+        assert_eq!(engine.simulate_action(&Hand::from_cards(2, 3), 10, Action::Hit), 1.0);
+    }
+
+    #[test]
+    fn enhc_dealer_natural_beats_a_doubled_charlie() {
+        // A three-card Charlie completes on the double's card
+        let rules = RuleSet { charlie: Some(3), peek: false, obo: false, ..RuleSet::default() };
+        let mut engine = BlackjackEngine::with_rules(ScriptedDeck::new(&[10, 2]), rules);
+        assert_eq!(engine.simulate_action(&Hand::from_cards(5, 6), 11, Action::Double), -2.0);
+    }
+}
@@ -0,0 +1,285 @@
+//! Exact probabilities, computed by recursing over every possible draw (from an infinite deck, or for the dealer
+//! from a known shoe). Serves as a cross-check for the Monte Carlo engine, and settles its stood hands.
+
+use crate::deck::{get_hand_for_state, Hand, HandValue, PlayerState};
+use crate::engine::{Action, NUM_ACTIONS};
+use crate::rules::RuleSet;
+
+/// Dealer final totals tracked by `dealer_outcomes`, in order: 12-21, bust, blackjack
+pub const DEALER_OUTCOMES: usize = 12;
+
+/// Lowest dealer total `dealer_outcomes` tracks, at index 0: the lowest `RuleSet::dealer_stands_on`
+pub const DEALER_LOWEST: u8 = 12;
+
+/// Index of a bust in `dealer_outcomes`
+pub const DEALER_BUST: usize = 10;
+
+/// Index of a dealer natural in `dealer_outcomes`
+pub const DEALER_BLACKJACK: usize = 11;
+
+/// Probability of each dealer final result for `upcard`, drawing from `card_probs`
+/// (indexed by value - 2) under the rules' stand threshold and soft rule. Entries 0-9 are totals 12-21,
+/// zero below `RuleSet::dealer_stands_on` (a two-card 21 counts as blackjack instead); the hole card is not peeked.
+pub fn dealer_outcomes(rules: &RuleSet, upcard: u8, card_probs: &[f64; 10]) -> [f64; DEALER_OUTCOMES] {
+    dealer_distribution(rules, upcard, card_probs).0
+}
+
+/// `dealer_outcomes`, and the part of the bust probability that busts with exactly 22
+fn dealer_distribution(rules: &RuleSet, upcard: u8, card_probs: &[f64; 10]) -> ([f64; DEALER_OUTCOMES], f64) {
+    let mut outcomes = [0.0; DEALER_OUTCOMES + 1];
+    let mut hand = Hand::new();
+    hand.push(upcard);
+    dealer_draws(rules, hand, 1.0, card_probs, &mut outcomes);
+    let mut totals = [0.0; DEALER_OUTCOMES];
+    totals.copy_from_slice(&outcomes[..DEALER_OUTCOMES]);
+    (totals, outcomes[DEALER_OUTCOMES])
+}
+
+/// Add `p` to the outcome `hand` ends on, if the dealer is done drawing to it
+fn dealer_done(rules: &RuleSet, hand: &Hand, p: f64, outcomes: &mut [f64; DEALER_OUTCOMES + 1]) -> bool {
+    let value = hand.value();
+    let total = value.total;
+    if total > 21 {
+        outcomes[DEALER_BUST] += p;
+        if total == 22 { outcomes[DEALER_OUTCOMES] += p; }
+        return true;
+    }
+    if hand.len() >= 2 && value.dealer_stands(rules.dealer_stands_on, rules.hit_soft_17) {
+        let idx = if hand.len() == 2 && total == 21 { DEALER_BLACKJACK } else { (total - DEALER_LOWEST) as usize };
+        outcomes[idx] += p;
+        return true;
+    }
+    false
+}
+
+/// Add `p` times the distribution of finishing `hand` to `outcomes`, whose extra last entry counts busts on 22
+fn dealer_draws(
+    rules: &RuleSet,
+    hand: Hand,
+    p: f64,
+    card_probs: &[f64; 10],
+    outcomes: &mut [f64; DEALER_OUTCOMES + 1],
+) {
+    if dealer_done(rules, &hand, p, outcomes) { return; }
+    for card in 2..=11u8 {
+        let mut next = hand;
+        next.push(card);
+        dealer_draws(rules, next, p * card_probs[(card - 2) as usize], card_probs, outcomes);
+    }
+}
+
+/// `dealer_draws` without replacement from the `left` cards of `counts` (indexed by value - 2)
+fn shoe_dealer_draws(
+    rules: &RuleSet,
+    hand: Hand,
+    p: f64,
+    counts: &mut [u16; 10],
+    left: u32,
+    outcomes: &mut [f64; DEALER_OUTCOMES + 1],
+) {
+    if dealer_done(rules, &hand, p, outcomes) { return; }
+    for idx in 0..counts.len() {
+        let count = counts[idx];
+        if count == 0 { continue; }
+        let mut next = hand;
+        next.push(idx as u8 + 2);
+        counts[idx] -= 1;
+        shoe_dealer_draws(rules, next, p * count as f64 / left as f64, counts, left - 1, outcomes);
+        counts[idx] += 1;
+    }
+}
+
+/// Exact EVs of standing, hitting (then playing on optimally) and doubling a hand,
+/// in units of the initial bet
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HandEvs {
+    pub stand: f64,
+    pub hit: f64,
+    pub double: f64,
+}
+
+/// `HandEvs` indexed by softness, then by total (hard 4-21, soft 12-21; other entries are unused)
+pub type HandEvTable = [[HandEvs; 22]; 2];
+
+/// Exact infinite-deck EVs of every player total against `upcard`, drawing from `card_probs`.
+/// Hitting assumes the best of hitting and standing afterwards. The dealer's hole card is
+/// unknown under ENHC; with peek the player only acts when the dealer has no natural.
+/// A dealer 22 pushes under `push_22`. Charlie wins are not modeled.
+pub fn hand_evs(rules: &RuleSet, upcard: u8, card_probs: &[f64; 10]) -> HandEvTable {
+    let mut playout = Playout { stand: stand_evs(rules, upcard, card_probs), card_probs, best: [[None; 22]; 2] };
+    let mut table = [[HandEvs::default(); 22]; 2];
+    for (soft, totals) in [(false, 4..=21u8), (true, 12..=21u8)] {
+        for total in totals {
+            table[soft as usize][total as usize] = HandEvs {
+                stand: playout.stand[total as usize],
+                hit: playout.hit(total, soft),
+                double: (1.0 + rules.double_fraction) * playout.double(total, soft),
+            };
+        }
+    }
+    table
+}
+
+/// Exact infinite-deck EV of standing on each total (indexed by total; 4-21 are meaningful) against
+/// `upcard`, as in `hand_evs`: with peek or OBO it is conditioned on the dealer having no natural
+pub fn stand_evs(rules: &RuleSet, upcard: u8, card_probs: &[f64; 10]) -> [f64; 22] {
+    let (dealer, bust_22) = dealer_distribution(rules, upcard, card_probs);
+    stand_evs_against(rules, dealer, bust_22)
+}
+
+/// `stand_evs` with the dealer drawing from a shoe holding `counts` of each value (indexed by value - 2),
+/// the upcard already out: exact for one shoe composition, where the player's cards have left the shoe
+pub fn shoe_stand_evs(rules: &RuleSet, upcard: u8, counts: &[u16; 10]) -> [f64; 22] {
+    let mut outcomes = [0.0; DEALER_OUTCOMES + 1];
+    let mut hand = Hand::new();
+    hand.push(upcard);
+    let left = counts.iter().map(|&count| count as u32).sum();
+    shoe_dealer_draws(rules, hand, 1.0, &mut counts.clone(), left, &mut outcomes);
+    let mut dealer = [0.0; DEALER_OUTCOMES];
+    dealer.copy_from_slice(&outcomes[..DEALER_OUTCOMES]);
+    stand_evs_against(rules, dealer, outcomes[DEALER_OUTCOMES])
+}
+
+/// EV of standing on each total against the dealer results `dealer`, of which `bust_22` bust on 22
+fn stand_evs_against(rules: &RuleSet, mut dealer: [f64; DEALER_OUTCOMES], mut bust_22: f64) -> [f64; 22] {
+    if rules.original_bets_only() && dealer[DEALER_BLACKJACK] > 0.0 {
+        let no_natural = 1.0 - dealer[DEALER_BLACKJACK];
+        dealer[DEALER_BLACKJACK] = 0.0;
+        dealer.iter_mut().for_each(|p| *p /= no_natural);
+        bust_22 /= no_natural;
+    }
+    let bust_wins = if rules.push_22 { dealer[DEALER_BUST] - bust_22 } else { dealer[DEALER_BUST] };
+
+    let mut stand = [0.0; 22];
+    for (total, ev) in stand.iter_mut().enumerate() {
+        *ev = bust_wins - dealer[DEALER_BLACKJACK];
+        for (i, &p) in dealer[..DEALER_BUST].iter().enumerate() {
+            *ev += p * ((total as i32 - DEALER_LOWEST as i32 - i as i32).signum() as f64);
+        }
+    }
+    stand
+}
+
+/// Smallest weight `hit_proposal` gives a card, relative to a bust
+const PROPOSAL_FLOOR: f64 = 0.1;
+
+/// Proposal for importance sampling the hands of `state` (see `deck::ImportanceDeck`): each value's
+/// chance scaled by how far the next card moves the result from zero, i.e. the exact stand EV of the new
+/// total or -1 for a bust. That is the zero-variance proposal for a single hit, so the decisive draws
+/// (busts, and stiffs drawing to 19-21) are dealt more and the draws that leave the hand stiff less.
+pub fn hit_proposal(rules: &RuleSet, state: &PlayerState, card_probs: &[f64; 10]) -> [f64; 10] {
+    let stand = stand_evs(rules, state.dealer_upcard, card_probs);
+    let hand = get_hand_for_state(state.total, state.is_soft, state.is_pair);
+    let mut proposal = [0.0; 10];
+    for (idx, (q, &p)) in proposal.iter_mut().zip(card_probs).enumerate() {
+        let mut next = hand;
+        next.push(idx as u8 + 2);
+        let value = next.value();
+        let result = if value.is_bust { -1.0 } else { stand[value.total as usize] };
+        *q = p * result.abs().max(PROPOSAL_FLOOR);
+    }
+    let total: f64 = proposal.iter().sum();
+    proposal.map(|q| q / total)
+}
+
+/// Exact infinite-deck EV of every action offered in `state`, indexed by `Action::index` (`None` where
+/// not offered). Stand, hit and double come from `hand_evs`; each split hand is played on optimally
+/// (doubling only with DAS on totals `RuleSet::double_on` allows, one card to split aces, no resplits). Like the
+/// simulated EVs they include losing to a dealer natural. Spanish 21 bonuses and exposed hole cards are
+/// not modeled.
+pub fn action_evs(rules: &RuleSet, state: &PlayerState, card_probs: &[f64; 10]) -> [Option<f64>; NUM_ACTIONS] {
+    let upcard = state.dealer_upcard;
+    let natural = match upcard {
+        10 => card_probs[11 - 2],
+        11 => card_probs[10 - 2],
+        _ => 0.0,
+    };
+    // With peek or OBO `hand_evs` is conditioned on no natural, which instead loses one unit up front
+    let settled = |ev: f64| if rules.original_bets_only() { (1.0 - natural) * ev - natural } else { ev };
+    let table = hand_evs(rules, upcard, card_probs);
+    let evs = &table[state.is_soft as usize][state.total as usize];
+
+    let mut actions = [None; NUM_ACTIONS];
+    actions[Action::Stand.index()] = Some(settled(evs.stand));
+    actions[Action::Hit.index()] = Some(settled(evs.hit));
+    if rules.double_on.allows(state.total, state.is_soft) {
+        actions[Action::Double.index()] = Some(settled(evs.double));
+    }
+    if rules.surrender_allowed_vs(upcard) {
+        let surrender = if rules.early_surrender { -0.5 } else { -0.5 * (1.0 - natural) - natural };
+        actions[Action::Surrender.index()] = Some(surrender);
+    }
+    if Action::Split.is_allowed(state, rules) {
+        let split_card = if state.is_soft { 11 } else { state.total / 2 };
+        let mut hand_ev = 0.0;
+        for card in 2..=11u8 {
+            let HandValue { total, is_soft, .. } = Hand::from_cards(split_card, card).value();
+            let evs = &table[is_soft as usize][total as usize];
+            let ev = if split_card == 11 {
+                evs.stand
+            } else if rules.double_after_split && rules.double_on.allows(total, is_soft) {
+                evs.stand.max(evs.hit).max(evs.double)
+            } else {
+                evs.stand.max(evs.hit)
+            };
+            hand_ev += card_probs[(card - 2) as usize] * ev;
+        }
+        actions[Action::Split.index()] = Some(settled(2.0 * hand_ev));
+    }
+    actions
+}
+
+/// Memoized optimal hit/stand recursion for `hand_evs`
+struct Playout<'a> {
+    stand: [f64; 22],
+    card_probs: &'a [f64; 10],
+    best: [[Option<f64>; 22]; 2],
+}
+
+impl Playout<'_> {
+    /// Total and softness after drawing `card`, or `None` on a bust
+    fn draw(total: u8, soft: bool, card: u8) -> Option<(u8, bool)> {
+        let (mut total, mut soft) = (total + card, soft);
+        if card == 11 {
+            if total > 21 { total -= 10; } else { soft = true; }
+        }
+        if total > 21 && soft {
+            total -= 10;
+            soft = false;
+        }
+        if total > 21 { None } else { Some((total, soft)) }
+    }
+
+    /// EV of the better of standing and hitting
+    fn best(&mut self, total: u8, soft: bool) -> f64 {
+        if let Some(ev) = self.best[soft as usize][total as usize] { return ev; }
+        let ev = self.stand[total as usize].max(self.hit(total, soft));
+        self.best[soft as usize][total as usize] = Some(ev);
+        ev
+    }
+
+    fn hit(&mut self, total: u8, soft: bool) -> f64 {
+        let mut ev = 0.0;
+        for card in 2..=11u8 {
+            let p = self.card_probs[(card - 2) as usize];
+            ev += p * match Self::draw(total, soft, card) {
+                Some((total, soft)) => self.best(total, soft),
+                None => -1.0,
+            };
+        }
+        ev
+    }
+
+    /// EV per unit staked of taking exactly one card and standing
+    fn double(&self, total: u8, soft: bool) -> f64 {
+        let mut ev = 0.0;
+        for card in 2..=11u8 {
+            let p = self.card_probs[(card - 2) as usize];
+            ev += p * match Self::draw(total, soft, card) {
+                Some((total, _)) => self.stand[total as usize],
+                None => -1.0,
+            };
+        }
+        ev
+    }
+}
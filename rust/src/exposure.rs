@@ -0,0 +1,130 @@
+//! Double exposure: both dealer cards are dealt face up and the dealer wins ties.
+//! Every decision is solved against the dealer's two-card total instead of the upcard alone.
+
+use crate::deck::{card_name, Deck, FiniteShoe, Hand, HandValue, InfiniteDeck, PlayerState, ReplayDeck};
+use crate::engine::{generate_all_states, Action, BlackjackEngine};
+use crate::playout::ExposedStrategy;
+use crate::progress::{Progress, ProgressReporter};
+use crate::rules::RuleSet;
+use crate::solver::{batch_seed, ActionTable, SolverConfig};
+use rayon::prelude::*;
+use std::fmt;
+use tracing::{info, info_span};
+
+/// Player state against a face-up dealer hand. `player.dealer_upcard` is the dealer's first card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExposedState {
+    pub player: PlayerState,
+    pub dealer_hole: u8,
+}
+
+impl ExposedState {
+    /// The dealer's two-card total
+    pub fn dealer_value(&self) -> HandValue {
+        Hand::from_cards(self.player.dealer_upcard, self.dealer_hole).value()
+    }
+}
+
+/// e.g. "Hard 16 vs 10,6"
+impl fmt::Display for ExposedState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&format!("{},{}", self.player, card_name(self.dealer_hole)))
+    }
+}
+
+/// One two-card dealer hand for each total a decision can face: hard 4-20 and soft 12-20.
+/// A dealer natural ends the round before the player acts, so it has no decisions.
+pub fn dealer_hands() -> Vec<(u8, u8)> {
+    let hard = (4..=20).map(|total| if total <= 11 { (2, total - 2) } else { (10, total - 10) });
+    let soft = (12..=20).map(|total| if total == 12 { (11, 11) } else { (11, total - 11) });
+    hard.chain(soft).collect()
+}
+
+/// Every player state against every dealer hand of `dealer_hands`
+pub fn generate_exposed_states() -> Vec<ExposedState> {
+    let players = generate_all_states();
+    dealer_hands()
+        .into_iter()
+        .flat_map(|(upcard, hole)| {
+            players
+                .iter()
+                .filter(move |player| player.dealer_upcard == upcard)
+                .map(move |&player| ExposedState { player, dealer_hole: hole })
+        })
+        .collect()
+}
+
+/// Solved statistics of every exposed state
+pub type ExposedStats = Vec<(ExposedState, ActionTable)>;
+
+/// Work left for one state: the actions still above the target SEM
+struct ExposedTask {
+    state: ExposedState,
+    actions: Vec<Action>,
+    stats: ActionTable,
+}
+
+/// Solve every state of `generate_exposed_states` under `rules`, which should set `double_exposure`.
+/// Continuation play is `ExposedStrategy`; `config` sets convergence and seeding, while its
+/// threads, checkpoint and resume settings are not used here.
+pub fn solve_exposed(rules: &RuleSet, config: &SolverConfig, progress: Progress) -> ExposedStats {
+    match rules.num_decks {
+        Some(num_decks) => run(rules, || FiniteShoe::with_deck_type(num_decks, rules.deck_type), config, progress),
+        None => run(rules, || InfiniteDeck::with_deck_type(rules.deck_type), config, progress),
+    }
+}
+
+/// The convergence loop of `solve_exposed`. Each task keeps its own statistics, so batches merge in place.
+fn run<D, F>(rules: &RuleSet, make_deck: F, config: &SolverConfig, progress: Progress) -> ExposedStats
+where
+    D: Deck + Send,
+    F: Fn() -> D + Sync,
+{
+    let playout = ExposedStrategy { hit_soft_17: rules.hit_soft_17, dealer_stands_on: rules.dealer_stands_on };
+    let mut tasks: Vec<ExposedTask> = generate_exposed_states()
+        .into_iter()
+        .map(|state| ExposedTask {
+            state,
+            actions: Action::valid_actions(&state.player, rules).collect(),
+            stats: Default::default(),
+        })
+        .collect();
+
+    let total_pairs: usize = tasks.iter().map(|task| task.actions.len()).sum();
+    if !progress.is_quiet() {
+        info!("Total state-action pairs: {}", total_pairs);
+        info!("");
+    }
+    let reporter = ProgressReporter::new(progress, total_pairs);
+    let mut converged_count = 0;
+    let mut iterations = 0;
+
+    for iteration in 1..=config.max_iterations {
+        if tasks.iter().all(|task| task.actions.is_empty()) {
+            break;
+        }
+        iterations = iteration;
+        let _iteration = info_span!("iteration", n = iteration).entered();
+        reporter.update(iteration, converged_count, || f64::NAN);
+
+        tasks.par_iter_mut().enumerate().filter(|(_, task)| !task.actions.is_empty()).for_each_init(
+            || BlackjackEngine::with_playout(ReplayDeck::new(make_deck()), rules.clone(), playout),
+            |engine, (idx, task)| {
+                if let Some(seed) = config.seed {
+                    engine.deck_mut().seed(batch_seed(seed, iteration, idx, 0));
+                }
+                let ExposedState { player, dealer_hole } = task.state;
+                let batch = engine.simulate_batch_exposed(&player, dealer_hole, &task.actions, config.batch_size);
+                for (total, stats) in task.stats.iter_mut().zip(&batch) {
+                    total.merge(stats);
+                }
+                let stats = &task.stats;
+                task.actions.retain(|action| stats[action.index()].sem() >= config.target_sem);
+            },
+        );
+        converged_count = total_pairs - tasks.iter().map(|task| task.actions.len()).sum::<usize>();
+    }
+
+    reporter.finish(iterations, converged_count, || f64::NAN);
+    tasks.into_iter().map(|task| (task.state, task.stats)).collect()
+}
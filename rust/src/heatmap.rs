@@ -0,0 +1,288 @@
+//! PNG heatmap of a solved chart: player hands down the side, dealer upcards across the top.
+//! Encoded in-crate (stored deflate, labels from a 3x5 pixel font) so the `charts` feature adds no dependencies.
+
+use crate::deck::PlayerState;
+use crate::engine::Action;
+use crate::solver::{get_best_action, StateStats};
+use std::io;
+use std::path::Path;
+
+/// What each cell's color encodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeatmapMetric {
+    /// EV of the best action: red at -1 or below, white at 0, green at +1 or above
+    BestEv,
+    /// EV gap between the best action and the runner-up: red for near ties, white from `GAP_SCALE` up
+    Gap,
+}
+
+/// EV gap at and beyond which a decision is drawn as clear-cut
+const GAP_SCALE: f64 = 0.25;
+
+/// Cell and label geometry in pixels
+const CELL_W: usize = 28;
+const CELL_H: usize = 18;
+const SCALE: usize = 2;
+const LABEL_W: usize = 40;
+const HEADER_H: usize = 24;
+/// Blank rows between the hard, soft and pair blocks
+const BLOCK_GAP: usize = 10;
+
+const BACKGROUND: [u8; 3] = [255, 255, 255];
+const GRID: [u8; 3] = [136, 136, 136];
+const TEXT: [u8; 3] = [0, 0, 0];
+const UNSOLVED: [u8; 3] = [200, 200, 200];
+
+/// Rows of the chart as (label, total, is_soft, is_pair), in the blocks of `report::format_html_chart`
+fn chart_rows() -> [Vec<(String, u8, bool, bool)>; 3] {
+    let hard = (5..=21).rev().map(|total| (total.to_string(), total, false, false)).collect();
+    let soft = (13..=20).rev().map(|total| (format!("A{}", total - 11), total, true, false)).collect();
+    let pairs = [11, 10, 9, 8, 7, 6, 5, 4, 3, 2]
+        .into_iter()
+        .map(|card| match card {
+            11 => ("AA".to_string(), 12, true, true),
+            10 => ("TT".to_string(), 20, false, true),
+            _ => (format!("{}{}", card, card), card * 2, false, true),
+        })
+        .collect();
+    [hard, soft, pairs]
+}
+
+/// Value `metric` reads off a state's action table, if the state was solved and sampled
+fn cell_value(state_stats: &StateStats, state: &PlayerState, metric: HeatmapMetric) -> Option<f64> {
+    let actions = state_stats.get(state)?;
+    let (best, ev) = get_best_action(actions)?;
+    match metric {
+        HeatmapMetric::BestEv => Some(ev),
+        HeatmapMetric::Gap => {
+            let runner_up = Action::ALL
+                .iter()
+                .filter(|&&action| action != best && actions[action.index()].n > 0)
+                .map(|action| actions[action.index()].ev())
+                .fold(f64::NEG_INFINITY, f64::max);
+            Some(if runner_up.is_finite() { ev - runner_up } else { GAP_SCALE })
+        }
+    }
+}
+
+/// Blend from `from` toward `to` by `t` in 0-1
+fn blend(from: [u8; 3], to: [u8; 3], t: f64) -> [u8; 3] {
+    let t = t.clamp(0.0, 1.0);
+    std::array::from_fn(|i| (from[i] as f64 + (to[i] as f64 - from[i] as f64) * t).round() as u8)
+}
+
+fn cell_color(value: f64, metric: HeatmapMetric) -> [u8; 3] {
+    const RED: [u8; 3] = [214, 48, 49];
+    const GREEN: [u8; 3] = [39, 174, 96];
+    match metric {
+        HeatmapMetric::BestEv if value < 0.0 => blend(BACKGROUND, RED, -value),
+        HeatmapMetric::BestEv => blend(BACKGROUND, GREEN, value),
+        HeatmapMetric::Gap => blend(RED, BACKGROUND, value / GAP_SCALE),
+    }
+}
+
+/// 3x5 glyphs, one row per byte with the leftmost pixel in bit 2
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        '0' => [7, 5, 5, 5, 7],
+        '1' => [2, 6, 2, 2, 7],
+        '2' => [7, 1, 7, 4, 7],
+        '3' => [7, 1, 3, 1, 7],
+        '4' => [5, 5, 7, 1, 1],
+        '5' => [7, 4, 7, 1, 7],
+        '6' => [7, 4, 7, 5, 7],
+        '7' => [7, 1, 1, 2, 2],
+        '8' => [7, 5, 7, 5, 7],
+        '9' => [7, 5, 7, 1, 7],
+        'A' => [2, 5, 7, 5, 5],
+        'T' => [7, 2, 2, 2, 2],
+        _ => [0; 5],
+    }
+}
+
+/// RGB raster drawn on by `render_heatmap`
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Canvas { width, height, pixels: BACKGROUND.repeat(width * height) }
+    }
+
+    fn fill(&mut self, x: usize, y: usize, w: usize, h: usize, color: [u8; 3]) {
+        for row in y..(y + h).min(self.height) {
+            for col in x..(x + w).min(self.width) {
+                let i = 3 * (row * self.width + col);
+                self.pixels[i..i + 3].copy_from_slice(&color);
+            }
+        }
+    }
+
+    /// Outline of a `w` by `h` box
+    fn frame(&mut self, x: usize, y: usize, w: usize, h: usize, color: [u8; 3]) {
+        self.fill(x, y, w, 1, color);
+        self.fill(x, y + h - 1, w, 1, color);
+        self.fill(x, y, 1, h, color);
+        self.fill(x + w - 1, y, 1, h, color);
+    }
+
+    /// `text` centered in the `w` by `h` box at `x`, `y`
+    fn text(&mut self, x: usize, y: usize, w: usize, h: usize, text: &str) {
+        let width = text.len() * 4 * SCALE - SCALE;
+        let (x, y) = (x + w.saturating_sub(width) / 2, y + h.saturating_sub(5 * SCALE) / 2);
+        for (n, c) in text.chars().enumerate() {
+            for (row, bits) in glyph(c).iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (4 >> col) != 0 {
+                        self.fill(x + (4 * n + col) * SCALE, y + row * SCALE, SCALE, SCALE, TEXT);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// PNG of the hard, soft and pair charts with each cell colored by `metric`.
+/// Unsolved states are gray; rows are labeled like `A7` and `TT`, columns 2-10 and `A`.
+pub fn render_heatmap(state_stats: &StateStats, metric: HeatmapMetric) -> Vec<u8> {
+    let blocks = chart_rows();
+    let rows: usize = blocks.iter().map(Vec::len).sum();
+    let width = LABEL_W + 10 * CELL_W + 1;
+    let height = HEADER_H + rows * CELL_H + (blocks.len() - 1) * BLOCK_GAP + 1;
+    let mut canvas = Canvas::new(width, height);
+
+    for (col, dealer) in ["2", "3", "4", "5", "6", "7", "8", "9", "10", "A"].iter().enumerate() {
+        canvas.text(LABEL_W + col * CELL_W, 0, CELL_W, HEADER_H, dealer);
+    }
+    let mut y = HEADER_H;
+    for block in &blocks {
+        for (label, total, is_soft, is_pair) in block {
+            canvas.text(0, y, LABEL_W, CELL_H, label);
+            for (col, dealer) in (2..=11).enumerate() {
+                let state = PlayerState::new(*total, dealer, *is_soft, *is_pair);
+                let color = cell_value(state_stats, &state, metric).map_or(UNSOLVED, |value| cell_color(value, metric));
+                let x = LABEL_W + col * CELL_W;
+                canvas.fill(x, y, CELL_W, CELL_H, color);
+                canvas.frame(x, y, CELL_W + 1, CELL_H + 1, GRID);
+            }
+            y += CELL_H;
+        }
+        y += BLOCK_GAP;
+    }
+    encode_png(&canvas)
+}
+
+/// Render a heatmap (see `render_heatmap`) to `path`
+pub fn write_heatmap(path: impl AsRef<Path>, state_stats: &StateStats, metric: HeatmapMetric) -> io::Result<()> {
+    std::fs::write(path, render_heatmap(state_stats, metric))
+}
+
+/// 8-bit RGB PNG with the image data in uncompressed deflate blocks
+fn encode_png(canvas: &Canvas) -> Vec<u8> {
+    let mut raw = Vec::with_capacity((3 * canvas.width + 1) * canvas.height);
+    for row in canvas.pixels.chunks(3 * canvas.width) {
+        raw.push(0); // no filter
+        raw.extend_from_slice(row);
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(u16::MAX as usize).peekable();
+    while let Some(block) = blocks.next() {
+        zlib.push(blocks.peek().is_none() as u8);
+        let len = block.len() as u16;
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(canvas.width as u32).to_be_bytes());
+    header.extend_from_slice(&(canvas.height as u32).to_be_bytes());
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    for (kind, data) in [(b"IHDR", &header), (b"IDAT", &zlib), (b"IEND", &Vec::new())] {
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let start = png.len();
+        png.extend_from_slice(kind);
+        png.extend_from_slice(data);
+        let crc = crc32(&png[start..]);
+        png.extend_from_slice(&crc.to_be_bytes());
+    }
+    png
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::InfiniteDeck;
+    use crate::playout::BasicStrategy;
+    use crate::progress::Progress;
+    use crate::rules::RuleSet;
+    use crate::solver::{solve_with_playout, SolverConfig};
+
+    /// Hard 16 and soft 18 against every upcard, solved roughly
+    fn solved_rows() -> StateStats {
+        let states: Vec<PlayerState> = [(16, false), (18, true)]
+            .into_iter()
+            .flat_map(|(total, soft)| (2..=11).map(move |dealer| PlayerState::new(total, dealer, soft, false)))
+            .collect();
+        let config = SolverConfig { target_sem: 0.05, seed: Some(1), ..SolverConfig::default() };
+        solve_with_playout(&states, &RuleSet::default(), InfiniteDeck::new, &BasicStrategy, &config, Progress::Quiet)
+    }
+
+    #[test]
+    fn a_solved_table_is_written_as_a_nonempty_png() {
+        let path = std::env::temp_dir().join(format!("heatmap_test_{}.png", std::process::id()));
+        write_heatmap(&path, &solved_rows(), HeatmapMetric::BestEv).unwrap();
+        let written = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(written.len() > 1000, "{} bytes", written.len());
+        assert!(written.starts_with(b"\x89PNG\r\n\x1a\n"));
+    }
+
+    #[test]
+    fn the_png_opens_with_a_valid_ihdr_chunk() {
+        let png = render_heatmap(&solved_rows(), HeatmapMetric::Gap);
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+
+        let length = u32::from_be_bytes(png[8..12].try_into().unwrap());
+        assert_eq!(length, 13);
+        assert_eq!(&png[12..16], b"IHDR");
+        let ihdr = &png[16..29];
+        let rows: usize = chart_rows().iter().map(Vec::len).sum();
+        let (width, height) = (LABEL_W + 10 * CELL_W + 1, HEADER_H + rows * CELL_H + 2 * BLOCK_GAP + 1);
+        assert_eq!(u32::from_be_bytes(ihdr[..4].try_into().unwrap()) as usize, width);
+        assert_eq!(u32::from_be_bytes(ihdr[4..8].try_into().unwrap()) as usize, height);
+        // 8-bit RGB, deflate, no filter method, no interlace
+        assert_eq!(ihdr[8..], [8, 2, 0, 0, 0]);
+        assert_eq!(u32::from_be_bytes(png[29..33].try_into().unwrap()), crc32(&png[12..29]));
+
+        assert_eq!(&png[37..41], b"IDAT");
+        assert_eq!(&png[png.len() - 12..], b"\0\0\0\0IEND\xae\x42\x60\x82");
+    }
+}
@@ -0,0 +1,59 @@
+//! Blackjack Strategy Optimizer
+//! Monte Carlo engine and solver shared by the CLI and benchmarks
+
+pub mod analysis;
+pub mod bankroll;
+pub mod checkpoint;
+pub mod codegen;
+pub mod config;
+pub mod counting;
+pub mod deck;
+pub mod engine;
+pub mod exact;
+pub mod exposure;
+#[cfg(feature = "charts")]
+pub mod heatmap;
+pub mod logging;
+pub mod playout;
+pub mod progress;
+#[cfg(feature = "pyo3")]
+pub mod python;
+pub mod report;
+pub mod rules;
+pub mod session;
+pub mod side_bets;
+pub mod solver;
+pub mod strategy;
+pub mod validate;
+
+pub use deck::{Card, Deck, DeckType, FiniteShoe, Hand, HandValue, InfiniteDeck, PlayerState, Suit, SuitedShoe};
+pub use engine::{Action, ActionStats, BlackjackEngine, Outcome, OutcomeCounts};
+pub use playout::{BasicStrategy, ContinuationPolicy, ExposedStrategy, OptimalStrategy, PlayoutStrategy};
+pub use rules::{DoubleOn, RuleSet};
+pub use solver::{get_best_action, SolverConfig, StateStats};
+pub use strategy::StrategyTable;
+
+/// Solve every starting state under `rules`, drawing from the deck the rules describe
+pub fn solve(rules: &RuleSet) -> StateStats {
+    solver::solve_with_rules(&engine::generate_all_states(), rules, progress::Progress::Quiet)
+}
+
+/// `solve` with the convergence settings (target SEM, batch size, iteration cap, seed, threads) of `config`
+pub fn solve_with_config(rules: &RuleSet, config: &SolverConfig) -> StateStats {
+    let states = engine::generate_all_states();
+    solver::solve_with_rules_and_playout(&states, rules, &BasicStrategy, config, progress::Progress::Quiet)
+}
+
+/// Play `hands` hands of `state` with `action` under `rules`
+pub fn simulate(rules: &RuleSet, state: &PlayerState, action: Action, hands: u32) -> ActionStats {
+    match rules.num_decks {
+        Some(num_decks) => {
+            let shoe = FiniteShoe::with_deck_type(num_decks, rules.deck_type);
+            BlackjackEngine::with_rules(shoe, rules.clone()).simulate_batch(state, action, hands)
+        }
+        None => {
+            let deck = InfiniteDeck::with_deck_type(rules.deck_type);
+            BlackjackEngine::with_rules(deck, rules.clone()).simulate_batch(state, action, hands)
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Console output of `tracing` diagnostics: the `RUST_LOG` filter picks the levels printed, warnings and
+//! errors go to stderr and the rest to stdout, debug and trace lines prefixed with their span.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::writer::MakeWriterExt;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::EnvFilter;
+
+/// Environment variable holding the log filter, an `EnvFilter` directive list such as `debug` or
+/// `blackjack_solver=trace`
+pub const LOG_ENV: &str = "RUST_LOG";
+
+/// Print diagnostics on the console from now on. `info` (solve progress and status lines) when `RUST_LOG`
+/// is unset or invalid; `debug` adds every pending pair's EV and SEM each iteration, `trace` the converged
+/// ones too. Does nothing if a subscriber is already set.
+pub fn init() {
+    let filter = EnvFilter::try_from_env(LOG_ENV).unwrap_or_else(|_| EnvFilter::new("info"));
+    let writer = io::stderr.with_max_level(Level::WARN).or_else(io::stdout);
+    tracing_subscriber::fmt()
+        .with_ansi(false)
+        .event_format(Console)
+        .with_writer(writer)
+        .with_env_filter(filter)
+        .try_init()
+        .ok();
+}
+
+/// Run `f` with the diagnostics up to `level` logged on this thread returned as lines, formatted as
+/// `init` prints them, instead of printed
+pub fn capture<R>(level: Level, f: impl FnOnce() -> R) -> (R, Vec<String>) {
+    let buffer = Buffer::default();
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_ansi(false)
+        .event_format(Console)
+        .with_writer(move || writer.clone())
+        .with_max_level(level)
+        .finish();
+    let result = tracing::subscriber::with_default(subscriber, f);
+    let bytes = buffer.0.lock().unwrap();
+    (result, String::from_utf8_lossy(&bytes).lines().map(String::from).collect())
+}
+
+/// Lines written by `capture`'s subscriber
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Event format of the console: the message and any fields, after `error: ` or `warning: `, or after
+/// the span scope (`iteration{n=3}: `) for debug and trace
+struct Console;
+
+impl<S, N> FormatEvent<S, N> for Console
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        match *event.metadata().level() {
+            Level::ERROR => write!(writer, "error: ")?,
+            Level::WARN => write!(writer, "warning: ")?,
+            Level::INFO => {}
+            _ => {
+                for span in ctx.event_scope().into_iter().flat_map(|scope| scope.from_root()) {
+                    write!(writer, "{}", span.name())?;
+                    if let Some(fields) = span.extensions().get::<FormattedFields<N>>() {
+                        if !fields.is_empty() { write!(writer, "{{{}}}", fields)?; }
+                    }
+                    write!(writer, ": ")?;
+                }
+            }
+        }
+        ctx.format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::{debug, error, info_span, trace, warn};
+
+    #[test]
+    fn capture_collects_the_lines_up_to_its_level() {
+        let ((), lines) = capture(Level::DEBUG, || {
+            error!("cannot read {}", "rules.toml");
+            warn!("ties lose");
+            let _iteration = info_span!("iteration", n = 3).entered();
+            debug!("Hard 16 vs 10 Hit");
+            trace!("too detailed to print");
+        });
+        assert_eq!(lines, ["error: cannot read rules.toml", "warning: ties lose", "iteration{n=3}: Hard 16 vs 10 Hit"]);
+    }
+}
@@ -1,280 +1,514 @@
 //! Blackjack Strategy Optimizer
-//! Monte Carlo simulation for Evolution Live Blackjack rules (S17, DAS, ENHC)
-
-mod deck;
-mod engine;
-
-use engine::{generate_all_states, Action, ActionStats, BlackjackEngine};
-use deck::PlayerState;
-use rayon::prelude::*;
-use std::collections::HashMap;
+//! Monte Carlo simulation for the table rules given on the command line or in a config file
+
+mod cli;
+mod quiz;
+
+use blackjack_solver::analysis::{
+    house_edge, outcome_frequencies, round_stats, rules_composition, split_hand_distribution, strategy_cost,
+    strategy_ev,
+};
+use blackjack_solver::bankroll::{
+    lifetime_risk_of_ruin, risk_of_ruin, simulated_risk_of_ruin, simulated_ruin_before_target,
+};
+use blackjack_solver::codegen::rust_lookup_source;
+use blackjack_solver::counting::{
+    effect_of_removal, find_index_plays, kelly_table, shoe_decks, solve_at_true_count, solve_kelly_counts,
+    solve_true_counts, ten_split_counts, MAX_TRUE_COUNT, MIN_TRUE_COUNT,
+};
+use blackjack_solver::deck::AntitheticDeck;
+use blackjack_solver::engine::generate_all_states;
+use blackjack_solver::exposure::{generate_exposed_states, solve_exposed};
+#[cfg(feature = "charts")]
+use blackjack_solver::heatmap::write_heatmap;
+use blackjack_solver::logging;
+use blackjack_solver::progress::Progress;
+use blackjack_solver::report::{
+    format_dealer_outcomes, format_deck_comparison, format_decks, format_effect_of_removal, format_even_money,
+    format_exposed_tables,
+    format_full_table, format_hit_stand_curves, format_house_edge, format_html_chart, format_index_plays,
+    format_kelly_table, format_outcomes, format_query, format_side_bets, format_split_hands, format_strategy_tables, format_ten_split,
+    format_validation,
+};
+use blackjack_solver::session::{simulate_spread, BetSizing};
+use blackjack_solver::solver::{solve_with_playout, solve_with_rules_and_playout, with_threads};
+use blackjack_solver::validate::compare_exact;
+use blackjack_solver::{
+    Action, ActionStats, BasicStrategy, DeckType, OptimalStrategy, PlayerState, RuleSet, SolverConfig, StateStats,
+    StrategyTable,
+};
 use std::io::Write;
 use std::time::Instant;
 use std::fs::File;
+use tracing::info;
 
-const TARGET_SEM: f64 = 0.005;
-const BATCH_SIZE: u32 = 10_000;
-const MAX_ITERATIONS: u32 = 1000;
+/// Sessions simulated to check the analytic risk of ruin
+const RUIN_TRIALS: u32 = 2000;
 
-/// Task for simulation
-#[derive(Clone, Copy)]
-struct SimulationTask {
-    state: PlayerState,
-    action: Action,
-}
+/// Largest differences listed by --validate
+const VALIDATION_ROWS: usize = 25;
+
+/// Rounds dealt per state to measure the outcome frequencies in `--full`
+const OUTCOME_ROUNDS: u32 = 20_000;
+
+/// Largest share of bankroll the Kelly table will recommend on one hand
+const KELLY_CAP: f64 = 0.1;
+
+/// Shoe sizes compared by --compare-decks
+const COMPARED_DECKS: [u8; 4] = [1, 2, 6, 8];
 
 fn main() {
+    logging::init();
+    let options = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    // Every parallel phase runs on this one pool, so fixing its size fixes the whole run's
+    with_threads(options.solver.threads, || run(options));
+}
+
+fn run(options: cli::Options) {
     println!("============================================================");
     println!("Blackjack Strategy Optimizer (Rust - Optimized)");
-    println!("Evolution Live Blackjack Rules (S17, DAS, ENHC)");
+    println!("Rules: {}", options.rules);
     println!("============================================================");
     println!();
 
+    let config = &options.solver;
     let num_threads = rayon::current_num_threads();
-    println!("Starting Monte Carlo simulation with {} threads", num_threads);
-    println!("Target SEM: {}, Batch size: {}", TARGET_SEM, BATCH_SIZE);
-    println!();
-
-    let all_states = generate_all_states();
-    println!("Total states to analyze: {}", all_states.len());
-
-    // Initialize state statistics
-    let mut state_stats: HashMap<PlayerState, HashMap<Action, ActionStats>> = all_states
-        .iter()
-        .map(|&state| {
-            let action_stats: HashMap<Action, ActionStats> = Action::valid_actions(state.is_pair)
-                .iter()
-                .map(|&a| (a, ActionStats::new()))
-                .collect();
-            (state, action_stats)
-        })
-        .collect();
-
-    // Generate initial tasks
-    let mut pending_tasks: Vec<SimulationTask> = Vec::new();
-    for &state in &all_states {
-        for &action in Action::valid_actions(state.is_pair) {
-            pending_tasks.push(SimulationTask { state, action });
-        }
+    info!("Starting Monte Carlo simulation with {} threads", num_threads);
+    info!("Target SEM: {}, Batch size: {}", config.target_sem, config.batch_size);
+    if let Some(seed) = config.seed {
+        info!("Seed: {}", seed);
     }
-
-    let total_pairs = pending_tasks.len();
-    println!("Total state-action pairs: {}", total_pairs);
-    println!();
+    if let Some(checkpoint) = &config.resume {
+        info!("Resuming after iteration {}", checkpoint.iteration);
+    }
+    info!("");
+
+    let rules = options.rules.clone();
+    // A query solves only the state asked about
+    let all_states = match options.query {
+        Some(state) => vec![state],
+        None => generate_all_states(),
+    };
+    if rules.deck_type == DeckType::Spanish {
+        info!("Spanish 21 decks (10-spots removed)");
+    }
+    if rules.spanish_bonuses {
+        info!("Spanish 21 bonuses: player 21 always wins, 5+ card and 6-7-8 / 7-7-7 bonuses");
+    }
+    if let Some(tc) = options.true_count {
+        info!("Shoe held at Hi-Lo true count {:+}", tc);
+    }
+    if options.optimal_playout {
+        info!("Playout: exact infinite-deck hit/stand/double after the first decision");
+    }
+    if let Some(policy) = &options.continuation {
+        info!("Playout: {:?}", policy);
+    }
+    if options.antithetic {
+        info!("Antithetic sampling: hands dealt in mirrored pairs");
+    }
+    if config.importance_sampling && rules.num_decks.is_none() {
+        info!("Importance sampling: each state's draws tilted toward its decisive cards");
+    }
+    let progress = match &options.progress_json {
+        Some(path) => Progress::json(path).unwrap_or_else(|err| {
+            eprintln!("error: cannot write progress to '{}': {}", path, err);
+            std::process::exit(2);
+        }),
+        None => Progress::for_stdout(options.no_progress),
+    };
+    if rules.double_exposure {
+        solve_double_exposure(&rules, config, progress);
+        return;
+    }
+    info!("Total states to analyze: {}", all_states.len());
 
     let start_time = Instant::now();
-    let mut converged_count = 0usize;
-
-    for iteration in 1..=MAX_ITERATIONS {
-        if pending_tasks.is_empty() {
-            break;
+    let policy = options.continuation.unwrap_or_default();
+    let state_stats = if let Some(merged) = &options.merged {
+        info!("");
+        info!("Pooled {} iterations of {} states", merged.iteration, merged.stats.len());
+        if let Some(path) = &config.checkpoint {
+            merged.save(path).expect("Failed to write merged checkpoint");
+            info!("Pooled statistics saved to: {}", path);
         }
-
-        if iteration % 5 == 1 {
-            let elapsed = start_time.elapsed().as_secs_f64();
-            println!(
-                "Iteration {}: {}/{} converged ({:.1}%), elapsed: {:.2}s",
-                iteration, converged_count, total_pairs,
-                100.0 * converged_count as f64 / total_pairs as f64, elapsed
-            );
-        }
-
-        // Run batch in parallel - collect results without locks
-        let results: Vec<(PlayerState, Action, ActionStats)> = pending_tasks
-            .par_iter()
-            .map(|task| {
-                let mut engine = BlackjackEngine::new();
-                let stats = engine.simulate_batch(&task.state, task.action, BATCH_SIZE);
-                (task.state, task.action, stats)
-            })
-            .collect();
-
-        // Merge results (single-threaded, but fast)
-        for (state, action, batch_stats) in results {
-            if let Some(action_map) = state_stats.get_mut(&state) {
-                if let Some(stats) = action_map.get_mut(&action) {
-                    stats.merge(&batch_stats);
-                }
+        StateStats::from_checkpoint(merged)
+    } else {
+        let state_stats = match options.true_count {
+            Some(tc) => solve_at_true_count(&all_states, &rules, tc, config, progress.clone()),
+            None if options.antithetic => {
+                let make_deck = || AntitheticDeck::with_deck_type(rules.deck_type);
+                solve_with_playout(&all_states, &rules, make_deck, &policy, config, progress.clone())
             }
-        }
-
-        // Filter converged tasks
-        let mut new_pending = Vec::with_capacity(pending_tasks.len());
-        for task in pending_tasks {
-            if let Some(action_map) = state_stats.get(&task.state) {
-                if let Some(stats) = action_map.get(&task.action) {
-                    if stats.sem() >= TARGET_SEM {
-                        new_pending.push(task);
-                    } else {
-                        converged_count += 1;
-                    }
-                }
+            None if options.optimal_playout => {
+                let optimal = OptimalStrategy::new(&rules);
+                solve_with_rules_and_playout(&all_states, &rules, &optimal, config, progress.clone())
             }
+            None => solve_with_rules_and_playout(&all_states, &rules, &policy, config, progress.clone()),
+        };
+
+        let elapsed = start_time.elapsed().as_secs_f64();
+        info!("");
+        info!("Simulation complete in {:.2} seconds", elapsed);
+        let (converged, total) = state_stats.converged(config.target_sem);
+        info!("{}/{} state-actions converged to target SEM", converged, total);
+        state_stats
+    };
+
+    match (options.command, options.query) {
+        (cli::Command::Quiz, _) => {
+            println!();
+            quiz::run(&rules, &state_stats);
+            return;
+        }
+        (cli::Command::Query, Some(state)) => {
+            println!();
+            print!("{}", format_query(&state, state_stats.get(&state).expect("queried state is solved")));
+            return;
         }
-        pending_tasks = new_pending;
+        _ => {}
     }
 
-    let elapsed = start_time.elapsed().as_secs_f64();
-    println!();
-    println!("Simulation complete in {:.2} seconds", elapsed);
-    println!("All state-actions converged to target SEM");
-
     println!();
     println!("============================================================");
     println!("OPTIMAL STRATEGY TABLES");
     println!("============================================================");
     println!();
 
-    let output = format_strategy_tables(&state_stats);
+    // Further solves start from scratch and leave the checkpoint to the main solve
+    let side_config = SolverConfig { checkpoint: None, resume: None, ..config.clone() };
+
+    let mut output = format_strategy_tables(&rules, options.true_count, &state_stats);
     println!("{}", output);
 
-    let mut file = File::create("strategy_output.md").expect("Failed to create file");
-    writeln!(file, "# Optimal Blackjack Strategy\n").unwrap();
-    writeln!(file, "Evolution Live Blackjack Rules\n").unwrap();
-    write!(file, "{}", output).unwrap();
-    println!("\nStrategy saved to: strategy_output.md");
+    let summary = [format_house_edge(&rules, &state_stats), format_even_money(&rules), format_dealer_outcomes(&rules)];
+    for section in summary {
+        println!("{}", section);
+        output.push('\n');
+        output.push_str(&section);
+    }
 
-    println!();
-    print_close_decisions(&state_stats);
-}
+    if options.full {
+        let outcomes = outcome_frequencies(&rules, &state_stats, OUTCOME_ROUNDS);
+        let mut sections = vec![format_full_table(&state_stats), format_outcomes(&outcomes)];
+        if rules.max_splits > 1 {
+            sections.push(format_split_hands(&split_hand_distribution(&rules, OUTCOME_ROUNDS)));
+        }
+        for section in sections {
+            println!("{}", section);
+            output.push('\n');
+            output.push_str(&section);
+        }
+    }
 
-fn get_best_action(actions: &HashMap<Action, ActionStats>) -> (Action, f64) {
-    actions
-        .iter()
-        .filter(|(_, stats)| stats.n > 0)
-        .max_by(|(_, a), (_, b)| a.ev().partial_cmp(&b.ev()).unwrap())
-        .map(|(&action, stats)| (action, stats.ev()))
-        .unwrap_or((Action::Stand, f64::NEG_INFINITY))
-}
+    if options.validate {
+        let section = format_validation(&compare_exact(&rules, &state_stats), VALIDATION_ROWS);
+        println!("{}", section);
+        output.push('\n');
+        output.push_str(&section);
+    }
 
-fn format_strategy_tables(state_stats: &HashMap<PlayerState, HashMap<Action, ActionStats>>) -> String {
-    let mut output = String::new();
-    let dealer_cards = ["2", "3", "4", "5", "6", "7", "8", "9", "10", "A"];
-
-    // Hard totals
-    output.push_str("## Hard Totals Strategy\n\n");
-    output.push_str("| Hand | ");
-    output.push_str(&dealer_cards.join(" | "));
-    output.push_str(" |\n|------|");
-    output.push_str(&vec!["---"; 10].join("|"));
-    output.push_str("|\n");
-
-    for total in (5..=17).rev() {
-        output.push_str(&format!("| **{}** |", total));
-        for dealer in 2..=11 {
-            let state = PlayerState::new(total, dealer, false, false);
-            if let Some(actions) = state_stats.get(&state) {
-                let (best, _) = get_best_action(actions);
-                output.push_str(&format!(" {} |", best.symbol()));
-            } else {
-                output.push_str(" - |");
-            }
-        }
+    if options.curves {
+        let section = format_hit_stand_curves(&state_stats);
+        println!("{}", section);
         output.push('\n');
+        output.push_str(&section);
     }
-    output.push('\n');
 
-    // Soft totals
-    output.push_str("## Soft Totals Strategy\n\n");
-    output.push_str("| Hand | ");
-    output.push_str(&dealer_cards.join(" | "));
-    output.push_str(" |\n|------|");
-    output.push_str(&vec!["---"; 10].join("|"));
-    output.push_str("|\n");
-
-    for total in (13..=20).rev() {
-        output.push_str(&format!("| **A,{}** |", total - 11));
-        for dealer in 2..=11 {
-            let state = PlayerState::new(total, dealer, true, false);
-            if let Some(actions) = state_stats.get(&state) {
-                let (best, _) = get_best_action(actions);
-                output.push_str(&format!(" {} |", best.symbol()));
-            } else {
-                output.push_str(" - |");
-            }
-        }
+    if options.eor {
+        let section = format_effect_of_removal(&effect_of_removal(&rules));
+        println!("{}", section);
         output.push('\n');
+        output.push_str(&section);
     }
-    output.push('\n');
 
-    // Pairs
-    output.push_str("## Pairs Strategy\n\n");
-    output.push_str("| Hand | ");
-    output.push_str(&dealer_cards.join(" | "));
-    output.push_str(" |\n|------|");
-    output.push_str(&vec!["---"; 10].join("|"));
-    output.push_str("|\n");
-
-    for card in [11, 10, 9, 8, 7, 6, 5, 4, 3, 2] {
-        let (label, total, is_soft) = if card == 11 {
-            ("A,A".to_string(), 12, true)
+    if options.side_bets {
+        let section = format_side_bets(&rules);
+        println!("{}", section);
+        output.push('\n');
+        output.push_str(&section);
+    }
+
+    let count_solves = (options.index_plays || options.kelly).then(|| {
+        info!("Solving true counts {:+} to {:+}...", MIN_TRUE_COUNT, MAX_TRUE_COUNT);
+        if options.kelly {
+            solve_kelly_counts(&all_states, &rules, &side_config, progress)
         } else {
-            (format!("{},{}", card, card), card * 2, false)
-        };
-        output.push_str(&format!("| **{}** |", label));
-        for dealer in 2..=11 {
-            let state = PlayerState::new(total, dealer, is_soft, true);
-            if let Some(actions) = state_stats.get(&state) {
-                let (best, _) = get_best_action(actions);
-                output.push_str(&format!(" {} |", best.symbol()));
-            } else {
-                output.push_str(" - |");
-            }
+            solve_true_counts(&all_states, &rules, &side_config, progress)
+        }
+    });
+    if let Some(solves) = &count_solves {
+        let mut sections = Vec::new();
+        if options.index_plays {
+            sections.push(format_index_plays(&find_index_plays(&all_states, solves)));
+        }
+        if options.kelly {
+            let table = kelly_table(&rules, solves);
+            sections.push(format_kelly_table(&table, options.bankroll, options.bet, options.kelly_fraction, KELLY_CAP));
+        }
+        for section in sections {
+            println!();
+            println!("{}", section);
+            output.push('\n');
+            output.push_str(&section);
         }
-        output.push('\n');
     }
+
+    let flips = count_solves.as_deref().map(ten_split_counts);
+    let section = format_ten_split(&state_stats, flips.as_deref());
+    println!();
+    println!("{}", section);
     output.push('\n');
+    output.push_str(&section);
+
+    if options.compare_decks {
+        let edges: Vec<(Option<u8>, f64)> = COMPARED_DECKS
+            .iter()
+            .map(|&n| Some(n))
+            .chain([None])
+            .map(|num_decks| {
+                info!("Solving {}...", format_decks(num_decks));
+                let rules = RuleSet { num_decks, ..rules.clone() };
+                let solved = solve_with_rules_and_playout(&all_states, &rules, &BasicStrategy, &side_config, Progress::Quiet);
+                (num_decks, house_edge(&rules, &solved))
+            })
+            .collect();
+        let section = format_deck_comparison(&edges);
+        println!();
+        println!("{}", section);
+        output.push('\n');
+        output.push_str(&section);
+    }
+
+    match options.format {
+        cli::ReportFormat::Markdown => save_report(&rules, &output),
+        cli::ReportFormat::Html => {
+            let html = format_html_chart(&rules, options.true_count, &state_stats);
+            std::fs::write("strategy_output.html", html).expect("Failed to write HTML chart");
+            info!("\nStrategy saved to: strategy_output.html");
+        }
+        #[cfg(feature = "charts")]
+        cli::ReportFormat::Heatmap(metric) => {
+            write_heatmap("strategy_output.png", &state_stats, metric).expect("Failed to write heatmap");
+            info!("\nHeatmap saved to: strategy_output.png");
+        }
+    }
+
+    if let Some(path) = &options.emit_rust {
+        let source = rust_lookup_source(&rules, &StrategyTable::from_stats(&state_stats));
+        std::fs::write(path, source).expect("Failed to write Rust lookup");
+        info!("Rust lookup saved to: {}", path);
+    }
+
+    println!();
+    print_close_decisions(&state_stats, &options);
+
+    if let Some(table) = &options.evaluate {
+        println!();
+        print_strategy_evaluation(&rules, &state_stats, table);
+    }
+
+    if options.risk_of_ruin {
+        println!();
+        print_risk_of_ruin(&rules, &state_stats, &options);
+    }
+
+    if options.hourly {
+        println!();
+        print_hourly(&rules, &state_stats, &options);
+    }
 
-    // Legend
-    output.push_str("## Legend\n\n");
-    output.push_str("- **H** = Hit\n- **S** = Stand\n- **D** = Double (if not allowed, Hit)\n");
-    output.push_str("- **P** = Split\n- **R** = Surrender (if not allowed, Hit)\n\n");
-    output.push_str("### Rules Used\n\n");
-    output.push_str("- 8 Decks (Infinite deck approximation)\n- Dealer Stands on All 17s (S17)\n");
-    output.push_str("- Double After Split (DAS) allowed\n- Late Surrender allowed\n");
-    output.push_str("- No Peek / European No Hole Card (ENHC)\n- Split once only (max 2 hands)\n");
-    output.push_str("- One card only to split Aces\n");
-
-    output
+    if let Some(ramp) = &options.spread {
+        println!();
+        print_bet_spread(&rules, &state_stats, ramp, &options);
+    }
 }
 
-fn print_close_decisions(state_stats: &HashMap<PlayerState, HashMap<Action, ActionStats>>) {
+/// An action with its statistics
+type RankedAction<'a> = (Action, &'a ActionStats);
+
+fn print_close_decisions(state_stats: &StateStats, options: &cli::Options) {
     println!("============================================================");
-    println!("CLOSE DECISIONS (EV difference < 0.02)");
+    println!("CLOSE DECISIONS (EV difference < {})", options.close_threshold);
     println!("============================================================\n");
-    println!("{:<20} {:>6} {:>10} {:>6} {:>10} {:>10}", "State", "Best", "EV", "2nd", "EV", "Diff");
-    println!("{}", "-".repeat(70));
+    println!(
+        "{:<20} {:>6} {:>10} {:>8} {:>6} {:>10} {:>8} {:>10}",
+        "State", "Best", "EV", "SEM", "2nd", "EV", "SEM", "Diff"
+    );
+    println!("{}", "-".repeat(85));
 
-    let mut close: Vec<(String, Action, f64, Action, f64, f64)> = Vec::new();
+    let mut close: Vec<(PlayerState, RankedAction, RankedAction, f64)> = Vec::new();
 
-    for (state, actions) in state_stats {
-        let mut evs: Vec<(Action, f64)> = actions.iter()
+    for (state, actions) in state_stats.iter() {
+        let mut evs: Vec<RankedAction> = Action::ALL.iter().copied().zip(actions)
             .filter(|(_, s)| s.n > 0)
-            .map(|(&a, s)| (a, s.ev()))
             .collect();
         if evs.len() < 2 { continue; }
-        evs.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        evs.sort_by(|a, b| b.1.ev().total_cmp(&a.1.ev()));
 
-        let diff = evs[0].1 - evs[1].1;
-        if diff < 0.02 {
-            close.push((format_state(state), evs[0].0, evs[0].1, evs[1].0, evs[1].1, diff));
+        let diff = evs[0].1.ev() - evs[1].1.ev();
+        if diff < options.close_threshold {
+            close.push((*state, evs[0], evs[1], diff));
         }
     }
 
-    close.sort_by(|a, b| a.5.partial_cmp(&b.5).unwrap());
-    for (s, b, bev, sec, sev, d) in close.iter().take(25) {
-        println!("{:<20} {:>6} {:>+10.4} {:>6} {:>+10.4} {:>10.4}", s, b.symbol(), bev, sec.symbol(), sev, d);
+    match options.close_sort {
+        cli::CloseSort::Diff => close.sort_by(|a, b| a.3.total_cmp(&b.3)),
+        cli::CloseSort::State => close.sort_by_key(|(state, ..)| state.index()),
+        cli::CloseSort::Ev => close.sort_by(|a, b| b.1 .1.ev().total_cmp(&a.1 .1.ev())),
+    }
+    for (s, (b, best), (sec, second), d) in close.iter().take(options.close_limit) {
+        println!(
+            "{:<20} {:>6} {:>+10.4} {:>8.4} {:>6} {:>+10.4} {:>8.4} {:>10.4}",
+            s, b.symbol(), best.ev(), best.sem(), sec.symbol(), second.ev(), second.sem(), d
+        );
     }
 }
 
-fn format_state(state: &PlayerState) -> String {
-    let d = if state.dealer_upcard == 11 { "A".to_string() } else { state.dealer_upcard.to_string() };
-    if state.is_pair {
-        if state.is_soft { format!("A,A vs {}", d) }
-        else { format!("{},{} vs {}", state.total/2, state.total/2, d) }
-    } else if state.is_soft {
-        format!("A,{} vs {}", state.total - 11, d)
-    } else {
-        format!("Hard {} vs {}", state.total, d)
+fn print_strategy_evaluation(rules: &RuleSet, state_stats: &StateStats, table: &StrategyTable) {
+    println!("============================================================");
+    println!("STRATEGY EVALUATION");
+    println!("============================================================\n");
+
+    let played = table.iter().count();
+    println!("Chart sets {} of {} states; the rest play the solved best action", played, state_stats.len());
+    let ev = strategy_ev(rules, state_stats, table);
+    let cost = strategy_cost(rules, state_stats, table);
+    println!("{:<34} {:>+8.3}%", "EV per hand (chart):", 100.0 * ev);
+    println!("{:<34} {:>+8.3}%", "EV per hand (optimal):", 100.0 * (ev + cost));
+    println!("{:<34} {:>8.3}%", "Cost per hand:", 100.0 * cost);
+}
+
+fn print_risk_of_ruin(rules: &RuleSet, state_stats: &StateStats, options: &cli::Options) {
+    println!("============================================================");
+    println!("RISK OF RUIN");
+    println!("============================================================\n");
+
+    let (card_probs, shoe_cards) = rules_composition(rules);
+    let round = round_stats(rules, state_stats, &card_probs, shoe_cards);
+    println!("Per-round EV: {:+.4}  SD: {:.4}", round.ev, round.std_dev);
+    println!("Bankroll: {}  Bet: {}  Rounds: {}", options.bankroll, options.bet, options.rounds);
+
+    let analytic = risk_of_ruin(round.ev, round.std_dev, options.bankroll, options.bet, options.rounds);
+    println!("{:<34} {:>7.2}%", "Analytic (diffusion):", 100.0 * analytic);
+
+    let simulated = simulated_risk_of_ruin(
+        rules, state_stats, shoe_decks(rules), options.bankroll, options.bet, options.rounds, RUIN_TRIALS,
+    );
+    println!("{:<34} {:>7.2}%", format!("Simulated ({} sessions):", RUIN_TRIALS), 100.0 * simulated);
+
+    let lifetime = lifetime_risk_of_ruin(round.ev, round.std_dev, options.bankroll, options.bet);
+    println!("{:<34} {:>7.2}%", "Lifetime (exponential):", 100.0 * lifetime);
+
+    let target = options.target.unwrap_or(2.0 * options.bankroll);
+    let before_target = simulated_ruin_before_target(
+        rules, state_stats, shoe_decks(rules), options.bankroll, options.bet, target, RUIN_TRIALS,
+    );
+    println!("{:<34} {:>7.2}%", format!("Ruin before reaching {}:", target), 100.0 * before_target);
+}
+
+fn print_hourly(rules: &RuleSet, state_stats: &StateStats, options: &cli::Options) {
+    println!("============================================================");
+    println!("EV PER HOUR");
+    println!("============================================================\n");
+
+    let (card_probs, shoe_cards) = rules_composition(rules);
+    let round = round_stats(rules, state_stats, &card_probs, shoe_cards);
+    let hour = round.per_hour(options.bet, options.rounds_per_hour);
+    println!("Bet: {}  Rounds/hour: {}", options.bet, options.rounds_per_hour);
+    println!("{:<34} {:>+10.4}  SD {:.4}", "Per round (units):", round.ev, round.std_dev);
+    println!("{:<34} {:>+10.2}  SD {:.2}", "Per hour:", hour.ev, hour.std_dev);
+    println!("{:<34} {:>10}", "N0 (rounds):", format_n0(round.n0()));
+    if options.spread.is_some() {
+        println!("Count-based play with the spread's ramp is under BET SPREAD");
+    }
+}
+
+/// N0 in whole rounds, or "never" for a game without an edge
+fn format_n0(n0: Option<f64>) -> String {
+    n0.map_or_else(|| "never".to_string(), |rounds| format!("{:.0}", rounds))
+}
+
+fn print_bet_spread(rules: &RuleSet, state_stats: &StateStats, ramp: &[(f64, f64)], options: &cli::Options) {
+    println!("============================================================");
+    println!("BET SPREAD");
+    println!("============================================================\n");
+    println!(
+        "Unit: {}  Penetration: {:.0}%  Hands: {}  Rounds/hour: {}",
+        options.bet, 100.0 * options.penetration, options.hands, options.rounds_per_hour
+    );
+    println!();
+    println!(
+        "{:<10} {:>10} {:>12} {:>10} {:>12} {:>10} {:>10} {:>12} {:>12}",
+        "Betting", "Avg bet", "EV/hand", "SD/hand", "EV/hour", "SD/hour", "Edge", "Rounds/shoe", "N0"
+    );
+    println!("{}", "-".repeat(106));
+
+    let mut bettors = vec![
+        ("Flat", BetSizing::Flat(options.bet), None),
+        ("Spread", BetSizing::CountRamp { unit: options.bet, ramp: ramp.to_vec() }, None),
+    ];
+    if options.wonging.is_some() {
+        bettors.push(("Wong", BetSizing::Flat(options.bet), options.wonging));
+    }
+    let mut results = Vec::new();
+    for (name, sizing, wonging) in &bettors {
+        let (penetration, hands, hands_per_hour) = (options.penetration, options.hands, options.rounds_per_hour);
+        let result = simulate_spread(rules, state_stats, sizing, *wonging, penetration, hands, hands_per_hour);
+        println!(
+            "{:<10} {:>10.2} {:>+12.4} {:>10.3} {:>+12.2} {:>10.2} {:>+9.3}% {:>12.1} {:>12}",
+            name, result.average_bet, result.ev_per_hand, result.std_dev_per_hand, result.ev_per_hour,
+            result.std_dev_per_hour, 100.0 * result.advantage, result.rounds_per_shoe, format_n0(result.n0())
+        );
+        results.push(result);
+    }
+
+    if let (Some(wonging), [flat, _, wong]) = (options.wonging, results.as_slice()) {
+        println!();
+        println!(
+            "Wonging in at TC {:+} and out below TC {:+} bets {:.1}% of rounds, {:+.2} EV/hour over flat betting",
+            wonging.wong_in_tc, wonging.wong_out_tc, 100.0 * wong.fraction_played, wong.ev_per_hour - flat.ev_per_hour
+        );
     }
 }
+
+
+/// Solve, print and save the double exposure chart, which replaces the upcard chart and its analyses
+fn solve_double_exposure(rules: &RuleSet, config: &SolverConfig, progress: Progress) {
+    info!("Double exposure: both dealer cards face up, ties lose");
+    info!("Total states to analyze: {}", generate_exposed_states().len());
+
+    let start_time = Instant::now();
+    let results = solve_exposed(rules, config, progress);
+    info!("");
+    info!("Simulation complete in {:.2} seconds", start_time.elapsed().as_secs_f64());
+
+    println!();
+    println!("============================================================");
+    println!("DOUBLE EXPOSURE STRATEGY TABLES");
+    println!("============================================================");
+    println!();
+    let output = format_exposed_tables(rules, &results);
+    println!("{}", output);
+    save_report(rules, &output);
+}
+
+/// Write the report for `rules` to strategy_output.md
+fn save_report(rules: &RuleSet, output: &str) {
+    let mut file = File::create("strategy_output.md").expect("Failed to create file");
+    writeln!(file, "# Optimal Blackjack Strategy\n").unwrap();
+    writeln!(file, "Rules: {}\n", rules).unwrap();
+    write!(file, "{}", output).unwrap();
+    info!("\nStrategy saved to: strategy_output.md");
+}
@@ -0,0 +1,220 @@
+//! Continuation play: how a hand is finished after the decision being simulated.
+//! The engine asks a `PlayoutStrategy` for every later action, including split hands.
+
+use crate::deck::{Hand, HandValue};
+use crate::engine::Action;
+use crate::exact::{hand_evs, HandEvTable};
+use crate::rules::RuleSet;
+use std::str::FromStr;
+
+/// Chooses the next action for a hand already in play.
+/// Stand ends the hand and Double is taken on a two-card split hand when the rules allow DAS.
+/// Double otherwise, Split and Surrender are not available mid-hand and are played as Hit.
+pub trait PlayoutStrategy {
+    fn next_action(&self, hand: &Hand, upcard: u8) -> Action;
+
+    /// Next action with the hole card face up as well, asked instead of `next_action` under double exposure.
+    /// Strategies that only read the upcard ignore the hole card.
+    #[inline(always)]
+    fn next_action_exposed(&self, hand: &Hand, upcard: u8, _hole: u8) -> Action {
+        self.next_action(hand, upcard)
+    }
+}
+
+impl<P: PlayoutStrategy> PlayoutStrategy for &P {
+    #[inline(always)]
+    fn next_action(&self, hand: &Hand, upcard: u8) -> Action {
+        (**self).next_action(hand, upcard)
+    }
+
+    #[inline(always)]
+    fn next_action_exposed(&self, hand: &Hand, upcard: u8, hole: u8) -> Action {
+        (**self).next_action_exposed(hand, upcard, hole)
+    }
+}
+
+/// Threshold continuation play, configurable to test how sensitive the solved EVs are to it.
+/// Hit, Double and Split EVs are measured with every later decision made by this policy, so
+/// a poor policy biases them low (and can flip close decisions); Stand and Surrender are unaffected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContinuationPolicy {
+    /// Stand on hard totals from this value
+    pub hard_stand: u8,
+    /// Stand on soft totals from this value
+    pub soft_stand: u8,
+    /// Stand on hard totals from this value against a weak upcard
+    pub stiff_stand: u8,
+    /// Highest dealer upcard counted as weak
+    pub weak_upcard: u8,
+    /// Two-card hard totals doubled, inclusive
+    pub hard_double: (u8, u8),
+    /// Two-card soft totals doubled, inclusive
+    pub soft_double: (u8, u8),
+}
+
+impl ContinuationPolicy {
+    /// Stand on hard 17+, soft 18+, and hard 12+ against 2-6; double a two-card hard 9-11 or soft 16-18
+    pub const BASIC: ContinuationPolicy = ContinuationPolicy {
+        hard_stand: 17,
+        soft_stand: 18,
+        stiff_stand: 12,
+        weak_upcard: 6,
+        hard_double: (9, 11),
+        soft_double: (16, 18),
+    };
+}
+
+impl Default for ContinuationPolicy {
+    fn default() -> Self {
+        ContinuationPolicy::BASIC
+    }
+}
+
+impl PlayoutStrategy for ContinuationPolicy {
+    #[inline(always)]
+    fn next_action(&self, hand: &Hand, upcard: u8) -> Action {
+        let HandValue { total, is_soft, .. } = hand.value();
+        if hand.len() == 2 {
+            let (low, high) = if is_soft { self.soft_double } else { self.hard_double };
+            if (low..=high).contains(&total) { return Action::Double; }
+        }
+
+        let stand = if is_soft {
+            total >= self.soft_stand
+        } else {
+            total >= self.hard_stand || (total >= self.stiff_stand && upcard <= self.weak_upcard)
+        };
+        if stand { Action::Stand } else { Action::Hit }
+    }
+}
+
+/// Overrides of `BASIC` as comma-separated `key=value` pairs, e.g. `soft=19,stiff=13,double-hard=10-11`.
+/// Keys: `hard`, `soft`, `stiff`, `weak`, `double-hard` and `double-soft`.
+impl FromStr for ContinuationPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut policy = ContinuationPolicy::BASIC;
+        for setting in s.split(',') {
+            let invalid = || format!("invalid continuation setting '{}'", setting.trim());
+            let (key, value) = setting.split_once('=').ok_or_else(invalid)?;
+            let total = || value.trim().parse::<u8>().map_err(|_| invalid());
+            let range = || {
+                let (low, high) = value.split_once('-').ok_or_else(invalid)?;
+                Ok::<_, String>((low.trim().parse().map_err(|_| invalid())?, high.trim().parse().map_err(|_| invalid())?))
+            };
+            match key.trim() {
+                "hard" => policy.hard_stand = total()?,
+                "soft" => policy.soft_stand = total()?,
+                "stiff" => policy.stiff_stand = total()?,
+                "weak" => policy.weak_upcard = total()?,
+                "double-hard" => policy.hard_double = range()?,
+                "double-soft" => policy.soft_double = range()?,
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(policy)
+    }
+}
+
+/// `ContinuationPolicy::BASIC` as a zero-sized strategy, the engine's default playout
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BasicStrategy;
+
+impl PlayoutStrategy for BasicStrategy {
+    #[inline(always)]
+    fn next_action(&self, hand: &Hand, upcard: u8) -> Action {
+        ContinuationPolicy::BASIC.next_action(hand, upcard)
+    }
+}
+
+/// Threshold play against both dealer cards for double exposure, where ties lose.
+/// Against a dealer total that stands, hit until ahead of it; otherwise play `BASIC` as against an upcard
+/// standing in for the dealer's hand, so a stiff counts as weak and a soft total as an ace.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExposedStrategy {
+    /// Dealer hits soft 17, as in `RuleSet::hit_soft_17`
+    pub hit_soft_17: bool,
+    /// Lowest total the dealer stands on, as in `RuleSet::dealer_stands_on`
+    pub dealer_stands_on: u8,
+}
+
+impl PlayoutStrategy for ExposedStrategy {
+    #[inline(always)]
+    fn next_action(&self, hand: &Hand, upcard: u8) -> Action {
+        ContinuationPolicy::BASIC.next_action(hand, upcard)
+    }
+
+    #[inline(always)]
+    fn next_action_exposed(&self, hand: &Hand, upcard: u8, hole: u8) -> Action {
+        let dealer = Hand::from_cards(upcard, hole).value();
+        let dealer_stands = dealer.dealer_stands(self.dealer_stands_on, self.hit_soft_17);
+        if dealer_stands {
+            return if hand.value().total > dealer.total { Action::Stand } else { Action::Hit };
+        }
+        // A hard total of 11 or less plays like that upcard, a stiff like a weak one and a soft total like an ace
+        let upcard = if dealer.is_soft { 11 } else if dealer.total >= 12 { 2 } else { dealer.total };
+        ContinuationPolicy::BASIC.next_action(hand, upcard)
+    }
+}
+
+/// Highest-EV hit, stand or double from the exact infinite-deck solution of every total
+#[derive(Debug, Clone)]
+pub struct OptimalStrategy {
+    /// Exact EVs per dealer upcard, indexed by upcard - 2
+    evs: Vec<HandEvTable>,
+}
+
+impl OptimalStrategy {
+    /// Solve every total exactly under `rules` with the rules' deck type
+    pub fn new(rules: &RuleSet) -> Self {
+        let card_probs = rules.deck_type.probabilities();
+        OptimalStrategy { evs: (2..=11).map(|upcard| hand_evs(rules, upcard, &card_probs)).collect() }
+    }
+}
+
+impl PlayoutStrategy for OptimalStrategy {
+    #[inline(always)]
+    fn next_action(&self, hand: &Hand, upcard: u8) -> Action {
+        let HandValue { total, is_soft, .. } = hand.value();
+        if total > 21 { return Action::Stand; }
+        let evs = &self.evs[(upcard - 2) as usize][is_soft as usize][total as usize];
+        if hand.len() == 2 && evs.double > evs.hit.max(evs.stand) {
+            Action::Double
+        } else if evs.hit > evs.stand {
+            Action::Hit
+        } else {
+            Action::Stand
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::{InfiniteDeck, PlayerState};
+    use crate::engine::BlackjackEngine;
+
+    /// Deliberately bad continuation: hits every hand until it busts
+    struct AlwaysHit;
+
+    impl PlayoutStrategy for AlwaysHit {
+        fn next_action(&self, _hand: &Hand, _upcard: u8) -> Action {
+            Action::Hit
+        }
+    }
+
+    #[test]
+    fn always_hit_continuation_has_low_ev() {
+        let rules = RuleSet { num_decks: None, ..RuleSet::default() };
+        for state in [PlayerState::new(12, 6, false, false), PlayerState::new(9, 10, false, false)] {
+            let mut bad = BlackjackEngine::with_playout(InfiniteDeck::new(), rules.clone(), AlwaysHit);
+            let mut basic = BlackjackEngine::with_playout(InfiniteDeck::new(), rules.clone(), BasicStrategy);
+            let bad_ev = bad.simulate_batch(&state, Action::Hit, 100_000).ev();
+            let basic_ev = basic.simulate_batch(&state, Action::Hit, 100_000).ev();
+            // Without a Charlie, a hand that never stands always busts
+            assert_eq!(bad_ev, -1.0, "{} always hitting", state);
+            assert!(basic_ev > bad_ev + 0.5, "{} basic {} vs always hitting {}", state, basic_ev, bad_ev);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Solver progress: a live `indicatif` bar with an ETA on a terminal, periodic lines for logs, or JSON events.
+//! The ETA extrapolates the pair convergence rate so far, so it runs short while stragglers remain.
+
+use crate::deck::PlayerState;
+use crate::engine::{Action, ActionStats};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::fs::File;
+use std::io::{IsTerminal, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tracing::{debug, info, trace, Level};
+
+/// Width of the bar in characters
+const BAR_WIDTH: usize = 40;
+
+/// Iterations between progress lines
+const LINE_INTERVAL: u32 = 5;
+
+/// Destination of JSON progress events, shared by every solve of a run
+pub type JsonSink = Arc<Mutex<dyn Write + Send>>;
+
+/// How a solve reports its progress
+#[derive(Clone)]
+pub enum Progress {
+    /// Print nothing
+    Quiet,
+    /// A line every few iterations
+    Lines,
+    /// A bar redrawn in place every iteration
+    Bar,
+    /// One JSON object per line each iteration, for external UIs to tail
+    Json(JsonSink),
+}
+
+impl Progress {
+    /// `Bar` when stdout is a terminal, `Lines` when it is redirected or `no_progress` (`--no-progress`) is set
+    pub fn for_stdout(no_progress: bool) -> Self {
+        if !no_progress && std::io::stdout().is_terminal() { Progress::Bar } else { Progress::Lines }
+    }
+
+    /// JSON events written to the file at `path`, or to stdout for `-`
+    pub fn json(path: &str) -> std::io::Result<Self> {
+        let sink: JsonSink = if path == "-" {
+            Arc::new(Mutex::new(std::io::stdout()))
+        } else {
+            Arc::new(Mutex::new(File::create(path)?))
+        };
+        Ok(Progress::Json(sink))
+    }
+
+    #[inline(always)]
+    pub fn is_quiet(&self) -> bool {
+        matches!(self, Progress::Quiet)
+    }
+}
+
+/// Renders converged / total state-action pairs in the chosen style
+pub(crate) struct ProgressReporter {
+    mode: Progress,
+    total: usize,
+    start: Instant,
+    /// Live bar of `Progress::Bar`, drawn while info lines are printed
+    bar: Option<ProgressBar>,
+}
+
+impl ProgressReporter {
+    pub(crate) fn new(mode: Progress, total: usize) -> Self {
+        let bar = (matches!(mode, Progress::Bar) && tracing::enabled!(Level::INFO)).then(|| {
+            let template =
+                format!("[{{bar:{}}}] {{pos}}/{{len}} ({{percent}}%) {{msg}}, elapsed {{elapsed}}, ETA {{eta}}", BAR_WIDTH);
+            let style = ProgressStyle::with_template(&template).expect("valid bar template").progress_chars("#-");
+            ProgressBar::with_draw_target(Some(total as u64), ProgressDrawTarget::stdout()).with_style(style)
+        });
+        ProgressReporter { mode, total, start: Instant::now(), bar }
+    }
+
+    /// Whether the solve logs to the console; a quiet or JSON solve does not
+    #[inline(always)]
+    fn console(&self) -> bool {
+        matches!(self.mode, Progress::Lines | Progress::Bar)
+    }
+
+    /// Log each pair with its EV, SEM and hands, in the solver's `iteration` span: pending pairs at
+    /// `DEBUG`, and those `pairs` flags converged at `TRACE`
+    pub(crate) fn pairs<'a>(&self, pairs: impl FnOnce() -> Vec<(PlayerState, Action, &'a ActionStats, bool)>) {
+        if !self.console() || !tracing::enabled!(Level::DEBUG) { return; }
+        let log = || {
+            for (state, action, stats, converged) in pairs() {
+                let (ev, sem) = (stats.ev(), stats.sem());
+                if converged {
+                    trace!("{} {}: EV {:+.5}, SEM {:.5}, {} hands (converged)", state, action, ev, sem, stats.n);
+                } else {
+                    debug!("{} {}: EV {:+.5}, SEM {:.5}, {} hands", state, action, ev, sem, stats.n);
+                }
+            }
+        };
+        // Print above the bar, keeping its line intact
+        match &self.bar {
+            Some(bar) => bar.suspend(log),
+            None => log(),
+        }
+    }
+
+    /// Report the state at the start of `iteration`. `house_edge` estimates the edge
+    /// from the statistics so far and is only called for JSON events.
+    pub(crate) fn update(&self, iteration: u32, converged: usize, house_edge: impl FnOnce() -> f64) {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let percent = 100.0 * converged as f64 / self.total as f64;
+        match &self.mode {
+            Progress::Quiet => {}
+            Progress::Lines | Progress::Bar if !tracing::enabled!(Level::INFO) => {}
+            Progress::Lines => {
+                if iteration % LINE_INTERVAL != 1 { return; }
+                info!(
+                    "Iteration {}: {}/{} converged ({:.1}%), elapsed: {:.2}s",
+                    iteration, converged, self.total, percent, elapsed
+                );
+            }
+            Progress::Bar => {
+                let Some(bar) = &self.bar else { return };
+                bar.set_message(format!("iteration {}", iteration));
+                bar.set_position(converged as u64);
+            }
+            Progress::Json(sink) => {
+                // Before any hands are merged the estimate is not finite
+                let edge = house_edge();
+                let edge = if edge.is_finite() { format!("{:.6}", edge) } else { "null".to_string() };
+                let mut sink = sink.lock().unwrap();
+                writeln!(
+                    sink,
+                    "{{\"iteration\":{},\"converged_count\":{},\"total_pairs\":{},\"elapsed_secs\":{:.3},\
+                     \"current_house_edge_estimate\":{}}}",
+                    iteration, converged, self.total, elapsed, edge
+                )
+                .and_then(|_| sink.flush())
+                .ok();
+            }
+        }
+    }
+
+    /// Report the final state; leaves the bar drawn at it
+    pub(crate) fn finish(&self, iterations: u32, converged: usize, house_edge: impl FnOnce() -> f64) {
+        match (&self.mode, &self.bar) {
+            (Progress::Bar, Some(bar)) => {
+                self.update(iterations, converged, house_edge);
+                bar.finish();
+            }
+            (Progress::Json(_), _) => self.update(iterations, converged, house_edge),
+            (Progress::Quiet | Progress::Lines | Progress::Bar, _) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging;
+    use crate::playout::BasicStrategy;
+    use crate::rules::RuleSet;
+    use crate::solver::{solve_with_rules_and_playout, SolverConfig};
+
+    #[test]
+    fn debug_logging_lists_each_pending_pair_with_its_sem() {
+        let rules = RuleSet { num_decks: None, ..RuleSet::default() };
+        let config = SolverConfig { seed: Some(5), ..SolverConfig::default() };
+        let solve_at = |level| {
+            let ((), lines) = logging::capture(level, || {
+                let state = PlayerState::new(16, 10, false, false);
+                solve_with_rules_and_playout(&[state], &rules, &BasicStrategy, &config, Progress::Lines);
+            });
+            lines
+        };
+
+        let debug = solve_at(Level::DEBUG);
+        let hit = debug.iter().find(|line| line.starts_with("iteration{n=1}: Hard 16 vs 10 Hit: EV")).expect("hit logged");
+        assert!(hit.contains(", SEM 0.0"), "{}", hit);
+        assert!(debug.iter().all(|line| !line.ends_with("(converged)")));
+        assert!(debug.iter().any(|line| line.starts_with("Iteration 1: 0/4 converged")));
+
+        let info = solve_at(Level::INFO);
+        assert!(!info.is_empty() && info.iter().all(|line| !line.contains("SEM")));
+        assert!(solve_at(Level::WARN).is_empty());
+    }
+
+    #[test]
+    fn no_progress_falls_back_to_a_line_every_few_iterations() {
+        assert!(matches!(Progress::for_stdout(true), Progress::Lines));
+        let rules = RuleSet { num_decks: None, ..RuleSet::default() };
+        let config = SolverConfig { target_sem: 1e-9, max_iterations: 12, seed: Some(2), ..SolverConfig::default() };
+        let ((), lines) = logging::capture(Level::INFO, || {
+            let state = PlayerState::new(16, 10, false, false);
+            solve_with_rules_and_playout(&[state], &rules, &BasicStrategy, &config, Progress::for_stdout(true));
+        });
+        let iterations: Vec<u32> = lines
+            .iter()
+            .filter_map(|line| line.strip_prefix("Iteration ")?.split(':').next()?.parse().ok())
+            .collect();
+        assert_eq!(iterations, [1, 6, 11], "{:?}", lines);
+    }
+}
@@ -0,0 +1,191 @@
+//! Python bindings (`pyo3` feature): solve a rule set into a dict of EVs or a `Strategy`, or look up one
+//! best action. Charts solved for `best_action` are cached per rule set for the life of the interpreter.
+
+// pyo3's function macros convert each returned `PyErr` into itself
+#![allow(clippy::useless_conversion)]
+
+use crate::{
+    solve as solve_rules, solve_with_config, Action, DeckType, PlayerState, RuleSet, SolverConfig, StateStats,
+    StrategyTable,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::sync::Mutex;
+
+/// Charts already solved by `best_action`
+static TABLES: Mutex<Vec<(RuleSet, StrategyTable)>> = Mutex::new(Vec::new());
+
+/// Rules from a dict overriding the defaults. Keys: `num_decks` (int, default 8, or None for
+/// an infinite deck), `spanish` (the deck only), `hit_soft_17`, `dealer_stands_on`, `blackjack_payout`, `peek`,
+/// `obo`, `charlie`, `double_on` (`"any"`, `"9-11"`, `"10-11"` or `"none"`), `double_after_split`,
+/// `max_splits`, `split_mixed_tens`, `late_surrender`, `early_surrender`, `surrender_vs` (a list of
+/// upcards, 1 or 11 for an ace, or None for all), `double_fraction`, `spanish_bonuses`, `push_22`
+/// and `double_exposure`. The result must pass `RuleSet::validate`.
+fn rules_from_dict(dict: Option<&Bound<'_, PyDict>>) -> PyResult<RuleSet> {
+    let mut rules = RuleSet::default();
+    let Some(dict) = dict else { return Ok(rules) };
+    for (key, value) in dict.iter() {
+        let key: String = key.extract()?;
+        match key.as_str() {
+            "num_decks" => rules.num_decks = value.extract()?,
+            "spanish" => rules.deck_type = if value.extract()? { DeckType::Spanish } else { DeckType::Standard },
+            "hit_soft_17" => rules.hit_soft_17 = value.extract()?,
+            "dealer_stands_on" => rules.dealer_stands_on = value.extract()?,
+            "blackjack_payout" => rules.blackjack_payout = value.extract()?,
+            "peek" => rules.peek = value.extract()?,
+            "obo" => rules.obo = value.extract()?,
+            "charlie" => rules.charlie = value.extract()?,
+            "double_on" => {
+                rules.double_on = value.extract::<String>()?.parse().map_err(PyValueError::new_err)?;
+            }
+            "double_after_split" => rules.double_after_split = value.extract()?,
+            "max_splits" => rules.max_splits = value.extract()?,
+            "split_mixed_tens" => rules.split_mixed_tens = value.extract()?,
+            "late_surrender" | "surrender_allowed" => rules.late_surrender = value.extract()?,
+            "early_surrender" => rules.early_surrender = value.extract()?,
+            "surrender_vs" => {
+                let upcards: Option<Vec<u8>> = value.extract()?;
+                let ace_high = |card: u8| if card == 1 { 11 } else { card };
+                rules.surrender_vs = upcards.map(|upcards| upcards.into_iter().map(ace_high).collect());
+            }
+            "double_fraction" => rules.double_fraction = value.extract()?,
+            "spanish_bonuses" => rules.spanish_bonuses = value.extract()?,
+            "push_22" => rules.push_22 = value.extract()?,
+            "double_exposure" => rules.double_exposure = value.extract()?,
+            _ => return Err(PyValueError::new_err(format!("unknown rule '{}'", key))),
+        }
+    }
+    rules.validate().map_err(PyValueError::new_err)?;
+    Ok(rules)
+}
+
+/// Solve `rules`, down to `target_sem` when given (the solver's default otherwise)
+fn solve_to(py: Python<'_>, rules: &RuleSet, target_sem: Option<f64>) -> PyResult<StateStats> {
+    let mut config = SolverConfig::default();
+    if let Some(sem) = target_sem {
+        if sem <= 0.0 { return Err(PyValueError::new_err("target_sem must be positive")); }
+        config.target_sem = sem;
+    }
+    Ok(py.allow_threads(|| solve_with_config(rules, &config)))
+}
+
+/// `{"Hit": ev, "Stand": ev, ...}` of the actions simulated for one state
+fn action_evs<'py>(py: Python<'py>, stats: &StateStats, state: &PlayerState) -> PyResult<Bound<'py, PyDict>> {
+    let evs = PyDict::new_bound(py);
+    if let Some(actions) = stats.get(state) {
+        for (action, action_stats) in Action::ALL.iter().zip(actions).filter(|(_, s)| s.n > 0) {
+            evs.set_item(action.to_string(), action_stats.ev())?;
+        }
+    }
+    Ok(evs)
+}
+
+/// `{"Hard 16 vs 10": {"Hit": ev, "Stand": ev, ...}, ...}` with EVs per initial bet
+fn ev_table<'py>(py: Python<'py>, stats: &StateStats) -> PyResult<Bound<'py, PyDict>> {
+    let table = PyDict::new_bound(py);
+    for (state, _) in stats.iter() {
+        table.set_item(state.to_string(), action_evs(py, stats, state)?)?;
+    }
+    Ok(table)
+}
+
+/// Solve every state under `rules` (a dict of overrides, see `rules_from_dict`), down to `target_sem`.
+/// Returns `{"Hard 16 vs 10": {"Hit": ev, "Stand": ev, ...}, ...}` with EVs per initial bet.
+#[pyfunction]
+#[pyo3(signature = (rules=None, target_sem=None))]
+fn solve<'py>(
+    py: Python<'py>,
+    rules: Option<&Bound<'py, PyDict>>,
+    target_sem: Option<f64>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let rules = rules_from_dict(rules)?;
+    let stats = solve_to(py, &rules, target_sem)?;
+    ev_table(py, &stats)
+}
+
+/// A solved chart kept on the Rust side: `Strategy(rules=None, target_sem=None)` solves once,
+/// then answers lookups without re-solving or converting the whole table.
+#[pyclass]
+struct Strategy {
+    rules: RuleSet,
+    stats: StateStats,
+    table: StrategyTable,
+}
+
+#[pymethods]
+impl Strategy {
+    #[new]
+    #[pyo3(signature = (rules=None, target_sem=None))]
+    fn new(py: Python<'_>, rules: Option<&Bound<'_, PyDict>>, target_sem: Option<f64>) -> PyResult<Self> {
+        let rules = rules_from_dict(rules)?;
+        let stats = solve_to(py, &rules, target_sem)?;
+        let table = StrategyTable::from_stats(&stats);
+        Ok(Strategy { rules, stats, table })
+    }
+
+    /// Best action for a hand; `dealer` is 2-10, with 1 or 11 for an ace
+    #[pyo3(signature = (total, dealer, soft=false, pair=false))]
+    fn best_action(&self, total: u8, dealer: u8, soft: bool, pair: bool) -> PyResult<String> {
+        self.table
+            .lookup(total, dealer, soft, pair)
+            .map(|(action, _)| action.to_string())
+            .ok_or_else(|| PyValueError::new_err(format!("no solved state for total {} vs {}", total, dealer)))
+    }
+
+    /// `{"Hit": ev, "Stand": ev, ...}` for one hand, looked up like `best_action`
+    #[pyo3(signature = (total, dealer, soft=false, pair=false))]
+    fn evs<'py>(&self, py: Python<'py>, total: u8, dealer: u8, soft: bool, pair: bool) -> PyResult<Bound<'py, PyDict>> {
+        let dealer = if dealer == 1 { 11 } else { dealer };
+        let pair = pair || (soft && total == 12) || (!soft && total == 4);
+        action_evs(py, &self.stats, &PlayerState::new(total, dealer, soft, pair))
+    }
+
+    /// Every state's EVs, as `solve` returns them
+    fn ev_table<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        ev_table(py, &self.stats)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Strategy({})", self.rules)
+    }
+}
+
+/// Best action ("Hit", "Stand", "Double", "Split" or "Surrender") for a hand under `rules`.
+/// `dealer` is 2-10, with 1 or 11 for an ace. The first call for a rule set solves it.
+#[pyfunction]
+#[pyo3(signature = (total, dealer, is_soft=false, is_pair=false, rules=None))]
+fn best_action(
+    py: Python<'_>,
+    total: u8,
+    dealer: u8,
+    is_soft: bool,
+    is_pair: bool,
+    rules: Option<&Bound<'_, PyDict>>,
+) -> PyResult<String> {
+    let rules = rules_from_dict(rules)?;
+    let best = py.allow_threads(|| {
+        let mut tables = TABLES.lock().unwrap();
+        let idx = match tables.iter().position(|(solved, _)| *solved == rules) {
+            Some(idx) => idx,
+            None => {
+                let table = StrategyTable::from_stats(&solve_rules(&rules));
+                tables.push((rules, table));
+                tables.len() - 1
+            }
+        };
+        tables[idx].1.lookup(total, dealer, is_soft, is_pair)
+    });
+    best.map(|(action, _)| action.to_string()).ok_or_else(|| {
+        PyValueError::new_err(format!("no solved state for total {} vs {}", total, dealer))
+    })
+}
+
+/// The `blackjack_solver_rs` extension module maturin builds from this crate
+#[pymodule]
+fn blackjack_solver_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(solve, m)?)?;
+    m.add_function(wrap_pyfunction!(best_action, m)?)?;
+    m.add_class::<Strategy>()?;
+    Ok(())
+}
@@ -0,0 +1,93 @@
+//! Strategy trainer: deals random hands and grades the player's action against the solved chart.
+//! Hands are drawn from the rules' card distribution, so common hands come up as often as at the table.
+
+use blackjack_solver::analysis::rules_composition;
+use blackjack_solver::{get_best_action, Action, Hand, PlayerState, RuleSet, StateStats};
+use fastrand::Rng;
+use std::io::{BufRead, Write};
+
+/// Card value 2-11 drawn with probabilities indexed by value - 2
+fn draw(rng: &mut Rng, card_probs: &[f64; 10]) -> u8 {
+    let mut left = rng.f64() * card_probs.iter().sum::<f64>();
+    for (idx, &p) in card_probs.iter().enumerate() {
+        if left < p { return idx as u8 + 2; }
+        left -= p;
+    }
+    11
+}
+
+/// A dealt starting hand that needs a decision: no naturals, only solved states
+fn deal(rng: &mut Rng, card_probs: &[f64; 10], stats: &StateStats) -> PlayerState {
+    loop {
+        let first = draw(rng, card_probs);
+        let upcard = draw(rng, card_probs);
+        let hand = Hand::from_cards(first, draw(rng, card_probs));
+        if hand.value().total == 21 { continue; }
+        let state = PlayerState::from_hand(&hand, upcard);
+        if stats.get(&state).is_some() { return state; }
+    }
+}
+
+/// Quiz the player on stdin until they quit or input ends, then print the score and the
+/// states answered wrong most often
+pub fn run(rules: &RuleSet, stats: &StateStats) {
+    let (card_probs, _) = rules_composition(rules);
+    let mut rng = Rng::new();
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    let (mut asked, mut correct, mut ev_lost) = (0u32, 0u32, 0.0);
+    let mut mistakes: Vec<(PlayerState, u32)> = Vec::new();
+
+    println!("============================================================");
+    println!("STRATEGY QUIZ");
+    println!("============================================================\n");
+    println!("Answer H, S, D, P or R (or the action's name); q quits.\n");
+
+    'quiz: loop {
+        let state = deal(&mut rng, &card_probs, stats);
+        let actions = stats.get(&state).expect("dealt states are solved");
+        let Some((best, best_ev)) = get_best_action(actions) else { continue };
+
+        let answer = loop {
+            print!("{} > ", state);
+            std::io::stdout().flush().ok();
+            let Some(Ok(line)) = lines.next() else { break 'quiz };
+            let line = line.trim();
+            if line.eq_ignore_ascii_case("q") || line.eq_ignore_ascii_case("quit") { break 'quiz; }
+            match line.parse::<Action>() {
+                Ok(action) if actions[action.index()].n > 0 => break action,
+                Ok(action) => println!("  {} is not allowed here", action),
+                Err(err) => println!("  {}", err),
+            }
+        };
+
+        asked += 1;
+        if answer == best {
+            correct += 1;
+            println!("  Correct: {} ({:+.4})", best, best_ev);
+        } else {
+            let cost = best_ev - actions[answer.index()].ev();
+            ev_lost += cost;
+            println!("  Wrong: {} ({:+.4}), {} costs {:.4} per hand", best, best_ev, answer, cost);
+            match mistakes.iter_mut().find(|(missed, _)| *missed == state) {
+                Some((_, count)) => *count += 1,
+                None => mistakes.push((state, 1)),
+            }
+        }
+        println!("  Score: {}/{}\n", correct, asked);
+    }
+
+    println!();
+    if asked == 0 { return; }
+    println!("Final score: {}/{} ({:.1}%)", correct, asked, 100.0 * correct as f64 / asked as f64);
+    println!("EV given up: {:.4} units ({:.4} per hand)", ev_lost, ev_lost / asked as f64);
+    if mistakes.is_empty() { return; }
+
+    mistakes.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    println!("\n{:<20} {:>8}", "Missed state", "Times");
+    println!("{}", "-".repeat(29));
+    for (state, count) in &mistakes {
+        println!("{:<20} {:>8}", state, count);
+    }
+}
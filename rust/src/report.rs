@@ -0,0 +1,720 @@
+//! Markdown sections of the strategy report, and the chart as a standalone HTML page.
+//! Pure formatting over solved results; the CLI decides which sections to print and save.
+
+use crate::analysis::{even_money, house_edge, natural_probability, rules_composition};
+use crate::counting::{IndexPlay, KellyBet};
+use crate::deck::{card_name, hi_lo_tag, rank_name, rank_value, DeckType, FiniteShoe, Hand, InfiniteDeck, PlayerState};
+use crate::exact::{dealer_outcomes, DEALER_LOWEST};
+use crate::engine::{Action, ActionStats, Outcome, OutcomeCounts, SplitHandCounts, MAX_SPLIT_HANDS};
+use crate::exposure::{dealer_hands, ExposedState};
+use crate::rules::{DoubleOn, RuleSet};
+use crate::side_bets::{
+    perfect_pairs_break_even_decks, perfect_pairs_ev, twentyone_plus_three_ev, PerfectPairsPayouts,
+    TwentyOnePlusThreePayouts,
+};
+use crate::solver::{get_best_action, ActionTable, StateStats};
+use crate::validate::{Discrepancy, FLAG_SEMS};
+
+/// Chart cell of a state that was generated but never sampled, where no action can be recommended
+pub const UNSAMPLED: &str = "UNSAMPLED";
+
+/// Chart symbol of a state's best action, or `UNSAMPLED`
+fn best_symbol(actions: &ActionTable) -> &'static str {
+    get_best_action(actions).map_or(UNSAMPLED, |(best, _)| best.symbol())
+}
+
+/// Markdown hard, soft and pair charts of the best action, followed by the legend and rules
+pub fn format_strategy_tables(rules: &RuleSet, true_count: Option<f64>, state_stats: &StateStats) -> String {
+    let mut output = String::new();
+    let dealer_cards = ["2", "3", "4", "5", "6", "7", "8", "9", "10", "A"];
+
+    // Hard totals
+    output.push_str("## Hard Totals Strategy\n\n");
+    output.push_str("| Hand | ");
+    output.push_str(&dealer_cards.join(" | "));
+    output.push_str(" |\n|------|");
+    output.push_str(&["---"; 10].join("|"));
+    output.push_str("|\n");
+
+    for total in (5..=17).rev() {
+        output.push_str(&format!("| **{}** |", total));
+        for dealer in 2..=11 {
+            let state = PlayerState::new(total, dealer, false, false);
+            if let Some(actions) = state_stats.get(&state) {
+                output.push_str(&format!(" {} |", best_symbol(actions)));
+            } else {
+                output.push_str(" - |");
+            }
+        }
+        output.push('\n');
+    }
+    output.push('\n');
+
+    // Soft totals
+    output.push_str("## Soft Totals Strategy\n\n");
+    output.push_str("| Hand | ");
+    output.push_str(&dealer_cards.join(" | "));
+    output.push_str(" |\n|------|");
+    output.push_str(&["---"; 10].join("|"));
+    output.push_str("|\n");
+
+    for total in (13..=20).rev() {
+        output.push_str(&format!("| **A,{}** |", total - 11));
+        for dealer in 2..=11 {
+            let state = PlayerState::new(total, dealer, true, false);
+            if let Some(actions) = state_stats.get(&state) {
+                output.push_str(&format!(" {} |", best_symbol(actions)));
+            } else {
+                output.push_str(" - |");
+            }
+        }
+        output.push('\n');
+    }
+    output.push('\n');
+
+    // Pairs
+    output.push_str("## Pairs Strategy\n\n");
+    output.push_str("| Hand | ");
+    output.push_str(&dealer_cards.join(" | "));
+    output.push_str(" |\n|------|");
+    output.push_str(&["---"; 10].join("|"));
+    output.push_str("|\n");
+
+    for card in [11, 10, 9, 8, 7, 6, 5, 4, 3, 2] {
+        let (label, total, is_soft) = if card == 11 {
+            ("A,A".to_string(), 12, true)
+        } else {
+            (format!("{},{}", card, card), card * 2, false)
+        };
+        output.push_str(&format!("| **{}** |", label));
+        for dealer in 2..=11 {
+            let state = PlayerState::new(total, dealer, is_soft, true);
+            if let Some(actions) = state_stats.get(&state) {
+                output.push_str(&format!(" {} |", best_symbol(actions)));
+            } else {
+                output.push_str(" - |");
+            }
+        }
+        output.push('\n');
+    }
+    output.push('\n');
+
+    output.push_str(&format_surrender(rules, state_stats));
+    output.push('\n');
+
+    // Legend
+    output.push_str("## Legend\n\n");
+    output.push_str(&format_legend(rules));
+    output.push_str("\n### Rules Used\n\n");
+    let dealer = match (rules.dealer_stands_on, rules.hit_soft_17) {
+        (17, true) => "Hits Soft 17 (H17)".to_string(),
+        (17, false) => "Stands on All 17s (S17)".to_string(),
+        (total, true) => format!("Hits Soft {0}, Stands on Hard {0} (H{0})", total),
+        (total, false) => format!("Stands on All {0}s (S{0})", total),
+    };
+    output.push_str(&format!("- {}\n- Dealer {}\n", format_decks(rules.num_decks), dealer));
+    if rules.deck_type == DeckType::Spanish {
+        output.push_str("- Spanish 21 decks: 10-spots removed\n");
+    }
+    if rules.spanish_bonuses {
+        output.push_str("- Player 21 always wins; 5-card 21 pays 3:2, 6-card 2:1, 7+ cards 3:1, 6-7-8 and 7-7-7 3:2\n");
+    }
+    if let Some(tc) = true_count {
+        output.push_str(&format!("- Shoe at Hi-Lo true count {:+}\n", tc));
+    }
+    let das = if rules.double_after_split { "allowed" } else { "not allowed" };
+    let surrender = if rules.early_surrender {
+        "Early Surrender allowed"
+    } else if rules.late_surrender {
+        "Late Surrender allowed"
+    } else {
+        "No surrender"
+    };
+    output.push_str(&format!("- Double After Split (DAS) {}\n- {}\n", das, surrender));
+    if let Some(upcards) = rules.surrender_vs.as_ref().filter(|_| rules.surrender_allowed()) {
+        let upcards: Vec<String> = upcards.iter().map(|&card| card_name(card)).collect();
+        output.push_str(&format!("- Surrender only against {}\n", upcards.join(", ")));
+    }
+    match rules.double_on {
+        DoubleOn::Any => {}
+        DoubleOn::NineToEleven => output.push_str("- Double on hard 9-11 only\n"),
+        DoubleOn::TenToEleven => output.push_str("- Double on hard 10-11 only\n"),
+        DoubleOn::Never => output.push_str("- No doubling\n"),
+    }
+    if rules.double_fraction != 1.0 {
+        output.push_str(&format!("- Doubles add {}x the original bet\n", rules.double_fraction));
+    }
+    let peek = match (rules.peek, rules.obo) {
+        (true, _) => "Dealer peeks for blackjack",
+        (false, true) => "European No Hole Card (ENHC), original bets only (OBO): doubles and splits are returned",
+        (false, false) => "No Peek / European No Hole Card (ENHC)",
+    };
+    output.push_str(&format!("- {}\n", peek));
+    if rules.max_splits > 1 {
+        output.push_str(&format!("- Resplit to {} hands (Aces split once)\n", rules.max_splits + 1));
+    } else {
+        output.push_str("- Split once only (max 2 hands)\n");
+    }
+    output.push_str("- One card only to split Aces\n");
+    if !rules.split_mixed_tens {
+        output.push_str("- Tens split only with matching ranks (10,10 but not 10,K)\n");
+    }
+    if rules.push_22 {
+        output.push_str("- Dealer 22 pushes against every standing hand\n");
+    }
+    if rules.double_exposure {
+        output.push_str("- Double exposure: both dealer cards face up, ties lose\n");
+    }
+
+    output
+}
+
+/// Background color of each action's chart cells, as in printed strategy cards
+const HTML_COLORS: [(Action, &str); 5] = [
+    (Action::Hit, "#f5e663"),
+    (Action::Stand, "#62c46b"),
+    (Action::Double, "#5fa8e8"),
+    (Action::Split, "#b58ad6"),
+    (Action::Surrender, "#e57373"),
+];
+
+/// Self-contained HTML page of the hard, soft and pair charts with every cell colored by its best
+/// action, headed by the rules and house edge. Each solved state is one `<td>`, titled with its EV.
+pub fn format_html_chart(rules: &RuleSet, true_count: Option<f64>, state_stats: &StateStats) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Blackjack Strategy</title>\n<style>\n");
+    html.push_str("body { font-family: sans-serif; }\n");
+    html.push_str("table { border-collapse: collapse; margin-bottom: 1.5em; }\n");
+    html.push_str("th, td { border: 1px solid #888; padding: 4px 8px; text-align: center; }\n");
+    for (action, color) in HTML_COLORS {
+        html.push_str(&format!("td.{} {{ background: {}; }}\n", action.symbol(), color));
+    }
+    html.push_str("</style>\n</head>\n<body>\n<h1>Optimal Blackjack Strategy</h1>\n");
+
+    html.push_str(&format!("<p>Rules: {}", rules));
+    if let Some(tc) = true_count {
+        html.push_str(&format!(", shoe at Hi-Lo true count {:+}", tc));
+    }
+    let edge = house_edge(rules, state_stats);
+    html.push_str(&format!("</p>\n<p>House edge: {:.3}%</p>\n", 100.0 * edge));
+
+    let hard: Vec<_> = (5..=21).rev().map(|total| (format!("Hard {}", total), total, false, false)).collect();
+    let soft: Vec<_> = (13..=20).rev().map(|total| (format!("A,{}", total - 11), total, true, false)).collect();
+    let pairs: Vec<_> = [11, 10, 9, 8, 7, 6, 5, 4, 3, 2]
+        .into_iter()
+        .map(|card| match card {
+            11 => ("A,A".to_string(), 12, true, true),
+            _ => (format!("{},{}", card, card), card * 2, false, true),
+        })
+        .collect();
+    for (title, rows) in [("Hard Totals", hard), ("Soft Totals", soft), ("Pairs", pairs)] {
+        html.push_str(&format!("<h2>{}</h2>\n<table>\n<tr><th>Hand</th>", title));
+        for dealer in ["2", "3", "4", "5", "6", "7", "8", "9", "10", "A"] {
+            html.push_str(&format!("<th>{}</th>", dealer));
+        }
+        html.push_str("</tr>\n");
+        for (label, total, is_soft, is_pair) in rows {
+            html.push_str(&format!("<tr><th>{}</th>", label));
+            for dealer in 2..=11 {
+                let state = PlayerState::new(total, dealer, is_soft, is_pair);
+                match state_stats.get(&state) {
+                    Some(actions) => match get_best_action(actions) {
+                        Some((best, ev)) => {
+                            let symbol = best.symbol();
+                            let title = format!("{}: {} {:+.3}", state, best, ev);
+                            html.push_str(&format!("<td class=\"{}\" title=\"{}\">{}</td>", symbol, title, symbol));
+                        }
+                        None => {
+                            html.push_str(&format!("<td title=\"{}: no action sampled\">{}</td>", state, UNSAMPLED));
+                        }
+                    },
+                    None => html.push_str("<td>-</td>"),
+                }
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("<h2>Legend</h2>\n<table class=\"legend\">\n");
+    for (action, _) in HTML_COLORS {
+        let symbol = action.symbol();
+        html.push_str(&format!("<tr><td class=\"{}\">{}</td><td>{}</td></tr>\n", symbol, symbol, action));
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+/// Markdown hard, soft and pair charts of the best action against each face-up dealer hand,
+/// with a column per dealer total (hard 4-20, then soft S12-S20)
+pub fn format_exposed_tables(rules: &RuleSet, results: &[(ExposedState, ActionTable)]) -> String {
+    let dealers = dealer_hands();
+    let labels: Vec<String> = dealers
+        .iter()
+        .map(|&(upcard, hole)| {
+            let value = Hand::from_cards(upcard, hole).value();
+            if value.is_soft { format!("S{}", value.total) } else { value.total.to_string() }
+        })
+        .collect();
+    let header = format!("| Hand | {} |\n|------|{}|\n", labels.join(" | "), vec!["---"; labels.len()].join("|"));
+
+    let mut rows: Vec<(&str, String, u8, bool, bool)> = Vec::new();
+    rows.extend((5..=20).rev().map(|total| ("Hard", total.to_string(), total, false, false)));
+    rows.extend((13..=20).rev().map(|total| ("Soft", format!("A,{}", total - 11), total, true, false)));
+    rows.push(("Pairs", "A,A".to_string(), 12, true, true));
+    rows.extend((2..=10).rev().map(|card| ("Pairs", format!("{},{}", card, card), card * 2, false, true)));
+
+    let mut output = String::new();
+    let mut table = "";
+    for (name, label, total, is_soft, is_pair) in rows {
+        if name != table {
+            if !table.is_empty() { output.push('\n'); }
+            output.push_str(&format!("## Double Exposure {} Strategy\n\n{}", name, header));
+            table = name;
+        }
+        output.push_str(&format!("| **{}** |", label));
+        for &(upcard, hole) in &dealers {
+            let state = ExposedState { player: PlayerState::new(total, upcard, is_soft, is_pair), dealer_hole: hole };
+            match results.iter().find(|(solved, _)| *solved == state) {
+                Some((_, actions)) => output.push_str(&format!(" {} |", best_symbol(actions))),
+                None => output.push_str(" - |"),
+            }
+        }
+        output.push('\n');
+    }
+
+    output.push_str("\n## Legend\n\n");
+    output.push_str(&format_legend(rules));
+    output.push_str("- Columns are the dealer's two-card total; S marks a soft total\n\n");
+    output.push_str(&format!("Rules: {}\n", rules));
+    output
+}
+
+/// Markdown list of the chart symbols, with Surrender only when the rules offer it
+fn format_legend(rules: &RuleSet) -> String {
+    let mut legend = String::from("- **H** = Hit\n- **S** = Stand\n- **D** = Double (if not allowed, Hit)\n");
+    legend.push_str("- **P** = Split\n");
+    if rules.surrender_allowed() { legend.push_str("- **R** = Surrender (if not allowed, Hit)\n"); }
+    legend
+}
+
+/// Markdown table of the hands where surrender is correct against some upcard. Each cell is the
+/// surrender EV minus the best other action's EV, marked **R** where surrender wins.
+pub fn format_surrender(rules: &RuleSet, state_stats: &StateStats) -> String {
+    let mut output = String::from("## Surrender Strategy\n\n");
+    if !rules.surrender_allowed() {
+        output.push_str("Surrender is not offered under these rules.\n");
+        return output;
+    }
+
+    // Hands in chart order against a placeholder upcard: hard totals, soft totals, then pairs
+    let hard = (5..=17).rev().map(|total| PlayerState::new(total, 2, false, false));
+    let soft = (13..=20).rev().map(|total| PlayerState::new(total, 2, true, false));
+    let pairs = (2..=10).rev().map(|card| PlayerState::new(card * 2, 2, false, true));
+    let hands = hard.chain(soft).chain([PlayerState::new(12, 2, true, true)]).chain(pairs);
+
+    let mut rows = String::new();
+    for hand in hands {
+        let margins: Vec<Option<f64>> = (2..=11)
+            .map(|dealer| {
+                let actions = state_stats.get(&PlayerState { dealer_upcard: dealer, ..hand })?;
+                surrender_margin(actions)
+            })
+            .collect();
+        if !margins.iter().any(|margin| margin.is_some_and(|m| m > 0.0)) { continue; }
+
+        let name = hand.to_string();
+        let label = name.split(" vs ").next().unwrap_or_default();
+        rows.push_str(&format!("| **{}** |", label));
+        for margin in margins {
+            match margin {
+                Some(m) if m > 0.0 => rows.push_str(&format!(" **R** {:+.3} |", m)),
+                Some(m) => rows.push_str(&format!(" {:+.3} |", m)),
+                None => rows.push_str(" - |"),
+            }
+        }
+        rows.push('\n');
+    }
+
+    if rows.is_empty() {
+        output.push_str("Surrender is never the best action under these rules.\n");
+        return output;
+    }
+    output.push_str("EV of surrendering minus the best other action, per initial bet.\n\n");
+    output.push_str("| Hand | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10 | A |\n|------|");
+    output.push_str(&["---"; 10].join("|"));
+    output.push_str("|\n");
+    output.push_str(&rows);
+    output
+}
+
+/// Surrender EV minus the best other sampled action, if surrender was sampled
+fn surrender_margin(actions: &ActionTable) -> Option<f64> {
+    let surrender = &actions[Action::Surrender.index()];
+    if surrender.n == 0 { return None; }
+    let best_other = Action::ALL
+        .iter()
+        .zip(actions)
+        .filter(|(&action, stats)| action != Action::Surrender && stats.n > 0)
+        .map(|(_, stats)| stats.ev())
+        .fold(f64::NEG_INFINITY, f64::max);
+    Some(surrender.ev() - best_other)
+}
+
+/// Markdown table of every sampled action of every state, best first, with its EV, hands and SEM
+pub fn format_full_table(state_stats: &StateStats) -> String {
+    let mut output = String::new();
+    output.push_str("## Per-Action EVs\n\n");
+    output.push_str("| State | Action | EV | Hands | SEM |\n|-------|--------|----|-------|-----|\n");
+    for (state, actions) in state_stats.iter() {
+        let mut ranked: Vec<(Action, &ActionStats)> =
+            Action::ALL.iter().copied().zip(actions).filter(|(_, stats)| stats.n > 0).collect();
+        ranked.sort_by(|a, b| b.1.ev().total_cmp(&a.1.ev()));
+        for (rank, (action, stats)) in ranked.into_iter().enumerate() {
+            let name = if rank == 0 { format!("**{}**", action) } else { action.to_string() };
+            output.push_str(&format!(
+                "| {} | {} | {:+.4} | {} | {:.4} |\n",
+                state, name, stats.ev(), stats.n, stats.sem()
+            ));
+        }
+    }
+    output
+}
+
+/// One state's best action and runner-up with their EVs and SEMs, and the margin between them; a state
+/// with no action sampled (`--max-iterations 0`) is reported as such
+pub fn format_query(state: &PlayerState, actions: &ActionTable) -> String {
+    let mut ranked: Vec<(Action, &ActionStats)> =
+        Action::ALL.iter().copied().zip(actions).filter(|(_, stats)| stats.n > 0).collect();
+    ranked.sort_by(|a, b| b.1.ev().total_cmp(&a.1.ev()));
+
+    let mut output = format!("{}\n", state);
+    let Some(&(best, best_stats)) = ranked.first() else {
+        output.push_str(&format!("  {:<11} no action sampled\n", "Not sampled:"));
+        return output;
+    };
+    output.push_str(&format!("  {:<11} {:<10} {:>+8.4} ± {:.4}\n", "Best:", best, best_stats.ev(), best_stats.sem()));
+    if let Some(&(second, stats)) = ranked.get(1) {
+        output.push_str(&format!("  {:<11} {:<10} {:>+8.4} ± {:.4}\n", "Runner-up:", second, stats.ev(), stats.sem()));
+        output.push_str(&format!("  {:<11} {:.4} per hand\n", "Margin:", best_stats.ev() - stats.ev()));
+    }
+    output
+}
+
+/// Columns of the EV axis in `format_hit_stand_curves`, spanning -1 to +1
+const CURVE_WIDTH: usize = 41;
+
+/// Hit and Stand EVs of hard 8-20 against each upcard, side by side with an ASCII plot of both on a -1 to +1
+/// axis (`H`, `S`, `*` where they meet). Each total where the better of the two changes is marked, which is
+/// where the chart's stand line falls.
+pub fn format_hit_stand_curves(state_stats: &StateStats) -> String {
+    let mut output = String::from("## Hit vs Stand by Total
+");
+    let column = |ev: f64| (((ev.clamp(-1.0, 1.0) + 1.0) / 2.0) * (CURVE_WIDTH - 1) as f64).round() as usize;
+    for upcard in 2..=11 {
+        let label = if upcard == 11 { "A".to_string() } else { upcard.to_string() };
+        output.push_str(&format!("\n### Dealer {}\n\n```\n", label));
+        output.push_str(&format!("Total    Hit   Stand  -1{:^w$}+1\n", "0", w = CURVE_WIDTH - 4));
+        let mut previous = None;
+        for total in 8..=20 {
+            let Some(actions) = state_stats.get(&PlayerState::new(total, upcard, false, false)) else { continue };
+            let (hit, stand) = (&actions[Action::Hit.index()], &actions[Action::Stand.index()]);
+            if hit.n == 0 || stand.n == 0 { continue; }
+            let (hit, stand) = (hit.ev(), stand.ev());
+
+            let mut plot = vec![b'.'; CURVE_WIDTH];
+            plot[CURVE_WIDTH / 2] = b':';
+            plot[column(hit)] = b'H';
+            plot[column(stand)] = if column(stand) == column(hit) { b'*' } else { b'S' };
+            let better = if stand >= hit { Action::Stand } else { Action::Hit };
+            let mark = match previous {
+                Some(action) if action != better => format!("  <- {} from here", better),
+                _ => String::new(),
+            };
+            previous = Some(better);
+            output.push_str(&format!(
+                "{:>5} {:+.3} {:+.3}  |{}|{}\n",
+                total,
+                hit,
+                stand,
+                String::from_utf8_lossy(&plot),
+                mark
+            ));
+        }
+        output.push_str("```\n");
+    }
+    output
+}
+
+/// Markdown table of `counting::effect_of_removal`, each rank's effect on the player's EV beside its Hi-Lo tag
+pub fn format_effect_of_removal(effects: &[f64; 13]) -> String {
+    let mut output = String::from("## Effect of Removal\n\n");
+    output.push_str("Change in the player's EV per round when one card of the rank leaves a single deck.\n\n");
+    output.push_str("| Rank | Effect | Hi-Lo |\n|------|--------|-------|\n");
+    for (idx, effect) in effects.iter().enumerate() {
+        let rank = idx as u8 + 2;
+        let tag = match hi_lo_tag(rank_value(rank)) {
+            0 => "0".to_string(),
+            tag => format!("{:+}", tag),
+        };
+        output.push_str(&format!("| {} | {:+.3}% | {} |\n", rank_name(rank), 100.0 * effect, tag));
+    }
+    output
+}
+
+/// Markdown table of how often each state's best action wins, loses and pushes (from `outcome_frequencies`).
+/// Wins include naturals paid at the blackjack payout.
+pub fn format_outcomes(outcomes: &[(PlayerState, Action, OutcomeCounts)]) -> String {
+    let mut output = String::from("## Outcome Frequencies\n\n");
+    output.push_str("| State | Action | Win | Loss | Push |\n|-------|--------|-----|------|------|\n");
+    for (state, action, counts) in outcomes {
+        let win = counts.fraction(Outcome::Win) + counts.fraction(Outcome::Blackjack);
+        output.push_str(&format!(
+            "| {} | {} | {:.1}% | {:.1}% | {:.1}% |\n",
+            state,
+            action,
+            100.0 * win,
+            100.0 * counts.fraction(Outcome::Loss),
+            100.0 * counts.fraction(Outcome::Push)
+        ));
+    }
+    output
+}
+
+/// The `limit` largest simulated-minus-exact differences from `validate::compare_exact`, flagged (**!**)
+/// beyond `FLAG_SEMS` standard errors, after a count of every flagged state-action
+pub fn format_validation(discrepancies: &[Discrepancy], limit: usize) -> String {
+    let mut output = String::from("## Monte Carlo vs Exact\n\n");
+    let flagged = discrepancies.iter().filter(|d| d.is_flagged()).count();
+    output.push_str(&format!(
+        "{} of {} state-actions differ from the exact infinite-deck EV by more than {} SEM.\n\n",
+        flagged,
+        discrepancies.len(),
+        FLAG_SEMS
+    ));
+    output.push_str("| State | Action | Simulated | Exact | Difference | SEM | z | |\n");
+    output.push_str("|-------|--------|-----------|-------|------------|-----|---|---|\n");
+    for d in discrepancies.iter().take(limit) {
+        output.push_str(&format!(
+            "| {} | {} | {:+.4} | {:+.4} | {:+.4} | {:.4} | {:+.1} | {} |\n",
+            d.state,
+            d.action,
+            d.simulated,
+            d.exact,
+            d.difference(),
+            d.sem,
+            d.z(),
+            if d.is_flagged() { "**!**" } else { "" }
+        ));
+    }
+    output
+}
+
+/// Markdown table of how many hands splitting each pair ends as (from `split_hand_distribution`),
+/// with the average, which is the initial bets a split puts out before any double
+pub fn format_split_hands(distribution: &[(u8, SplitHandCounts)]) -> String {
+    let mut output = String::from("## Hands per Split
+
+| Pair |");
+    (2..=MAX_SPLIT_HANDS).for_each(|hands| output.push_str(&format!(" {} hands |", hands)));
+    output.push_str(" Average |\n|------|");
+    (2..=MAX_SPLIT_HANDS).for_each(|_| output.push_str("---------|"));
+    output.push_str("---------|\n");
+    for (card, counts) in distribution.iter().rev() {
+        output.push_str(&format!("| **{0},{0}** |", card_name(*card)));
+        for hands in 2..=MAX_SPLIT_HANDS {
+            output.push_str(&format!(" {:.1}% |", 100.0 * counts.fraction(hands)));
+        }
+        output.push_str(&format!(" {:.2} |\n", counts.mean_hands()));
+    }
+    output
+}
+
+/// Markdown table of splitting the ten pair (10,10, one rank) against standing on it for each upcard, with
+/// the true count at which splitting starts to win when `flips` (from `ten_split_counts`) is given
+pub fn format_ten_split(state_stats: &StateStats, flips: Option<&[(u8, Option<i32>)]>) -> String {
+    let mut output = String::from("## Splitting Tens\n\n");
+    output.push_str("| Upcard | Stand | Split | Split - Stand |");
+    output.push_str(if flips.is_some() { " Split from |\n" } else { "\n" });
+    output.push_str("|--------|-------|-------|---------------|");
+    output.push_str(if flips.is_some() { "------------|\n" } else { "\n" });
+
+    let mut split_ever = false;
+    for upcard in 2..=11 {
+        let Some(actions) = state_stats.get(&PlayerState::new(20, upcard, false, true)) else { continue };
+        let (stand, split) = (actions[Action::Stand.index()].ev(), actions[Action::Split.index()].ev());
+        split_ever |= split > stand;
+        let label = if upcard == 11 { "A".to_string() } else { upcard.to_string() };
+        output.push_str(&format!("| **{}** | {:+.4} | {:+.4} | {:+.4} |", label, stand, split, split - stand));
+        if let Some(flips) = flips {
+            match flips.iter().find(|(card, _)| *card == upcard).and_then(|(_, tc)| *tc) {
+                Some(tc) => output.push_str(&format!(" TC {:+} |", tc)),
+                None => output.push_str(" never |"),
+            }
+        }
+        output.push('\n');
+    }
+    if !split_ever {
+        output.push_str("\nStanding beats splitting tens against every upcard.\n");
+    }
+    output
+}
+
+/// Overall value of the solved strategy: each starting hand's best EV weighted by how often it is dealt,
+/// and how often that hand is a natural
+pub fn format_house_edge(rules: &RuleSet, state_stats: &StateStats) -> String {
+    let edge = house_edge(rules, state_stats);
+    let (card_probs, shoe_cards) = rules_composition(rules);
+    format!(
+        "## House Edge\n\nExpected value per unit bet: {:+.3}% (house edge {:.3}%)\n\n\
+         A player natural is dealt {:.2}% of the time.\n",
+        -100.0 * edge,
+        100.0 * edge,
+        100.0 * natural_probability(&card_probs, shoe_cards)
+    )
+}
+
+/// Perfect Pairs and 21+3 at their standard payouts, priced from a fresh shoe under `rules`
+pub fn format_side_bets(rules: &RuleSet) -> String {
+    let pairs_payouts = PerfectPairsPayouts::STANDARD;
+    let poker_payouts = TwentyOnePlusThreePayouts::STANDARD;
+    let (pairs, poker) = match rules.num_decks {
+        Some(num_decks) => {
+            let shoe = FiniteShoe::with_deck_type(num_decks, rules.deck_type);
+            (perfect_pairs_ev(&shoe, &pairs_payouts), twentyone_plus_three_ev(&shoe, &poker_payouts))
+        }
+        None => {
+            let deck = InfiniteDeck::with_deck_type(rules.deck_type);
+            (perfect_pairs_ev(&deck, &pairs_payouts), twentyone_plus_three_ev(&deck, &poker_payouts))
+        }
+    };
+
+    let mut output = format!("## Side Bets ({})\n\n", format_decks(rules.num_decks));
+    output.push_str("| Bet | Hand | Pays | Chance |\n|-----|------|------|--------|\n");
+    let rows = [
+        ("Perfect Pairs", "Mixed pair", pairs_payouts.mixed, pairs.mixed),
+        ("Perfect Pairs", "Colored pair", pairs_payouts.colored, pairs.colored),
+        ("Perfect Pairs", "Perfect pair", pairs_payouts.perfect, pairs.perfect),
+        ("21+3", "Flush", poker_payouts.flush, poker.flush),
+        ("21+3", "Straight", poker_payouts.straight, poker.straight),
+        ("21+3", "Three of a kind", poker_payouts.three_of_a_kind, poker.three_of_a_kind),
+        ("21+3", "Straight flush", poker_payouts.straight_flush, poker.straight_flush),
+        ("21+3", "Suited trips", poker_payouts.suited_trips, poker.suited_trips),
+    ];
+    for (bet, hand, pays, chance) in rows {
+        output.push_str(&format!("| {} | {} | {}:1 | {:.3}% |\n", bet, hand, pays, 100.0 * chance));
+    }
+
+    output.push_str(&format!("\nPerfect Pairs EV: {:+.3}%", 100.0 * pairs.ev));
+    match perfect_pairs_break_even_decks(&pairs_payouts, rules.deck_type) {
+        Some(decks) => output.push_str(&format!(" (breaks even at {:.1} evenly spread decks)\n", decks)),
+        None => output.push('\n'),
+    }
+    output.push_str(&format!("21+3 EV: {:+.3}%\n", 100.0 * poker.ev));
+    output
+}
+
+/// Whether to take even money on a natural against a dealer ace, from a fresh shoe
+pub fn format_even_money(rules: &RuleSet) -> String {
+    let mut output = String::from("## Even Money\n\n");
+    if rules.double_exposure {
+        output.push_str("Not offered: the dealer's hole card is already face up.\n");
+        return output;
+    }
+    let (card_probs, shoe_cards) = rules_composition(rules);
+    let even = even_money(rules, &card_probs, shoe_cards);
+    output.push_str(&format!(
+        "Natural against a dealer ace: taking even money wins {:+.3}; declining wins {:+.3} \
+         (the dealer has blackjack {:.1}% of the time and pushes).\n\n",
+        even.take,
+        even.decline,
+        100.0 * even.dealer_blackjack
+    ));
+    let verdict = if even.should_take() { "Take even money." } else { "Decline even money." };
+    output.push_str(&format!("**{}**\n", verdict));
+    output
+}
+
+/// Exact infinite-deck dealer final totals for every upcard
+pub fn format_dealer_outcomes(rules: &RuleSet) -> String {
+    let mut output = String::new();
+    output.push_str("## Dealer Outcomes (Infinite Deck)\n\n");
+    let totals = rules.dealer_stands_on..=21;
+    output.push_str("| Upcard |");
+    totals.clone().for_each(|total| output.push_str(&format!(" {} |", total)));
+    output.push_str(" Bust | BJ |\n|--------|");
+    totals.for_each(|_| output.push_str("----|"));
+    output.push_str("------|----|\n");
+
+    let card_probs = rules.deck_type.probabilities();
+    for upcard in 2..=11 {
+        let label = if upcard == 11 { "A".to_string() } else { upcard.to_string() };
+        output.push_str(&format!("| **{}** |", label));
+        let outcomes = dealer_outcomes(rules, upcard, &card_probs);
+        for p in &outcomes[(rules.dealer_stands_on - DEALER_LOWEST) as usize..] {
+            output.push_str(&format!(" {:.1}% |", 100.0 * p));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Markdown table of the house edge solved at each shoe size (`None` for an infinite deck)
+pub fn format_deck_comparison(edges: &[(Option<u8>, f64)]) -> String {
+    let mut output = String::new();
+    output.push_str("## House Edge by Deck Count\n\n");
+    output.push_str("| Decks | Player EV | House Edge |\n|-------|-----------|------------|\n");
+
+    for &(num_decks, edge) in edges {
+        let label = num_decks.map_or("Infinite".to_string(), |n| n.to_string());
+        output.push_str(&format!("| {} | {:+.3}% | {:.3}% |\n", label, -100.0 * edge, 100.0 * edge));
+    }
+    output
+}
+
+/// Markdown table of index plays
+pub fn format_index_plays(plays: &[IndexPlay]) -> String {
+    let mut output = String::new();
+    output.push_str("## Index Plays (Hi-Lo True Count)\n\n");
+    output.push_str("| State | Basic | Deviation | Index |\n|-------|-------|-----------|-------|\n");
+    for play in plays {
+        let sign = if play.above { "≥" } else { "≤" };
+        output.push_str(&format!(
+            "| {} | {} | {} | TC {} {:+.1} |\n",
+            play.state, play.basic.symbol(), play.deviation.symbol(), sign, play.index
+        ));
+    }
+    output
+}
+
+/// Markdown Kelly table for a `bankroll` and `min_bet`, scaling full Kelly by `multiplier`
+/// and capping every bet at `cap` of the bankroll
+pub fn format_kelly_table(table: &[KellyBet], bankroll: f64, min_bet: f64, multiplier: f64, cap: f64) -> String {
+    let mut output = String::new();
+    output.push_str("## Kelly Bet Sizing (Hi-Lo True Count)\n\n");
+    output.push_str(&format!(
+        "Bankroll {}, minimum bet {}, {}x Kelly capped at {:.0}% of bankroll\n\n",
+        bankroll, min_bet, multiplier, 100.0 * cap
+    ));
+    output.push_str("| TC | Advantage | Full Kelly | Scaled Kelly | Bet (x min) |\n");
+    output.push_str("|----|-----------|------------|--------------|-------------|\n");
+    for bet in table {
+        let fraction = bet.scaled_fraction(multiplier, cap);
+        let multiple = (fraction * bankroll / min_bet).max(1.0);
+        output.push_str(&format!(
+            "| {:+} | {:+.2}% | {:.2}% | {:.2}% | {:.1} |\n",
+            bet.true_count, 100.0 * bet.advantage, 100.0 * bet.kelly_fraction, 100.0 * fraction, multiple
+        ));
+    }
+    output
+}
+
+/// Short description of a shoe size
+pub fn format_decks(num_decks: Option<u8>) -> String {
+    match num_decks {
+        Some(1) => "Single deck".to_string(),
+        Some(n) => format!("{} Decks", n),
+        None => "Infinite deck".to_string(),
+    }
+}
@@ -0,0 +1,334 @@
+//! Table rules the engine plays under.
+
+use crate::config::Config;
+use crate::deck::{card_name, DeckType};
+use std::fmt;
+use std::str::FromStr;
+
+/// Starting hands the player may double on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DoubleOn {
+    /// Any first two cards
+    #[default]
+    Any,
+    /// Hard 9, 10 and 11 only (Reno rule)
+    NineToEleven,
+    /// Hard 10 and 11 only
+    TenToEleven,
+    /// Doubling is not offered
+    Never,
+}
+
+impl DoubleOn {
+    /// Whether a two-card hand of `total` may be doubled
+    #[inline(always)]
+    pub fn allows(self, total: u8, is_soft: bool) -> bool {
+        match self {
+            DoubleOn::Any => true,
+            DoubleOn::NineToEleven => !is_soft && (9..=11).contains(&total),
+            DoubleOn::TenToEleven => !is_soft && (10..=11).contains(&total),
+            DoubleOn::Never => false,
+        }
+    }
+}
+
+/// Parses `any`, `9-11`, `10-11` or `none`
+impl FromStr for DoubleOn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "any" => Ok(DoubleOn::Any),
+            "9-11" => Ok(DoubleOn::NineToEleven),
+            "10-11" => Ok(DoubleOn::TenToEleven),
+            "none" => Ok(DoubleOn::Never),
+            _ => Err(format!("unknown double rule '{}', expected any, 9-11, 10-11 or none", s)),
+        }
+    }
+}
+
+/// Configurable table rules.
+/// Defaults match Evolution Live Blackjack (8 decks, S17, DAS, ENHC, 3:2 naturals);
+/// fields missing when deserializing keep them, and the result must pass `RuleSet::validate`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "UncheckedRuleSet")
+)]
+pub struct RuleSet {
+    /// Decks in the shoe, dealt without replacement. `None` draws from an infinite deck, which every
+    /// solve did before this field; the default is 8.
+    pub num_decks: Option<u8>,
+    /// Cards in each deck (standard, or Spanish 21 without the 10-spots)
+    pub deck_type: DeckType,
+    /// Dealer hits soft 17 (H17) instead of standing on all 17s (S17).
+    /// With another `dealer_stands_on`, the dealer hits a soft total of that threshold instead.
+    pub hit_soft_17: bool,
+    /// Lowest total the dealer stands on, 12 to 21: 17 in a standard game, 16 or 18 in some novelty games
+    pub dealer_stands_on: u8,
+    /// Payout multiple for a player natural
+    pub blackjack_payout: f64,
+    /// Dealer checks a ten or ace upcard for blackjack before the player acts (US peek).
+    /// A dealer natural then costs only the original bet. Off is ENHC: the full wager,
+    /// including doubles and splits, is lost to a dealer natural.
+    pub peek: bool,
+    /// Original bets only (OBO), the European ENHC variant: a dealer natural takes only the original bet,
+    /// and the extra wagered on doubles and splits is returned. No effect with `peek`.
+    pub obo: bool,
+    /// Card count at which a non-busted player hand wins outright (e.g. `Some(5)`
+    /// for a five-card Charlie), paid before the dealer plays
+    pub charlie: Option<u8>,
+    /// Two-card hands that may be doubled, at the start and (with DAS) after a split
+    pub double_on: DoubleOn,
+    /// Two-card split hands may be doubled (DAS)
+    pub double_after_split: bool,
+    /// Splits allowed per round: 1 splits once into two hands, 3 resplits up to four.
+    /// Aces are never resplit.
+    pub max_splits: u8,
+    /// Any two ten-value cards may be split (10,K), not only matching ranks. Values alone can't tell a
+    /// 10 from a K, so with this off the engine never resplits tens.
+    pub split_mixed_tens: bool,
+    /// Late surrender is offered on the first two cards, after any peek; it still loses the whole
+    /// bet to a dealer natural
+    pub late_surrender: bool,
+    /// Surrender is offered before the dealer checks for (or, under ENHC, draws to) a natural,
+    /// so it always returns half the bet. Implies late surrender.
+    pub early_surrender: bool,
+    /// Dealer upcards (2-11, 11 for an ace) surrender is offered against, e.g. 9, 10 and A at tables that
+    /// restrict it. `None` offers it against every upcard.
+    pub surrender_vs: Option<Vec<u8>>,
+    /// Extra wager placed on a double, as a fraction of the original bet (below 1 doubles for less)
+    pub double_fraction: f64,
+    /// Spanish 21 payouts: a player 21 always wins, paying 3:2 with five cards, 2:1 with six and 3:1
+    /// with seven or more, and 3:2 for 6-7-8 or 7-7-7. Doubled hands win even money. Suits aren't
+    /// dealt, so the suited 6-7-8 and 7-7-7 bonuses pay the mixed rate.
+    pub spanish_bonuses: bool,
+    /// A dealer bust on exactly 22 pushes against every player hand still standing (Free Bet, Blackjack Switch)
+    pub push_22: bool,
+    /// Both dealer cards are dealt face up and the dealer wins every tie, naturals included
+    pub double_exposure: bool,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet {
+            num_decks: Some(8),
+            deck_type: DeckType::Standard,
+            hit_soft_17: false,
+            dealer_stands_on: 17,
+            blackjack_payout: 1.5,
+            peek: false,
+            obo: false,
+            charlie: None,
+            double_on: DoubleOn::Any,
+            double_after_split: true,
+            max_splits: 1,
+            split_mixed_tens: true,
+            late_surrender: true,
+            early_surrender: false,
+            surrender_vs: None,
+            double_fraction: 1.0,
+            spanish_bonuses: false,
+            push_22: false,
+            double_exposure: false,
+        }
+    }
+}
+
+/// `RuleSet` as deserialized, before `RuleSet::validate` accepts it
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(default)]
+struct UncheckedRuleSet {
+    num_decks: Option<u8>,
+    deck_type: DeckType,
+    hit_soft_17: bool,
+    dealer_stands_on: u8,
+    blackjack_payout: f64,
+    peek: bool,
+    obo: bool,
+    charlie: Option<u8>,
+    double_on: DoubleOn,
+    double_after_split: bool,
+    max_splits: u8,
+    split_mixed_tens: bool,
+    late_surrender: bool,
+    early_surrender: bool,
+    surrender_vs: Option<Vec<u8>>,
+    double_fraction: f64,
+    spanish_bonuses: bool,
+    push_22: bool,
+    double_exposure: bool,
+}
+
+#[cfg(feature = "serde")]
+impl Default for UncheckedRuleSet {
+    fn default() -> Self {
+        let RuleSet {
+            num_decks, deck_type, hit_soft_17, dealer_stands_on, blackjack_payout, peek, obo, charlie, double_on,
+            double_after_split, max_splits, split_mixed_tens, late_surrender, early_surrender, surrender_vs,
+            double_fraction, spanish_bonuses, push_22, double_exposure
+        } = RuleSet::default();
+        UncheckedRuleSet {
+            num_decks, deck_type, hit_soft_17, dealer_stands_on, blackjack_payout, peek, obo, charlie, double_on,
+            double_after_split, max_splits, split_mixed_tens, late_surrender, early_surrender, surrender_vs,
+            double_fraction, spanish_bonuses, push_22, double_exposure
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<UncheckedRuleSet> for RuleSet {
+    type Error = String;
+
+    fn try_from(unchecked: UncheckedRuleSet) -> Result<Self, String> {
+        let UncheckedRuleSet {
+            num_decks, deck_type, hit_soft_17, dealer_stands_on, blackjack_payout, peek, obo, charlie, double_on,
+            double_after_split, max_splits, split_mixed_tens, late_surrender, early_surrender, surrender_vs,
+            double_fraction, spanish_bonuses, push_22, double_exposure
+        } = unchecked;
+        let rules = RuleSet {
+            num_decks, deck_type, hit_soft_17, dealer_stands_on, blackjack_payout, peek, obo, charlie, double_on,
+            double_after_split, max_splits, split_mixed_tens, late_surrender, early_surrender, surrender_vs,
+            double_fraction, spanish_bonuses, push_22, double_exposure
+        };
+        rules.validate()?;
+        Ok(rules)
+    }
+}
+
+impl RuleSet {
+    /// Rules from a TOML file, optionally under a `[rules]` table; see `Config::from_toml` for the keys.
+    /// Missing keys keep the defaults.
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        Config::from_toml(text).map(|config| config.rules)
+    }
+
+    /// Check the fields the engine only plays within range: at least one deck, `dealer_stands_on` 12 to 21,
+    /// `max_splits` 1 to 3, a Charlie of at least 3 cards (any two-card hand would win), `surrender_vs`
+    /// upcards 2 to 11 and `double_fraction` above 0 and at most 1
+    pub fn validate(&self) -> Result<(), String> {
+        if self.num_decks == Some(0) {
+            return Err("need at least 1 deck".to_string());
+        }
+        if !(12..=21).contains(&self.dealer_stands_on) {
+            return Err("dealer_stands_on must be 12 to 21".to_string());
+        }
+        if !(1..=3).contains(&self.max_splits) {
+            return Err("max_splits must be 1 to 3".to_string());
+        }
+        if self.charlie.is_some_and(|cards| cards < 3) {
+            return Err("charlie must be at least 3 cards".to_string());
+        }
+        if self.surrender_vs.as_ref().is_some_and(|upcards| upcards.iter().any(|card| !(2..=11).contains(card))) {
+            return Err("surrender_vs upcards must be 2 to 11".to_string());
+        }
+        if !(self.double_fraction > 0.0 && self.double_fraction <= 1.0) {
+            return Err("double_fraction must be above 0 and at most 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// A dealer natural costs only the original bet, as under peek or OBO. Both then play to the same EVs:
+    /// hands against a dealer natural all lose one unit, however they were played.
+    #[inline(always)]
+    pub fn original_bets_only(&self) -> bool {
+        self.peek || self.obo
+    }
+
+    /// Surrender is offered at all, early or late
+    #[inline(always)]
+    pub fn surrender_allowed(&self) -> bool {
+        self.late_surrender || self.early_surrender
+    }
+
+    /// Surrender is offered against `upcard`: offered at all, and `upcard` is among `surrender_vs` when set
+    #[inline(always)]
+    pub fn surrender_allowed_vs(&self, upcard: u8) -> bool {
+        self.surrender_allowed() && self.surrender_vs.as_ref().is_none_or(|upcards| upcards.contains(&upcard))
+    }
+}
+
+/// One-line summary, e.g. "8 decks, S17, DAS, ENHC, blackjack pays 3:2"
+impl fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.num_decks {
+            Some(1) => write!(f, "1 deck")?,
+            Some(n) => write!(f, "{} decks", n)?,
+            None => write!(f, "infinite deck")?,
+        }
+        if self.deck_type == DeckType::Spanish { write!(f, " (Spanish 21)")?; }
+        if self.spanish_bonuses { write!(f, ", Spanish 21 bonuses")?; }
+        write!(f, ", {}{}", if self.hit_soft_17 { "H" } else { "S" }, self.dealer_stands_on)?;
+        match self.double_on {
+            DoubleOn::Any => {}
+            DoubleOn::NineToEleven => write!(f, ", double on 9-11")?,
+            DoubleOn::TenToEleven => write!(f, ", double on 10-11")?,
+            DoubleOn::Never => write!(f, ", no doubling")?,
+        }
+        write!(f, ", {}", if self.double_after_split { "DAS" } else { "no DAS" })?;
+        if self.max_splits > 1 { write!(f, ", resplit to {} hands", self.max_splits + 1)?; }
+        if !self.split_mixed_tens { write!(f, ", tens split by rank")?; }
+        write!(f, ", {}", if self.peek { "peek" } else if self.obo { "ENHC (OBO)" } else { "ENHC" })?;
+        if self.early_surrender {
+            write!(f, ", early surrender")?;
+        } else if !self.late_surrender {
+            write!(f, ", no surrender")?;
+        }
+        if let Some(upcards) = self.surrender_vs.as_ref().filter(|_| self.surrender_allowed()) {
+            let upcards: Vec<String> = upcards.iter().map(|&card| card_name(card)).collect();
+            write!(f, ", surrender vs {} only", upcards.join("/"))?;
+        }
+        if self.double_fraction != 1.0 { write!(f, ", double for {}x", self.double_fraction)?; }
+        let payout = [(1.5, "3:2"), (1.2, "6:5"), (1.0, "1:1")]
+            .iter()
+            .find(|(multiple, _)| *multiple == self.blackjack_payout)
+            .map_or_else(|| format!("{}x", self.blackjack_payout), |(_, ratio)| ratio.to_string());
+        write!(f, ", blackjack pays {}", payout)?;
+        if let Some(cards) = self.charlie { write!(f, ", {}-card Charlie", cards)?; }
+        if self.push_22 { write!(f, ", dealer 22 pushes")?; }
+        if self.double_exposure { write!(f, ", double exposure")?; }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_rejects_each_field_out_of_range() {
+        assert_eq!(RuleSet::default().validate(), Ok(()));
+        let invalid = [
+            RuleSet { num_decks: Some(0), ..RuleSet::default() },
+            RuleSet { dealer_stands_on: 11, ..RuleSet::default() },
+            RuleSet { dealer_stands_on: 22, ..RuleSet::default() },
+            RuleSet { max_splits: 0, ..RuleSet::default() },
+            RuleSet { max_splits: 4, ..RuleSet::default() },
+            RuleSet { charlie: Some(2), ..RuleSet::default() },
+            RuleSet { surrender_vs: Some(vec![10, 12]), ..RuleSet::default() },
+            RuleSet { double_fraction: 0.0, ..RuleSet::default() },
+        ];
+        for rules in invalid {
+            assert!(rules.validate().is_err(), "{:?}", rules);
+        }
+        assert_eq!(RuleSet { charlie: Some(3), max_splits: 3, ..RuleSet::default() }.validate(), Ok(()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserializing_validates_the_rules() {
+        use serde::de::value::{Error, MapDeserializer};
+        use serde::Deserialize;
+
+        let rules = |fields: Vec<(&'static str, u8)>| {
+            RuleSet::deserialize(MapDeserializer::<_, Error>::new(fields.into_iter())).map_err(|err| err.to_string())
+        };
+        assert_eq!(rules(vec![("max_splits", 3)]), Ok(RuleSet { max_splits: 3, ..RuleSet::default() }));
+        assert_eq!(rules(vec![]), Ok(RuleSet::default()));
+        assert_eq!(rules(vec![("max_splits", 0)]), Err("max_splits must be 1 to 3".to_string()));
+        assert_eq!(rules(vec![("dealer_stands_on", 30)]), Err("dealer_stands_on must be 12 to 21".to_string()));
+    }
+}
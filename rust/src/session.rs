@@ -0,0 +1,340 @@
+//! Full-shoe session simulation.
+//! Plays consecutive rounds from one shoe with a solved strategy and tracks the bankroll.
+
+use crate::analysis::n0;
+use crate::counting::shoe_decks;
+use crate::deck::{Deck, FiniteShoe, Hand, PlayerState};
+use crate::engine::{Action, BlackjackEngine};
+use crate::rules::RuleSet;
+use crate::solver::{get_best_action, StateStats};
+use rayon::prelude::*;
+
+/// Fraction of the shoe dealt before the cut card forces a reshuffle, unless the shoe places its own
+pub const PENETRATION: f64 = 0.75;
+
+/// Trajectory capacity reserved up front; longer sessions grow it as they go
+const PREALLOCATED_ROUNDS: u32 = 100_000;
+
+/// How much to wager each round
+#[derive(Debug, Clone)]
+pub enum BetSizing {
+    /// Same bet every round
+    Flat(f64),
+    /// `unit` times the multiple of the highest `(min_true_count, units)` step reached.
+    /// Steps are in ascending count order; counts below the first step bet one unit.
+    CountRamp { unit: f64, ramp: Vec<(f64, f64)> },
+    /// Fraction of the current bankroll from the highest `(min_true_count, fraction)` step
+    /// reached, never below `min_bet` (see `counting::kelly_table`)
+    Kelly { min_bet: f64, ramp: Vec<(f64, f64)> },
+}
+
+impl BetSizing {
+    /// Wager for a round dealt at `true_count` with `bankroll` on hand
+    pub fn bet(&self, true_count: f64, bankroll: f64) -> f64 {
+        match self {
+            BetSizing::Flat(bet) => *bet,
+            BetSizing::CountRamp { unit, ramp } => unit * step(ramp, true_count).unwrap_or(1.0),
+            BetSizing::Kelly { min_bet, ramp } => {
+                let fraction = step(ramp, true_count).unwrap_or(0.0);
+                (fraction * bankroll).max(*min_bet)
+            }
+        }
+    }
+}
+
+/// Back-counting: watch the shoe, sit in once the true count reaches `wong_in_tc` and leave when
+/// it falls below `wong_out_tc` or the shoe is reshuffled. Rounds sat out are dealt without a bet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wonging {
+    pub wong_in_tc: f64,
+    pub wong_out_tc: f64,
+}
+
+impl Wonging {
+    /// Whether to bet a round dealt at `true_count`, given whether the last round was bet
+    #[inline(always)]
+    pub fn plays(&self, playing: bool, true_count: f64) -> bool {
+        true_count >= if playing { self.wong_out_tc } else { self.wong_in_tc }
+    }
+}
+
+/// Value of the last ramp step whose minimum true count has been reached
+fn step(ramp: &[(f64, f64)], true_count: f64) -> Option<f64> {
+    ramp.iter().rev().find(|(min_tc, _)| true_count >= *min_tc).map(|&(_, value)| value)
+}
+
+/// Outcome of a simulated session
+#[derive(Debug, Clone)]
+pub struct SessionResult {
+    /// Bankroll after each round played
+    pub trajectory: Vec<f64>,
+    pub final_bankroll: f64,
+    /// Bankroll ran out before all rounds were played
+    pub ruined: bool,
+    /// Shoes dealt from, counting the one the session started on
+    pub shoes: u32,
+    /// Rounds a bet was placed on; below `trajectory.len()` only when wonging
+    pub rounds_played: u32,
+}
+
+impl SessionResult {
+    /// Rounds dealt from each shoe on average, including the unfinished last shoe
+    pub fn rounds_per_shoe(&self) -> f64 {
+        self.trajectory.len() as f64 / self.shoes as f64
+    }
+
+    /// Fraction of the rounds dealt that were bet on
+    pub fn fraction_played(&self) -> f64 {
+        if self.trajectory.is_empty() { 0.0 } else { self.rounds_played as f64 / self.trajectory.len() as f64 }
+    }
+
+    /// Largest peak-to-trough drop in bankroll
+    pub fn max_drawdown(&self, starting_bankroll: f64) -> f64 {
+        let mut peak = starting_bankroll;
+        let mut drawdown: f64 = 0.0;
+        for &bankroll in &self.trajectory {
+            peak = peak.max(bankroll);
+            drawdown = drawdown.max(peak - bankroll);
+        }
+        drawdown
+    }
+}
+
+/// Play up to `rounds` rounds from `shoe`, choosing each hand's first action from the solved
+/// `strategy`. Bets never exceed the bankroll; the session stops early on ruin.
+/// The shoe reshuffles at its cut card (`FiniteShoe::with_cut_card`), or at `PENETRATION` without one.
+/// With `wonging`, rounds outside its true count range are dealt but not bet.
+pub fn simulate_session(
+    rules: &RuleSet,
+    shoe: FiniteShoe,
+    strategy: &StateStats,
+    bet: &BetSizing,
+    bankroll: f64,
+    rounds: u32,
+    wonging: Option<Wonging>,
+) -> SessionResult {
+    run_session(rules, shoe, strategy, bet, bankroll, f64::INFINITY, rounds, wonging)
+}
+
+/// Like `simulate_session`, but also stops once the bankroll reaches `target`.
+/// `max_rounds` bounds sessions that neither go broke nor reach the target.
+pub fn simulate_session_to_target(
+    rules: &RuleSet,
+    shoe: FiniteShoe,
+    strategy: &StateStats,
+    bet: &BetSizing,
+    bankroll: f64,
+    target: f64,
+    max_rounds: u32,
+) -> SessionResult {
+    run_session(rules, shoe, strategy, bet, bankroll, target, max_rounds, None)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_session(
+    rules: &RuleSet,
+    shoe: FiniteShoe,
+    strategy: &StateStats,
+    bet: &BetSizing,
+    bankroll: f64,
+    target: f64,
+    rounds: u32,
+    wonging: Option<Wonging>,
+) -> SessionResult {
+    let shoe = if shoe.cut_card().is_some() { shoe } else { shoe.with_cut_card(PENETRATION) };
+    let mut engine = BlackjackEngine::with_rules(shoe, rules.clone());
+    let mut bankroll = bankroll;
+    let mut trajectory = Vec::with_capacity(rounds.min(PREALLOCATED_ROUNDS) as usize);
+    let mut ruined = false;
+    let mut shoes = 1;
+    let mut rounds_played = 0;
+    let mut playing = false;
+
+    for _ in 0..rounds {
+        if engine.deck_mut().shuffle_at_cut_card() {
+            shoes += 1;
+            playing = false;
+        }
+
+        let true_count = engine.deck().true_count();
+        playing = wonging.is_none_or(|wong| wong.plays(playing, true_count));
+        let result = play_round(&mut engine, strategy);
+        if playing {
+            bankroll += bet.bet(true_count, bankroll).min(bankroll) * result;
+            rounds_played += 1;
+        }
+        trajectory.push(bankroll);
+
+        if bankroll <= 0.0 {
+            ruined = true;
+            break;
+        }
+        if bankroll >= target { break; }
+    }
+
+    SessionResult { trajectory, final_bankroll: bankroll, ruined, shoes, rounds_played }
+}
+
+/// Totals of the hands dealt by `play_session`
+#[derive(Debug, Clone, Copy)]
+pub struct HandsResult {
+    pub hands: u64,
+    /// Net win over every hand, in bet units
+    pub total_won: f64,
+    pub total_wagered: f64,
+    /// Variance of one hand's net win, in bet units squared
+    pub variance_per_hand: f64,
+    /// Shoes dealt from, counting the one the session started on
+    pub shoes: u32,
+}
+
+impl HandsResult {
+    /// Expected win per hand dealt, in bet units
+    pub fn ev_per_hand(&self) -> f64 {
+        if self.hands == 0 { 0.0 } else { self.total_won / self.hands as f64 }
+    }
+
+    /// Variance of the total win, `hands` times the per-hand variance
+    pub fn total_variance(&self) -> f64 {
+        self.variance_per_hand * self.hands as f64
+    }
+}
+
+/// Deal `hands` hands from `shoe` with `strategy`, reshuffling at its cut card (or at `PENETRATION`
+/// without one), and total what `bet_fn` wins. `bet_fn` sees the shoe before each round, after any
+/// reshuffle, and returns the wager: 0 sits the round out, which is still dealt. Unlike `simulate_session`
+/// there is no bankroll to run out, so the totals capture card depletion alone.
+pub fn play_session(
+    rules: &RuleSet,
+    shoe: FiniteShoe,
+    strategy: &StateStats,
+    hands: u64,
+    mut bet_fn: impl FnMut(&FiniteShoe) -> f64,
+) -> HandsResult {
+    let shoe = if shoe.cut_card().is_some() { shoe } else { shoe.with_cut_card(PENETRATION) };
+    let mut engine = BlackjackEngine::with_rules(shoe, rules.clone());
+    let (mut won, mut won_squared, mut wagered, mut shoes) = (0.0, 0.0, 0.0, 1);
+    for _ in 0..hands {
+        if engine.deck_mut().shuffle_at_cut_card() { shoes += 1; }
+        let wager = bet_fn(engine.deck());
+        let result = wager * play_round(&mut engine, strategy);
+        won += result;
+        won_squared += result * result;
+        wagered += wager;
+    }
+
+    let n = hands.max(1) as f64;
+    let mean = won / n;
+    HandsResult {
+        hands,
+        total_won: won,
+        total_wagered: wagered,
+        variance_per_hand: (won_squared / n - mean * mean).max(0.0),
+        shoes,
+    }
+}
+
+/// Outcome of a bet spread played over many shoes
+#[derive(Debug, Clone, Copy)]
+pub struct SpreadResult {
+    pub hands: u64,
+    /// Expected win per hand, in bet units
+    pub ev_per_hand: f64,
+    /// Standard deviation of the result of one hand, in bet units
+    pub std_dev_per_hand: f64,
+    pub ev_per_hour: f64,
+    pub std_dev_per_hour: f64,
+    /// Average wager on the hands that were bet
+    pub average_bet: f64,
+    /// Total won divided by total wagered
+    pub advantage: f64,
+    /// Rounds dealt from each shoe before the cut card came out
+    pub rounds_per_shoe: f64,
+    /// Fraction of the rounds dealt that were bet on; below 1 only when wonging
+    pub fraction_played: f64,
+}
+
+impl SpreadResult {
+    /// Rounds for the spread's expected win to equal one standard deviation (`analysis::n0`)
+    pub fn n0(&self) -> Option<f64> {
+        n0(self.ev_per_hand, self.std_dev_per_hand)
+    }
+}
+
+/// Hands each parallel spread simulation plays from its own shoe
+const SPREAD_CHUNK: u64 = 100_000;
+
+/// Play `hands` hands through shoes reshuffled at `penetration`, sizing every bet with
+/// the count-keyed `bet` ramp, to estimate what the spread earns.
+/// With `wonging`, hands outside its range are dealt but not bet; per-hand and per-hour figures
+/// still count every hand dealt, as when back-counting a single table.
+pub fn simulate_spread(
+    rules: &RuleSet,
+    strategy: &StateStats,
+    bet: &BetSizing,
+    wonging: Option<Wonging>,
+    penetration: f64,
+    hands: u64,
+    hands_per_hour: f64,
+) -> SpreadResult {
+    let num_decks = shoe_decks(rules);
+    let chunks = hands.div_ceil(SPREAD_CHUNK);
+
+    // (won, won squared, wagered, shuffles, hands bet) summed over every hand
+    let (won, won_squared, wagered, shuffles, played) = (0..chunks)
+        .into_par_iter()
+        .map(|chunk| {
+            let shoe = FiniteShoe::with_deck_type(num_decks, rules.deck_type).with_cut_card(penetration);
+            let mut engine = BlackjackEngine::with_rules(shoe, rules.clone());
+            let mut sums = (0.0, 0.0, 0.0, 0, 0);
+            let mut playing = false;
+            for _ in 0..SPREAD_CHUNK.min(hands - chunk * SPREAD_CHUNK) {
+                if engine.deck_mut().shuffle_at_cut_card() {
+                    sums.3 += 1;
+                    playing = false;
+                }
+                let true_count = engine.deck().true_count();
+                playing = wonging.is_none_or(|wong| wong.plays(playing, true_count));
+                // Bankroll-relative ramps bet their floor; spreads are in fixed units
+                let wager = if playing { bet.bet(true_count, 0.0) } else { 0.0 };
+                let result = wager * play_round(&mut engine, strategy);
+                sums.0 += result;
+                sums.1 += result * result;
+                sums.2 += wager;
+                sums.4 += playing as u64;
+            }
+            sums
+        })
+        .reduce(|| (0.0, 0.0, 0.0, 0, 0), |a, b| (a.0 + b.0, a.1 + b.1, a.2 + b.2, a.3 + b.3, a.4 + b.4));
+
+    let n = hands as f64;
+    let ev_per_hand = won / n;
+    let std_dev_per_hand = (won_squared / n - ev_per_hand * ev_per_hand).max(0.0).sqrt();
+    SpreadResult {
+        hands,
+        ev_per_hand,
+        std_dev_per_hand,
+        ev_per_hour: ev_per_hand * hands_per_hour,
+        std_dev_per_hour: std_dev_per_hand * hands_per_hour.sqrt(),
+        average_bet: if played > 0 { wagered / played as f64 } else { 0.0 },
+        advantage: if wagered > 0.0 { won / wagered } else { 0.0 },
+        // Each chunk's last shoe is cut short, so count only finished shoes
+        rounds_per_shoe: if shuffles > 0 { n / shuffles as f64 } else { n },
+        fraction_played: played as f64 / n,
+    }
+}
+
+/// Deal one round and play it out, returning the result in units of the initial bet
+fn play_round(engine: &mut BlackjackEngine<FiniteShoe>, strategy: &StateStats) -> f64 {
+    let deck = engine.deck_mut();
+    let first = deck.draw();
+    let dealer_upcard = deck.draw();
+    let second = deck.draw();
+
+    let hand = Hand::from_cards(first, second);
+    let state = PlayerState::from_hand(&hand, dealer_upcard);
+    // Naturals are settled before any action, so states missing from the table can stand
+    let action = strategy.get(&state).and_then(get_best_action).map_or(Action::Stand, |(best, _)| best);
+
+    engine.simulate_action(&hand, dealer_upcard, action)
+}
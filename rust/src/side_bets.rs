@@ -0,0 +1,251 @@
+//! Side bets settled on the first cards dealt, priced from the shoe's composition.
+//! A `SuitedShoe` counts every card; a `FiniteShoe` tracks ranks but not suits, so its suits are spread evenly.
+
+use crate::deck::{DeckType, FiniteShoe, InfiniteDeck, Suit, SuitedShoe};
+
+/// Ranks 2-10, J, Q, K, A in order
+const RANKS: usize = 13;
+
+/// Suits, the first two red and the last two black as in `Suit::ALL`
+const SUITS: usize = 4;
+
+/// Shoe the side bets are priced from
+pub trait SideBetShoe {
+    /// Cards left of each rank (2 first, ace last) and suit (in `Suit::ALL` order)
+    fn card_counts(&self) -> [[f64; SUITS]; RANKS];
+
+    /// Dealing a card leaves the odds of the next unchanged, as from an infinite deck
+    fn with_replacement(&self) -> bool {
+        false
+    }
+}
+
+/// The shoe as `with_replacement` sees it: `card_counts` and the cards one deal removes
+fn shoe_cards(shoe: &impl SideBetShoe) -> ([[f64; SUITS]; RANKS], f64) {
+    (shoe.card_counts(), if shoe.with_replacement() { 0.0 } else { 1.0 })
+}
+
+/// Expected counts, with each rank's cards spread evenly over the suits
+impl SideBetShoe for FiniteShoe {
+    fn card_counts(&self) -> [[f64; SUITS]; RANKS] {
+        self.rank_counts().map(|count| [count as f64 / SUITS as f64; SUITS])
+    }
+}
+
+/// One deck's worth of even counts, dealt with replacement: side bets on an infinite deck are constants
+impl SideBetShoe for InfiniteDeck {
+    fn card_counts(&self) -> [[f64; SUITS]; RANKS] {
+        let mut cards = [[1.0; SUITS]; RANKS];
+        // Spanish decks have no 10-spots
+        if self.deck_type() == DeckType::Spanish { cards[8] = [0.0; SUITS]; }
+        cards
+    }
+
+    fn with_replacement(&self) -> bool {
+        true
+    }
+}
+
+impl SideBetShoe for SuitedShoe {
+    fn card_counts(&self) -> [[f64; SUITS]; RANKS] {
+        let mut cards = [[0.0; SUITS]; RANKS];
+        for card in self.remaining_cards() {
+            let suit = Suit::ALL.iter().position(|&suit| suit == card.suit).unwrap_or_default();
+            cards[(card.rank - 2) as usize][suit] += 1.0;
+        }
+        cards
+    }
+}
+
+/// Perfect Pairs payouts as multiples of the bet, which vary by casino
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PerfectPairsPayouts {
+    /// Same rank, different colour
+    pub mixed: f64,
+    /// Same rank and colour, different suit
+    pub colored: f64,
+    /// Same rank and suit
+    pub perfect: f64,
+}
+
+impl PerfectPairsPayouts {
+    /// 6:1, 12:1, 25:1, the most common schedule
+    pub const STANDARD: PerfectPairsPayouts = PerfectPairsPayouts { mixed: 6.0, colored: 12.0, perfect: 25.0 };
+}
+
+impl Default for PerfectPairsPayouts {
+    fn default() -> Self {
+        PerfectPairsPayouts::STANDARD
+    }
+}
+
+/// Chance of each pair type and the bet's expected net return per unit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PerfectPairsEv {
+    pub mixed: f64,
+    pub colored: f64,
+    pub perfect: f64,
+    pub ev: f64,
+}
+
+/// Perfect Pairs on the player's two cards, the next two dealt from `shoe`
+pub fn perfect_pairs_ev(shoe: &impl SideBetShoe, payouts: &PerfectPairsPayouts) -> PerfectPairsEv {
+    let (cards, dealt) = shoe_cards(shoe);
+    let remaining: f64 = cards.iter().flatten().sum();
+    let (mut mixed, mut colored, mut perfect) = (0.0, 0.0, 0.0);
+    for suits in &cards {
+        for (suit, &count) in suits.iter().enumerate() {
+            let first = count / remaining;
+            for (other, &left) in suits.iter().enumerate() {
+                let second = if other == suit { (left - dealt).max(0.0) } else { left } / (remaining - dealt);
+                match (other == suit, other / 2 == suit / 2) {
+                    (true, _) => perfect += first * second,
+                    (false, true) => colored += first * second,
+                    (false, false) => mixed += first * second,
+                }
+            }
+        }
+    }
+
+    let lose = 1.0 - mixed - colored - perfect;
+    let ev = mixed * payouts.mixed + colored * payouts.colored + perfect * payouts.perfect - lose;
+    PerfectPairsEv { mixed, colored, perfect, ev }
+}
+
+/// Decks of an evenly spread shoe above which Perfect Pairs pays the player, or `None` if it never does.
+/// With `n` decks of `c` cards the second card pairs the first perfectly with chance `(n - 1) / (nc - 1)`,
+/// in colour with `n / (nc - 1)` and mixed with `2n / (nc - 1)`, so the EV is
+/// `(n (perfect + colored + 2 mixed - (c - 4)) - perfect) / (nc - 1)`: removing the first card is the house's
+/// whole edge, which more decks dilute.
+pub fn perfect_pairs_break_even_decks(payouts: &PerfectPairsPayouts, deck_type: DeckType) -> Option<f64> {
+    let unpaired = deck_type.cards_per_deck() as f64 - 4.0;
+    let per_deck = payouts.perfect + payouts.colored + 2.0 * payouts.mixed - unpaired;
+    (per_deck > 0.0).then(|| payouts.perfect / per_deck)
+}
+
+/// 21+3 payouts as multiples of the bet, which vary by casino
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TwentyOnePlusThreePayouts {
+    pub flush: f64,
+    pub straight: f64,
+    pub three_of_a_kind: f64,
+    pub straight_flush: f64,
+    /// Three of a kind in one suit
+    pub suited_trips: f64,
+}
+
+impl TwentyOnePlusThreePayouts {
+    /// 5:1, 10:1, 30:1, 40:1, 100:1, as on Evolution's tables
+    pub const STANDARD: TwentyOnePlusThreePayouts = TwentyOnePlusThreePayouts {
+        flush: 5.0,
+        straight: 10.0,
+        three_of_a_kind: 30.0,
+        straight_flush: 40.0,
+        suited_trips: 100.0,
+    };
+}
+
+impl Default for TwentyOnePlusThreePayouts {
+    fn default() -> Self {
+        TwentyOnePlusThreePayouts::STANDARD
+    }
+}
+
+/// Chance of each 21+3 hand and the bet's expected net return per unit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TwentyOnePlusThreeEv {
+    pub flush: f64,
+    pub straight: f64,
+    pub three_of_a_kind: f64,
+    pub straight_flush: f64,
+    pub suited_trips: f64,
+    pub ev: f64,
+}
+
+/// 21+3 on the poker hand of the player's two cards and the dealer's upcard, the next three dealt
+/// from `shoe`. Aces play high or low in straights (A-2-3 and Q-K-A), but do not wrap around.
+pub fn twentyone_plus_three_ev(shoe: &impl SideBetShoe, payouts: &TwentyOnePlusThreePayouts) -> TwentyOnePlusThreeEv {
+    let (mut cards, dealt) = shoe_cards(shoe);
+    let remaining: f64 = cards.iter().flatten().sum();
+    let mut odds = TwentyOnePlusThreeEv {
+        flush: 0.0,
+        straight: 0.0,
+        three_of_a_kind: 0.0,
+        straight_flush: 0.0,
+        suited_trips: 0.0,
+        ev: 0.0,
+    };
+
+    // Every ordered deal of three cards, taking each card out of the shoe before the next
+    let all = || (0..RANKS).flat_map(|rank| (0..SUITS).map(move |suit| (rank, suit)));
+    for (r1, s1) in all() {
+        let p1 = cards[r1][s1] / remaining;
+        if p1 <= 0.0 { continue; }
+        cards[r1][s1] -= dealt;
+        for (r2, s2) in all() {
+            let p2 = p1 * cards[r2][s2].max(0.0) / (remaining - dealt);
+            if p2 <= 0.0 { continue; }
+            cards[r2][s2] -= dealt;
+            for (r3, s3) in all() {
+                let p = p2 * cards[r3][s3].max(0.0) / (remaining - 2.0 * dealt);
+                if p <= 0.0 { continue; }
+
+                let suited = s1 == s2 && s2 == s3;
+                let mut ranks = [r1, r2, r3];
+                ranks.sort_unstable();
+                let trips = ranks[0] == ranks[2];
+                // The ace is rank 12, so A-2-3 sorts as 0, 1, 12
+                let straight = (ranks[1] == ranks[0] + 1 && ranks[2] == ranks[1] + 1) || ranks == [0, 1, 12];
+                match (trips, straight, suited) {
+                    (true, _, true) => odds.suited_trips += p,
+                    (true, _, false) => odds.three_of_a_kind += p,
+                    (false, true, true) => odds.straight_flush += p,
+                    (false, true, false) => odds.straight += p,
+                    (false, false, true) => odds.flush += p,
+                    (false, false, false) => {}
+                }
+            }
+            cards[r2][s2] += dealt;
+        }
+        cards[r1][s1] += dealt;
+    }
+
+    let win = odds.flush + odds.straight + odds.three_of_a_kind + odds.straight_flush + odds.suited_trips;
+    odds.ev = odds.flush * payouts.flush
+        + odds.straight * payouts.straight
+        + odds.three_of_a_kind * payouts.three_of_a_kind
+        + odds.straight_flush * payouts.straight_flush
+        + odds.suited_trips * payouts.suited_trips
+        - (1.0 - win);
+    odds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perfect_pairs_on_eight_decks_has_the_published_edge() {
+        // 8 decks of 416 cards leave 415: 7 pair the first card perfectly, 8 in colour and 16 mixed
+        let odds = perfect_pairs_ev(&FiniteShoe::new(8), &PerfectPairsPayouts::STANDARD);
+        assert!((odds.perfect - 7.0 / 415.0).abs() < 1e-12, "{:?}", odds);
+        assert!((odds.colored - 8.0 / 415.0).abs() < 1e-12, "{:?}", odds);
+        assert!((odds.mixed - 16.0 / 415.0).abs() < 1e-12, "{:?}", odds);
+        // 6:1, 12:1, 25:1 carries a 4.10% house edge
+        assert!((odds.ev + 17.0 / 415.0).abs() < 1e-12, "{:?}", odds);
+        assert!((odds.ev + 0.0410).abs() < 0.00005, "{:?}", odds);
+        // Each deck adds 25 + 12 + 2 * 6 - 48 = 1 to the numerator, so only past 25 decks does it pay
+        assert_eq!(perfect_pairs_break_even_decks(&PerfectPairsPayouts::STANDARD, DeckType::Standard), Some(25.0));
+    }
+
+    #[test]
+    fn twentyone_plus_three_on_eight_decks_has_the_published_edge() {
+        let odds = twentyone_plus_three_ev(&FiniteShoe::new(8), &TwentyOnePlusThreePayouts::STANDARD);
+        // Suited trips take the other 7 copies of the first card, then one of the 6 left
+        assert!((odds.suited_trips - 7.0 / 415.0 * 6.0 / 414.0).abs() < 1e-12, "{:?}", odds);
+        // 5:1, 10:1, 30:1, 40:1, 100:1 carries a 3.70% house edge on 8 decks and 4.62% on 6
+        assert!((odds.ev + 0.0370).abs() < 0.00005, "{:?}", odds);
+        let six = twentyone_plus_three_ev(&FiniteShoe::new(6), &TwentyOnePlusThreePayouts::STANDARD);
+        assert!((six.ev + 0.0462).abs() < 0.00005, "{:?}", six);
+    }
+}
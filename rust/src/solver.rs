@@ -0,0 +1,445 @@
+//! Convergence loop driving the Monte Carlo engine over every state-action pair.
+//! Batches run in parallel, each state's actions dealt common cards, until every pair's SEM is below target.
+
+use crate::analysis::house_edge;
+use crate::checkpoint::Checkpoint;
+use crate::deck::{Deck, FiniteShoe, ImportanceDeck, InfiniteDeck, PlayerState, ReplayDeck, NUM_STATES};
+use crate::engine::{Action, ActionStats, BlackjackEngine, NUM_ACTIONS};
+use crate::exact;
+use crate::playout::{BasicStrategy, PlayoutStrategy};
+use crate::progress::{Progress, ProgressReporter};
+use crate::rules::RuleSet;
+use rayon::prelude::*;
+use std::sync::Mutex;
+use tracing::{info, info_span, warn};
+
+pub const TARGET_SEM: f64 = 0.005;
+pub const BATCH_SIZE: u32 = 10_000;
+pub const MAX_ITERATIONS: u32 = 1000;
+pub const CHECKPOINT_INTERVAL: u32 = 10;
+
+/// Sub-batches each iteration is split into at least, so the last few pending tasks still keep every
+/// thread of a large machine busy. Fixed rather than read from the pool, so a seeded solve deals the same
+/// sub-batches, and ends the same, on any thread count.
+const MIN_SUB_BATCHES: usize = 64;
+
+/// Convergence and sampling settings of a solve
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct SolverConfig {
+    /// Standard error of the mean every state-action pair is simulated down to
+    pub target_sem: f64,
+    /// Hands per pending state in each iteration
+    pub batch_size: u32,
+    /// Iterations before the solve stops with pairs still above `target_sem`
+    pub max_iterations: u32,
+    /// Seed of every batch's card stream, making the solve reproducible on any thread count.
+    /// `None` seeds each engine from the system.
+    pub seed: Option<u64>,
+    /// Worker threads for the solve; `None` runs on the current rayon pool
+    pub threads: Option<usize>,
+    /// File the statistics are saved to every `checkpoint_interval` iterations
+    pub checkpoint: Option<String>,
+    pub checkpoint_interval: u32,
+    /// Settle stood hands at their exact EV, on an infinite deck or for the cards left in a finite shoe
+    /// (see `BlackjackEngine::with_exact_shoe_stands` and `without_exact_stands`).
+    /// Turn off when the solved second moments are used, e.g. for risk of ruin.
+    pub exact_stands: bool,
+    /// Deal each state's hands from its `exact::hit_proposal` on an infinite deck, weighting the results
+    /// back (see `ImportanceDeck`). Finite shoes are always dealt as they are.
+    pub importance_sampling: bool,
+    /// Statistics of an interrupted solve to continue from. With the same seed the resumed
+    /// solve finishes exactly as the uninterrupted one would have.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub resume: Option<Checkpoint>,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        SolverConfig {
+            target_sem: TARGET_SEM,
+            batch_size: BATCH_SIZE,
+            max_iterations: MAX_ITERATIONS,
+            seed: None,
+            threads: None,
+            checkpoint: None,
+            checkpoint_interval: CHECKPOINT_INTERVAL,
+            exact_stands: true,
+            importance_sampling: false,
+            resume: None,
+        }
+    }
+}
+
+impl SolverConfig {
+    /// This config with exact stands and importance sampling off, so every state-action's second moment
+    /// is the plain per-hand one that `round_stats`' standard deviation, Kelly and risk of ruin read
+    pub fn plain_sampled(&self) -> Self {
+        SolverConfig { exact_stands: false, importance_sampling: false, ..self.clone() }
+    }
+}
+
+/// Statistics for every action of one state, indexed by `Action::index`.
+/// Actions that are never simulated for the state keep `n == 0`.
+pub type ActionTable = [ActionStats; NUM_ACTIONS];
+
+/// Accumulated statistics per state and action, stored densely by `PlayerState::index`
+#[derive(Debug, Clone)]
+pub struct StateStats {
+    states: Vec<PlayerState>,
+    stats: Vec<ActionTable>,
+    solved: Vec<bool>,
+}
+
+impl StateStats {
+    /// Empty statistics for `states`
+    pub fn new(states: &[PlayerState]) -> Self {
+        let mut solved = vec![false; NUM_STATES];
+        for state in states {
+            solved[state_index(state)] = true;
+        }
+        StateStats {
+            states: states.to_vec(),
+            stats: vec![Default::default(); NUM_STATES],
+            solved,
+        }
+    }
+
+    /// Statistics saved in a checkpoint, solving the states it holds
+    pub fn from_checkpoint(checkpoint: &Checkpoint) -> Self {
+        let states: Vec<PlayerState> = checkpoint.stats.iter().map(|(state, _)| *state).collect();
+        let mut state_stats = StateStats::new(&states);
+        state_stats.restore(checkpoint);
+        state_stats
+    }
+
+    /// Copy in a checkpoint's statistics of the states solved here
+    fn restore(&mut self, checkpoint: &Checkpoint) {
+        for (state, table) in &checkpoint.stats {
+            if let Some(idx) = state.index().filter(|&idx| self.solved[idx]) {
+                self.stats[idx] = table.clone();
+            }
+        }
+    }
+
+    /// Statistics of a state, if it is one of the solved states
+    #[inline(always)]
+    pub fn get(&self, state: &PlayerState) -> Option<&ActionTable> {
+        let idx = state.index()?;
+        if self.solved[idx] { Some(&self.stats[idx]) } else { None }
+    }
+
+    /// Solved states with their statistics, in generation order
+    pub fn iter(&self) -> impl Iterator<Item = (&PlayerState, &ActionTable)> {
+        self.states.iter().map(|state| (state, &self.stats[state_index(state)]))
+    }
+
+    /// Sampled state-action pairs whose SEM reached `target_sem`, and all sampled pairs
+    pub fn converged(&self, target_sem: f64) -> (usize, usize) {
+        let sampled = self.iter().flat_map(|(_, table)| table.iter().filter(|stats| stats.n > 0));
+        sampled.fold((0, 0), |(converged, total), stats| (converged + usize::from(stats.sem() < target_sem), total + 1))
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+}
+
+#[inline(always)]
+fn state_index(state: &PlayerState) -> usize {
+    state.index().expect("state outside the solver's state space")
+}
+
+/// Task for simulation: the actions of one state that have not converged yet.
+/// They are simulated together on common random numbers.
+#[derive(Clone)]
+struct SimulationTask {
+    state: PlayerState,
+    state_idx: usize,
+    actions: Vec<Action>,
+    /// Proposal the deck is tilted to under importance sampling
+    proposal: Option<[f64; 10]>,
+}
+
+/// Batch statistics produced by one task, owned until the merge phase.
+/// Actions outside the task keep `n == 0`.
+struct SimulationResult {
+    state_idx: usize,
+    stats: ActionTable,
+}
+
+/// Solve `states` from the deck `rules` describes: a fresh shoe of `num_decks`,
+/// or an infinite deck when unset
+pub fn solve_with_rules(states: &[PlayerState], rules: &RuleSet, progress: Progress) -> StateStats {
+    solve_with_rules_and_playout(states, rules, &BasicStrategy, &SolverConfig::default(), progress)
+}
+
+/// `solve_with_rules`, finishing hands with `playout` and converging as `config` sets
+pub fn solve_with_rules_and_playout<P>(
+    states: &[PlayerState],
+    rules: &RuleSet,
+    playout: &P,
+    config: &SolverConfig,
+    progress: Progress,
+) -> StateStats
+where
+    P: PlayoutStrategy + Sync,
+{
+    match rules.num_decks {
+        Some(num_decks) => {
+            let make_deck = || FiniteShoe::with_deck_type(num_decks, rules.deck_type);
+            solve_with_playout(states, rules, make_deck, playout, config, progress)
+        }
+        None if config.importance_sampling => {
+            let make_deck = || ImportanceDeck::with_deck_type(rules.deck_type);
+            solve_with_playout(states, rules, make_deck, playout, config, progress)
+        }
+        None => {
+            let make_deck = || InfiniteDeck::with_deck_type(rules.deck_type);
+            solve_with_playout(states, rules, make_deck, playout, config, progress)
+        }
+    }
+}
+
+/// Simulate every valid action of `states` under `rules` until converged.
+/// `make_deck` builds the card source for each engine; `progress` selects how progress is reported.
+pub fn solve<D, F>(states: &[PlayerState], rules: &RuleSet, make_deck: F, progress: Progress) -> StateStats
+where
+    D: Deck + Send,
+    F: Fn() -> D + Sync,
+{
+    solve_with_playout(states, rules, make_deck, &BasicStrategy, &SolverConfig::default(), progress)
+}
+
+/// `solve`, finishing every hand after its first decision with `playout` and converging as `config` sets.
+/// With `config.threads` set the solve runs on a pool of that size (see `with_threads`).
+pub fn solve_with_playout<D, F, P>(
+    states: &[PlayerState],
+    rules: &RuleSet,
+    make_deck: F,
+    playout: &P,
+    config: &SolverConfig,
+    progress: Progress,
+) -> StateStats
+where
+    D: Deck + Send,
+    F: Fn() -> D + Sync,
+    P: PlayoutStrategy + Sync,
+{
+    with_threads(config.threads, || run_solve(states, rules, &make_deck, playout, config, progress))
+}
+
+/// Run `f` on a pool of `threads` workers, or on the current pool when unset or already that size.
+/// The only place a pool is built, so a run that installs its pool up front builds no other.
+pub fn with_threads<R: Send>(threads: Option<usize>, f: impl FnOnce() -> R + Send) -> R {
+    match threads {
+        Some(threads) if threads != rayon::current_num_threads() => rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("failed to build solver thread pool")
+            .install(f),
+        _ => f(),
+    }
+}
+
+/// The convergence loop of `solve_with_playout`, on the current pool
+fn run_solve<D, F, P>(
+    states: &[PlayerState],
+    rules: &RuleSet,
+    make_deck: F,
+    playout: &P,
+    config: &SolverConfig,
+    progress: Progress,
+) -> StateStats
+where
+    D: Deck + Send,
+    F: Fn() -> D + Sync,
+    P: PlayoutStrategy + Sync,
+{
+    // Initialize state statistics, continuing a checkpoint's where given
+    let mut state_stats = StateStats::new(states);
+    let mut first_iteration = 1;
+    if let Some(checkpoint) = &config.resume {
+        state_stats.restore(checkpoint);
+        first_iteration = checkpoint.iteration + 1;
+    }
+
+    // Generate initial tasks. Importance sampling tilts each state's draws toward its own decisive cards.
+    let card_probs = make_deck().card_probs().filter(|_| config.importance_sampling);
+    let mut pending_tasks: Vec<SimulationTask> = states
+        .iter()
+        .map(|&state| SimulationTask {
+            state,
+            state_idx: state_index(&state),
+            actions: Action::valid_actions(&state, rules).collect(),
+            // A split would deal the tilted card to a hand it wasn't chosen for, so pairs are dealt untilted
+            proposal: card_probs
+                .map(|probs| if state.is_pair { probs } else { exact::hit_proposal(rules, &state, &probs) }),
+        })
+        .collect();
+
+    let total_pairs: usize = pending_tasks.iter().map(|task| task.actions.len()).sum();
+    if !progress.is_quiet() {
+        info!("Total state-action pairs: {}", total_pairs);
+        info!("");
+    }
+
+    // Pairs a resumed checkpoint already converged are not simulated again
+    for task in &mut pending_tasks {
+        let table = &state_stats.stats[task.state_idx];
+        task.actions.retain(|action| table[action.index()].sem() >= config.target_sem);
+    }
+    pending_tasks.retain(|task| !task.actions.is_empty());
+    let mut converged_count = total_pairs - pending_tasks.iter().map(|task| task.actions.len()).sum::<usize>();
+
+    // One engine per worker thread, kept for the whole solve so each RNG stream continues
+    // across iterations (unless the config's seed restarts it per sub-batch)
+    let engines: Vec<Mutex<_>> = (0..rayon::current_num_threads())
+        .map(|_| {
+            let engine = BlackjackEngine::with_playout(ReplayDeck::new(make_deck()), rules.clone(), playout);
+            Mutex::new(if config.exact_stands {
+                engine.with_exact_shoe_stands()
+            } else {
+                engine.without_exact_stands()
+            })
+        })
+        .collect();
+
+    let reporter = ProgressReporter::new(progress, total_pairs);
+    let mut iterations = first_iteration - 1;
+
+    for iteration in first_iteration..=config.max_iterations {
+        if pending_tasks.is_empty() {
+            break;
+        }
+        iterations = iteration;
+        // Per-pair debug lines carry the iteration through this span
+        let _iteration = info_span!("iteration", n = iteration).entered();
+        reporter.update(iteration, converged_count, || house_edge(rules, &state_stats));
+
+        // Near the end only a few high-variance pairs remain, so each batch is split
+        // into sub-batches that keep every thread busy
+        let splits = sub_batch_count(pending_tasks.len());
+
+        // Run batch in parallel - collect results without locks on shared stats.
+        // Each worker thread takes only its own engine, so the mutex is never contended.
+        let results: Vec<SimulationResult> = pending_tasks
+            .par_iter()
+            .flat_map_iter(|task| (0..splits).map(move |i| (task, i)))
+            .map(|(task, i)| {
+                let worker = rayon::current_thread_index().unwrap_or(0) % engines.len();
+                let mut engine = engines[worker].lock().unwrap();
+                // A seeded solve draws each sub-batch from its own stream, whichever thread runs it
+                if let Some(seed) = config.seed {
+                    engine.deck_mut().seed(batch_seed(seed, iteration, task.state_idx, i));
+                }
+                if let Some(proposal) = &task.proposal {
+                    engine.deck_mut().tilt(proposal);
+                }
+                let batch_size = sub_batch_size(i, splits, config.batch_size);
+                SimulationResult {
+                    state_idx: task.state_idx,
+                    stats: engine.simulate_batch_common(&task.state, &task.actions, batch_size),
+                }
+            })
+            .collect();
+
+        // Merge results (single-threaded, but fast). The map phase has finished,
+        // so plain stats are updated in place without locking.
+        for result in results {
+            for (total, batch) in state_stats.stats[result.state_idx].iter_mut().zip(&result.stats) {
+                total.merge(batch);
+            }
+        }
+
+        // Drop converged actions, then tasks with nothing left to simulate
+        let mut new_pending = Vec::with_capacity(pending_tasks.len());
+        for mut task in pending_tasks {
+            let table = &state_stats.stats[task.state_idx];
+            let before = task.actions.len();
+            task.actions.retain(|action| table[action.index()].sem() >= config.target_sem);
+            converged_count += before - task.actions.len();
+            if !task.actions.is_empty() {
+                new_pending.push(task);
+            }
+        }
+        pending_tasks = new_pending;
+        reporter.pairs(|| {
+            state_stats
+                .iter()
+                .flat_map(|(state, table)| {
+                    Action::ALL.iter().filter(|action| table[action.index()].n > 0).map(move |&action| {
+                        let stats = &table[action.index()];
+                        (*state, action, stats, stats.sem() < config.target_sem)
+                    })
+                })
+                .collect()
+        });
+
+        if let Some(path) = &config.checkpoint {
+            if iteration % config.checkpoint_interval == 0 {
+                save_checkpoint(path, rules, iteration, &state_stats);
+            }
+        }
+    }
+
+    // The final statistics are kept too, for `merge` to pool finished runs
+    if let Some(path) = &config.checkpoint {
+        save_checkpoint(path, rules, iterations, &state_stats);
+    }
+
+    reporter.finish(iterations, converged_count, || house_edge(rules, &state_stats));
+    state_stats
+}
+
+/// Save the statistics after `iteration`; a failed save is reported but does not stop the solve
+fn save_checkpoint(path: &str, rules: &RuleSet, iteration: u32, state_stats: &StateStats) {
+    let checkpoint = Checkpoint {
+        rules: rules.to_string(),
+        iteration,
+        stats: state_stats.iter().map(|(state, table)| (*state, table.clone())).collect(),
+    };
+    if let Err(err) = checkpoint.save(path) {
+        warn!("cannot write checkpoint '{}': {}", path, err);
+    }
+}
+
+/// Sub-batches per pending task so that the iteration has at least `MIN_SUB_BATCHES`
+fn sub_batch_count(pending: usize) -> u32 {
+    if pending == 0 || pending >= MIN_SUB_BATCHES { 1 } else { MIN_SUB_BATCHES.div_ceil(pending) as u32 }
+}
+
+/// Hands in sub-batch `i` of `splits`, together covering one `batch_size`
+#[inline(always)]
+fn sub_batch_size(i: u32, splits: u32, batch_size: u32) -> u32 {
+    batch_size / splits + u32::from(i < batch_size % splits)
+}
+
+/// Seed of one sub-batch's card stream, mixed from the run seed with SplitMix64. The run seed is mixed
+/// on its own first, or seeds differing in their low bits would only swap sub-batches.
+pub(crate) fn batch_seed(seed: u64, iteration: u32, state_idx: usize, sub_batch: u32) -> u64 {
+    split_mix(split_mix(seed) ^ ((iteration as u64) << 40) ^ ((state_idx as u64) << 16) ^ sub_batch as u64)
+}
+
+/// SplitMix64 output of `z`
+#[inline(always)]
+fn split_mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Highest-EV sampled action of a state, or `None` if no action was sampled
+pub fn get_best_action(actions: &ActionTable) -> Option<(Action, f64)> {
+    Action::ALL
+        .iter()
+        .zip(actions)
+        .filter(|(_, stats)| stats.n > 0)
+        .max_by(|(_, a), (_, b)| a.ev().total_cmp(&b.ev()))
+        .map(|(&action, stats)| (action, stats.ev()))
+}
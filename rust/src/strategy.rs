@@ -0,0 +1,177 @@
+//! Solved best actions in a form downstream code can query directly.
+//! Built once from `StateStats`; lookups are a dense index, no EV comparisons.
+
+use crate::deck::{PlayerState, NUM_STATES};
+use crate::engine::Action;
+use crate::exact::action_evs;
+use crate::rules::RuleSet;
+use crate::solver::{get_best_action, StateStats};
+
+/// Dealer upcards of the CSV columns, in order
+const CSV_UPCARDS: [u8; 10] = [2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+/// Best action and its EV for every solved state
+#[derive(Debug, Clone)]
+pub struct StrategyTable {
+    entries: Vec<Option<(Action, f64)>>,
+}
+
+impl StrategyTable {
+    /// Best sampled action of every state in `stats`; states with no samples are left out
+    pub fn from_stats(stats: &StateStats) -> Self {
+        let mut entries = vec![None; NUM_STATES];
+        for (state, actions) in stats.iter() {
+            if let Some(idx) = state.index() {
+                entries[idx] = get_best_action(actions);
+            }
+        }
+        StrategyTable { entries }
+    }
+
+    /// Chart of the exact infinite-deck EVs (`exact::action_evs`) under `rules`, computed in
+    /// milliseconds instead of a Monte Carlo solve
+    pub fn exact(rules: &RuleSet) -> Self {
+        let card_probs = rules.deck_type.probabilities();
+        let entries = (0..NUM_STATES)
+            .map(|idx| {
+                let state = PlayerState::from_index(idx)?;
+                let evs = action_evs(rules, &state, &card_probs);
+                Action::ALL
+                    .into_iter()
+                    .filter_map(|action| Some((action, evs[action.index()]?)))
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+            })
+            .collect();
+        StrategyTable { entries }
+    }
+
+    /// Best action and EV for a hand described the way a player reads it.
+    /// `dealer` is 2-10, with 1 or 11 for an ace; soft 12 and hard 4 can only be
+    /// A,A and 2,2, so they are looked up as pairs. `None` if the state was not solved.
+    pub fn lookup(&self, total: u8, dealer: u8, is_soft: bool, is_pair: bool) -> Option<(Action, f64)> {
+        let dealer = if dealer == 1 { 11 } else { dealer };
+        let is_pair = is_pair || (is_soft && total == 12) || (!is_soft && total == 4);
+        self.get(&PlayerState::new(total, dealer, is_soft, is_pair))
+    }
+
+    /// Best action and EV of a state, if it was solved
+    #[inline(always)]
+    pub fn get(&self, state: &PlayerState) -> Option<(Action, f64)> {
+        self.entries[state.index()?]
+    }
+
+    /// Every solved state with its best action and EV, in index order
+    pub fn iter(&self) -> impl Iterator<Item = (PlayerState, Action, f64)> + '_ {
+        self.entries.iter().enumerate().filter_map(|(idx, entry)| {
+            let (action, ev) = (*entry)?;
+            Some((PlayerState::from_index(idx)?, action, ev))
+        })
+    }
+
+    /// Chart read from CSV, e.g. a simplified one to compare against the solved chart.
+    /// The header is `hand,2,3,4,5,6,7,8,9,10,A`; each row is a hand, `H16` (hard), `S18` (soft),
+    /// `P8`, `P10`, `PA` (pairs) or `P10J` (unlike tens), then one action per upcard as a symbol or name ("H", "Stand").
+    /// Empty cells and missing rows leave the state unset. EVs are unknown and stored as NaN.
+    pub fn from_csv(text: &str) -> Result<Self, String> {
+        let mut entries = vec![None; NUM_STATES];
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+        let header = lines.next().ok_or("empty strategy CSV")?;
+        if !header.starts_with("hand") { return Err(format!("expected a 'hand,2,...,A' header, got '{}'", header)); }
+
+        for line in lines {
+            let mut cells = line.split(',').map(str::trim);
+            let label = cells.next().unwrap_or_default();
+            let hand = parse_hand_label(label)?;
+            for (&upcard, cell) in CSV_UPCARDS.iter().zip(cells) {
+                if cell.is_empty() { continue; }
+                let state = PlayerState { dealer_upcard: upcard, ..hand };
+                let action: Action = cell.parse()?;
+                if action == Action::Split && !state.is_pair { return Err(format!("cannot split {}", state)); }
+                let idx = state.index().ok_or_else(|| format!("'{}' is not a solved state", state))?;
+                entries[idx] = Some((action, f64::NAN));
+            }
+        }
+        Ok(StrategyTable { entries })
+    }
+
+    /// The chart in the CSV layout `from_csv` reads
+    pub fn to_csv(&self) -> String {
+        let mut output = String::from("hand,2,3,4,5,6,7,8,9,10,A\n");
+        let mut row = None;
+        for (idx, entry) in self.entries.iter().enumerate() {
+            let Some(state) = PlayerState::from_index(idx) else { continue };
+            if row != Some(idx / CSV_UPCARDS.len()) {
+                if row.is_some() { output.push('\n'); }
+                row = Some(idx / CSV_UPCARDS.len());
+                output.push_str(&hand_label(&state));
+            }
+            output.push(',');
+            if let Some((action, _)) = entry { output.push_str(action.symbol()); }
+        }
+        output.push('\n');
+        output
+    }
+}
+
+/// CSV row label of a state's hand: `H16`, `S18`, `P8`, `PA` or `P10J`
+fn hand_label(state: &PlayerState) -> String {
+    if state.unlike_tens { return "P10J".to_string(); }
+    match (state.is_pair, state.is_soft) {
+        (true, true) => "PA".to_string(),
+        (true, false) => format!("P{}", state.total / 2),
+        (false, true) => format!("S{}", state.total),
+        (false, false) => format!("H{}", state.total),
+    }
+}
+
+/// Hand of a CSV row label, as a state against a dealer 2
+fn parse_hand_label(label: &str) -> Result<PlayerState, String> {
+    let invalid = || format!("invalid hand '{}' (expected e.g. H16, S18, P8, PA or P10J)", label);
+    let (kind, value) = label.split_at_checked(1).ok_or_else(invalid)?;
+    let number = || value.parse::<u8>().map_err(|_| invalid());
+    let (total, is_soft, is_pair) = match kind.to_ascii_uppercase().as_str() {
+        "H" => (number()?, false, false),
+        "S" => (number()?, true, false),
+        "P" if value.eq_ignore_ascii_case("A") => (12, true, true),
+        "P" if value.eq_ignore_ascii_case("10J") => return Ok(PlayerState::new_unlike_tens(2)),
+        "P" => (number()?.checked_mul(2).ok_or_else(invalid)?, false, true),
+        _ => return Err(invalid()),
+    };
+    Ok(PlayerState::new(total, 2, is_soft, is_pair))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::Progress;
+    use crate::solver::solve_with_rules;
+
+    #[test]
+    fn hard_20_vs_6_stands() {
+        let state = PlayerState::new(20, 6, false, false);
+        let solved = solve_with_rules(&[state], &RuleSet::default(), Progress::Quiet);
+        let (action, ev) = StrategyTable::from_stats(&solved).lookup(20, 6, false, false).expect("state solved");
+        assert_eq!(action, Action::Stand);
+        assert!(ev > 0.6, "hard 20 vs 6 stands for {}", ev);
+    }
+
+    #[test]
+    fn the_csv_chart_round_trips_through_action_symbols() {
+        let exact = StrategyTable::exact(&RuleSet::default());
+        let csv = exact.to_csv();
+        assert!(csv.lines().nth(1).is_some_and(|row| row.split(',').skip(1).all(|cell| cell.len() == 1)));
+        let read = StrategyTable::from_csv(&csv).unwrap();
+        let actions = |table: &StrategyTable| table.iter().map(|(state, action, _)| (state, action)).collect();
+        let (read, exact): (Vec<_>, Vec<_>) = (actions(&read), actions(&exact));
+        assert_eq!(read, exact);
+    }
+
+    #[test]
+    fn lookup_reads_aces_and_the_forced_pairs() {
+        let table = StrategyTable::exact(&RuleSet::default());
+        assert_eq!(table.lookup(16, 1, false, false), table.get(&PlayerState::new(16, 11, false, false)));
+        assert_eq!(table.lookup(12, 6, true, false), table.get(&PlayerState::new(12, 6, true, true)));
+        assert_eq!(table.lookup(4, 6, false, false), table.get(&PlayerState::new(4, 6, false, true)));
+        assert!(table.lookup(12, 6, true, false).is_some());
+    }
+}
@@ -0,0 +1,65 @@
+//! Cross-check of the simulated EVs against the exact infinite-deck solution.
+//! Differences beyond a few standard errors point at a bias (the playout policy, an unmodeled rule), not noise.
+
+use crate::deck::PlayerState;
+use crate::engine::Action;
+use crate::exact::action_evs;
+use crate::rules::RuleSet;
+use crate::solver::StateStats;
+
+/// Standard errors a difference has to exceed to be flagged
+pub const FLAG_SEMS: f64 = 4.0;
+
+/// Floor on the flagged difference, so EVs the solver settles exactly (Stand under exact stands) match
+/// despite rounding
+const EXACT_TOLERANCE: f64 = 1e-9;
+
+/// One state-action's simulated EV next to its exact one
+#[derive(Debug, Clone, Copy)]
+pub struct Discrepancy {
+    pub state: PlayerState,
+    pub action: Action,
+    pub simulated: f64,
+    pub exact: f64,
+    /// Standard error of `simulated`
+    pub sem: f64,
+}
+
+impl Discrepancy {
+    /// Simulated minus exact EV
+    pub fn difference(&self) -> f64 {
+        self.simulated - self.exact
+    }
+
+    /// Difference in standard errors; 0 when both agree exactly
+    pub fn z(&self) -> f64 {
+        if self.difference().abs() <= EXACT_TOLERANCE { 0.0 } else { self.difference() / self.sem }
+    }
+
+    /// Difference too large to be sampling noise (`FLAG_SEMS` standard errors)
+    pub fn is_flagged(&self) -> bool {
+        self.difference().abs() > FLAG_SEMS * self.sem + EXACT_TOLERANCE
+    }
+}
+
+/// Every sampled state-action of `solved` against `exact::action_evs`, largest difference first.
+/// The exact side draws from an infinite deck of the rules' deck type, so `solved` should be too. It plays
+/// on optimally after the first decision and splits once without Charlie, Spanish 21 bonuses or double
+/// exposure, so the default playout's Hit and Double EVs and those rules show up here as well as bugs.
+pub fn compare_exact(rules: &RuleSet, solved: &StateStats) -> Vec<Discrepancy> {
+    let card_probs = rules.deck_type.probabilities();
+    let mut discrepancies: Vec<Discrepancy> = solved
+        .iter()
+        .flat_map(|(state, actions)| {
+            let exact = action_evs(rules, state, &card_probs);
+            Action::ALL.into_iter().filter_map(move |action| {
+                let stats = &actions[action.index()];
+                if stats.n == 0 { return None; }
+                let exact = exact[action.index()]?;
+                Some(Discrepancy { state: *state, action, simulated: stats.ev(), exact, sem: stats.sem() })
+            })
+        })
+        .collect();
+    discrepancies.sort_by(|a, b| b.difference().abs().total_cmp(&a.difference().abs()));
+    discrepancies
+}
@@ -0,0 +1,280 @@
+//! End-to-end use of the public API: solve a chart, read best actions from it and simulate single states.
+
+use blackjack_solver::analysis::house_edge;
+use blackjack_solver::checkpoint::Checkpoint;
+use blackjack_solver::engine::generate_all_states;
+use blackjack_solver::report::format_query;
+use blackjack_solver::progress::Progress;
+use blackjack_solver::InfiniteDeck;
+use blackjack_solver::{
+    get_best_action, simulate, solve_with_config, Action, PlayerState, RuleSet, SolverConfig, StateStats,
+};
+
+/// A quick, seeded infinite-deck solve: loose enough for a test, tight enough for clear decisions
+fn quick_solve(rules: &RuleSet) -> StateStats {
+    let config = SolverConfig { target_sem: 0.02, seed: Some(7), ..SolverConfig::default() };
+    solve_with_config(rules, &config)
+}
+
+/// `states` alone solved under `config` on an infinite deck, without progress output
+fn solve_states(states: &[PlayerState], config: &SolverConfig) -> StateStats {
+    solve_states_under(&RuleSet { num_decks: None, ..RuleSet::default() }, states, config)
+}
+
+/// `solve_states` under `rules`
+fn solve_states_under(rules: &RuleSet, states: &[PlayerState], config: &SolverConfig) -> StateStats {
+    blackjack_solver::solver::solve_with_rules_and_playout(
+        states,
+        rules,
+        &blackjack_solver::BasicStrategy,
+        config,
+        blackjack_solver::progress::Progress::Quiet,
+    )
+}
+
+/// `solve_states_under` checkpointing to a temporary file, returning the final checkpoint
+fn solve_to_checkpoint(rules: &RuleSet, states: &[PlayerState], config: &SolverConfig, name: &str) -> Checkpoint {
+    let path = std::env::temp_dir().join(format!("blackjack_solver_{}_{}.ckpt", name, std::process::id()));
+    let path = path.to_str().unwrap().to_string();
+    solve_states_under(rules, states, &SolverConfig { checkpoint: Some(path.clone()), ..config.clone() });
+    let checkpoint = Checkpoint::load(&path).expect("checkpoint saved");
+    std::fs::remove_file(&path).ok();
+    checkpoint
+}
+
+#[test]
+fn solve_covers_every_state_with_clear_best_actions() {
+    let rules = RuleSet { num_decks: None, ..RuleSet::default() };
+    let solved = quick_solve(&rules);
+    assert_eq!(solved.len(), generate_all_states().len());
+
+    let best = |total, dealer, is_soft, is_pair| {
+        let actions = solved.get(&PlayerState::new(total, dealer, is_soft, is_pair)).expect("state solved");
+        get_best_action(actions).expect("actions simulated").0
+    };
+    assert_eq!(best(20, 6, false, false), Action::Stand);
+    assert_eq!(best(11, 6, false, false), Action::Double);
+    assert_eq!(best(16, 6, false, true), Action::Split);
+    assert_eq!(best(12, 6, true, true), Action::Split);
+    assert_eq!(best(13, 10, false, false), Action::Hit);
+
+    // An ENHC S17 DAS game with 3:2 blackjacks sits around a half percent house edge
+    let edge = house_edge(&rules, &solved);
+    assert!((0.002..0.009).contains(&edge), "house edge {}", edge);
+}
+
+#[test]
+fn nearby_seeds_deal_different_cards() {
+    let state = PlayerState::new(16, 10, false, false);
+    let hit_ev = |seed| {
+        let config = SolverConfig { max_iterations: 1, seed: Some(seed), ..SolverConfig::default() };
+        solve_states(&[state], &config).get(&state).expect("state solved")[Action::Hit.index()].ev()
+    };
+    assert_eq!(hit_ev(1), hit_ev(1));
+    // Seeds 1 and 4 differ only in bits that also number the sub-batches
+    assert_ne!(hit_ev(1), hit_ev(4));
+}
+
+#[test]
+fn simulate_plays_one_state_and_action() {
+    let rules = RuleSet::default();
+    let twenty = simulate(&rules, &PlayerState::new(20, 6, false, false), Action::Stand, 200_000);
+    assert_eq!(twenty.n, 200_000);
+    assert!(twenty.ev() > 0.6, "hard 20 vs 6 stands for {}", twenty.ev());
+
+    let surrender = simulate(&rules, &PlayerState::new(16, 10, false, false), Action::Surrender, 1_000);
+    assert!(surrender.ev() < -0.5 && surrender.ev() > -0.6, "surrender costs {}", surrender.ev());
+}
+
+#[test]
+fn a_capped_solve_reports_its_unconverged_pairs() {
+    let state = PlayerState::new(16, 10, false, false);
+    let config = SolverConfig { max_iterations: 1, seed: Some(7), threads: Some(2), ..SolverConfig::default() };
+    let solved = solve_states(&[state], &config);
+    let (converged, total) = solved.converged(config.target_sem);
+    assert_eq!(total, 4, "hit, stand, double and surrender sampled");
+    assert!(converged < total, "one batch cannot reach the default target SEM");
+}
+
+#[test]
+fn a_seeded_solve_is_the_same_on_any_thread_count() {
+    let rules = RuleSet { num_decks: Some(6), ..RuleSet::default() };
+    let states = [PlayerState::new(16, 10, false, false), PlayerState::new(12, 2, false, false)];
+    let solve_on = |threads| {
+        let config =
+            SolverConfig { target_sem: 0.02, seed: Some(11), threads: Some(threads), ..SolverConfig::default() };
+        let solved = blackjack_solver::solver::solve_with_rules_and_playout(
+            &states,
+            &rules,
+            &blackjack_solver::BasicStrategy,
+            &config,
+            blackjack_solver::progress::Progress::Quiet,
+        );
+        solved.iter().map(|(state, table)| (*state, table.clone())).collect::<Vec<_>>()
+    };
+    assert_eq!(solve_on(1), solve_on(3));
+}
+
+#[test]
+fn a_query_names_the_best_action_and_its_ev() {
+    let state = PlayerState::new(16, 10, false, false);
+    let solve = |max_iterations| {
+        let config = SolverConfig { max_iterations, seed: Some(7), ..SolverConfig::default() };
+        solve_states(&[state], &config)
+    };
+
+    let solved = solve(2);
+    let actions = solved.get(&state).expect("queried state is solved");
+    let (best, ev) = get_best_action(actions).expect("actions simulated");
+    let report = format_query(&state, actions);
+    let best_line = report.lines().find(|line| line.trim_start().starts_with("Best:")).expect("best line");
+    assert!(best_line.contains(&best.to_string()) && best_line.contains(&format!("{:+.4}", ev)), "{}", report);
+
+    // Without a single iteration nothing is sampled, and the query says so instead of ranking
+    let unsolved = solve(0);
+    let report = format_query(&state, unsolved.get(&state).expect("queried state is generated"));
+    assert!(report.contains("Not sampled:") && !report.contains("Best:"), "{}", report);
+}
+
+#[test]
+fn a_resumed_solve_matches_the_uninterrupted_one() {
+    let states = [PlayerState::new(16, 10, false, false), PlayerState::new(16, 10, false, true)];
+    // A target no batch reaches keeps every pair sampled to the last iteration
+    let config = SolverConfig { target_sem: 1e-6, batch_size: 2_000, max_iterations: 4, ..SolverConfig::default() };
+    let config = SolverConfig { seed: Some(9), ..config };
+    let uninterrupted = solve_states(&states, &config);
+
+    let rules = RuleSet { num_decks: None, ..RuleSet::default() };
+    let stopped = SolverConfig { max_iterations: 2, ..config.clone() };
+    let checkpoint = solve_to_checkpoint(&rules, &states, &stopped, "resume");
+    assert_eq!(checkpoint.iteration, 2);
+
+    let resumed = solve_states(&states, &SolverConfig { resume: Some(checkpoint), ..config });
+    for state in &states {
+        assert_eq!(resumed.get(state), uninterrupted.get(state), "{}", state);
+    }
+}
+
+#[test]
+fn merged_half_runs_match_one_full_run() {
+    // Without surrender or exact stands no pair has a zero SEM, so every pair is sampled each iteration
+    let rules = RuleSet { num_decks: None, late_surrender: false, ..RuleSet::default() };
+    let states = [PlayerState::new(16, 10, false, false), PlayerState::new(16, 10, false, true)];
+    let config = SolverConfig { target_sem: 1e-6, batch_size: 2_000, max_iterations: 4, ..SolverConfig::default() };
+    let config = SolverConfig { seed: Some(9), ..config.plain_sampled() };
+    let full = solve_states_under(&rules, &states, &config);
+
+    // The halves deal iterations 1-2 and 3-4; the second starts from empty statistics after iteration 2
+    let first_half = SolverConfig { max_iterations: 2, ..config.clone() };
+    let mut merged = solve_to_checkpoint(&rules, &states, &first_half, "first_half");
+    let skip = Checkpoint { stats: Vec::new(), ..merged.clone() };
+    let second = solve_to_checkpoint(&rules, &states, &SolverConfig { resume: Some(skip), ..config }, "second_half");
+    merged.merge(&second).unwrap();
+    let merged = StateStats::from_checkpoint(&merged);
+
+    for state in &states {
+        for (pooled, stats) in merged.get(state).unwrap().iter().zip(full.get(state).unwrap()) {
+            assert_eq!(pooled.n, stats.n, "{}", state);
+            if stats.n > 0 {
+                assert!((pooled.ev() - stats.ev()).abs() < 1e-12 && (pooled.sem() - stats.sem()).abs() < 1e-12);
+            }
+        }
+    }
+}
+
+#[test]
+fn exact_stands_agree_with_plain_sampling_at_a_lower_sem() {
+    let rules = RuleSet { num_decks: None, late_surrender: false, ..RuleSet::default() };
+    let states = [
+        PlayerState::new(16, 10, false, false),
+        PlayerState::new(12, 6, false, false),
+        PlayerState::new(11, 6, false, false),
+        PlayerState::new(18, 9, true, false),
+    ];
+    // No pair reaches the target, so both solves deal every hit and double the same number of hands
+    let config = SolverConfig { target_sem: 1e-9, batch_size: 20_000, max_iterations: 5, ..SolverConfig::default() };
+    let config = SolverConfig { seed: Some(11), ..config };
+    let exact = solve_states_under(&rules, &states, &config);
+    let plain = solve_states_under(&rules, &states, &config.plain_sampled());
+
+    for state in &states {
+        for action in [Action::Hit, Action::Double] {
+            let exact = &exact.get(state).unwrap()[action.index()];
+            let plain = &plain.get(state).unwrap()[action.index()];
+            assert_eq!(exact.n, plain.n, "{} {}", state, action);
+            let sem = exact.sem().hypot(plain.sem());
+            let gap = (exact.ev() - plain.ev()).abs();
+            assert!(gap < 4.0 * sem, "{} {}: {} vs {}", state, action, exact.ev(), plain.ev());
+            // Stood hands settle without noise, which takes a tenth or more off the SEM
+            assert!(exact.sem() < 0.9 * plain.sem(), "{} {}: SEM {} vs {}", state, action, exact.sem(), plain.sem());
+        }
+    }
+}
+
+#[test]
+fn antithetic_pairs_agree_with_independent_hands_at_a_lower_sem() {
+    use blackjack_solver::deck::AntitheticDeck;
+    use blackjack_solver::solver::solve_with_playout;
+    use blackjack_solver::BasicStrategy;
+
+    let rules = RuleSet { num_decks: None, late_surrender: false, ..RuleSet::default() };
+    let states = [
+        PlayerState::new(16, 10, false, false),
+        PlayerState::new(12, 6, false, false),
+        PlayerState::new(11, 6, false, false),
+        PlayerState::new(18, 9, true, false),
+    ];
+    let config = SolverConfig { target_sem: 1e-9, batch_size: 20_000, max_iterations: 5, ..SolverConfig::default() };
+    let config = SolverConfig { seed: Some(13), ..config.plain_sampled() };
+    let antithetic = solve_with_playout(&states, &rules, AntitheticDeck::new, &BasicStrategy, &config, Progress::Quiet);
+    let plain = solve_with_playout(&states, &rules, InfiniteDeck::new, &BasicStrategy, &config, Progress::Quiet);
+
+    for state in &states {
+        for action in [Action::Hit, Action::Stand, Action::Double] {
+            let mirrored = &antithetic.get(state).unwrap()[action.index()];
+            let independent = &plain.get(state).unwrap()[action.index()];
+            // Both deal the same hands, the antithetic solve recording each pair as one sample
+            assert_eq!(2 * mirrored.n, independent.n, "{} {}", state, action);
+            let gap = (mirrored.ev() - independent.ev()).abs();
+            let sem = mirrored.sem().hypot(independent.sem());
+            assert!(gap < 4.0 * sem, "{} {}: {} vs {}", state, action, mirrored.ev(), independent.ev());
+        }
+        // A stood hand's only draws are the dealer's, which the mirror turns from stiffs into pat hands
+        let stand = |solved: &StateStats| solved.get(state).unwrap()[Action::Stand.index()].sem();
+        let (mirrored, independent) = (stand(&antithetic), stand(&plain));
+        assert!(mirrored < 0.95 * independent, "{}: SEM {} vs {}", state, mirrored, independent);
+    }
+}
+
+#[test]
+fn a_tighter_target_sem_deals_more_hands_per_pair() {
+    let rules = RuleSet { num_decks: None, late_surrender: false, ..RuleSet::default() };
+    let states = [PlayerState::new(16, 10, false, false), PlayerState::new(11, 6, false, false)];
+    let config = SolverConfig { batch_size: 500, seed: Some(17), ..SolverConfig::default().plain_sampled() };
+    let loose = solve_states_under(&rules, &states, &SolverConfig { target_sem: 0.02, ..config.clone() });
+    let tight = solve_states_under(&rules, &states, &SolverConfig { target_sem: 0.01, ..config });
+
+    for state in &states {
+        let pairs = loose.get(state).unwrap().iter().zip(tight.get(state).unwrap());
+        for (action, (loose, tight)) in Action::ALL.iter().zip(pairs) {
+            if loose.n == 0 { continue; }
+            // Halving the SEM takes about four times the hands
+            assert!(loose.sem() < 0.02 && tight.sem() < 0.01, "{} {}", state, action);
+            assert!(tight.n >= 3 * loose.n, "{} {}: {} hands vs {}", state, action, tight.n, loose.n);
+        }
+    }
+}
+
+#[test]
+fn fewer_decks_lower_the_house_edge() {
+    let edge = |num_decks| {
+        let rules = RuleSet { num_decks, ..RuleSet::default() };
+        house_edge(&rules, &quick_solve(&rules))
+    };
+    // A single deck deals more naturals and rewards doubles, taking the default game from about half a
+    // percent to about even; an 8-deck shoe sits close to the infinite deck
+    let (one, eight, infinite) = (edge(Some(1)), edge(Some(8)), edge(None));
+    assert!(eight - one > 0.004, "1 deck {} vs 8 decks {}", one, eight);
+    assert!(one.abs() < 0.003, "1 deck {}", one);
+    assert!((eight - infinite).abs() < 0.002, "8 decks {} vs infinite {}", eight, infinite);
+}
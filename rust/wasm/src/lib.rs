@@ -0,0 +1,109 @@
+//! WebAssembly bindings: best-action lookup for a web page.
+//! Each rule combination's chart comes from the exact infinite-deck EVs in milliseconds, the first time
+//! it is asked for, and is cached for the page's lifetime.
+
+use blackjack_solver::{solve_with_config, RuleSet, SolverConfig, StrategyTable};
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+
+thread_local! {
+    /// Solved charts keyed by `(h17, das)`
+    static TABLES: RefCell<Vec<((bool, bool), StrategyTable)>> = const { RefCell::new(Vec::new()) };
+    /// Chart of the last `solve`, answering `best_action`
+    static LAST: RefCell<Option<StrategyTable>> = const { RefCell::new(None) };
+}
+
+/// Run `f` on the exact chart for the default rules with H17 and DAS set, solving it on first use
+fn with_table<T>(h17: bool, das: bool, f: impl FnOnce(&StrategyTable) -> T) -> T {
+    TABLES.with(|tables| {
+        let mut tables = tables.borrow_mut();
+        let idx = match tables.iter().position(|(key, _)| *key == (h17, das)) {
+            Some(idx) => idx,
+            None => {
+                let rules = RuleSet { hit_soft_17: h17, double_after_split: das, ..RuleSet::default() };
+                tables.push(((h17, das), StrategyTable::exact(&rules)));
+                tables.len() - 1
+            }
+        };
+        f(&tables[idx].1)
+    })
+}
+
+/// Solve the chart for these rules now, so the first lookup answers immediately
+#[wasm_bindgen]
+pub fn wasm_prepare(h17: bool, das: bool) {
+    with_table(h17, das, |_| ());
+}
+
+/// Best action ("Hit", "Stand", "Double", "Split" or "Surrender") for a hand under the default
+/// rules with H17 and DAS set. `dealer` is 2-10, with 1 or 11 for an ace; empty for an unknown state.
+#[wasm_bindgen]
+pub fn wasm_best_action(total: u8, dealer: u8, is_soft: bool, is_pair: bool, h17: bool, das: bool) -> String {
+    with_table(h17, das, |table| {
+        table.lookup(total, dealer, is_soft, is_pair).map(|(action, _)| action.to_string()).unwrap_or_default()
+    })
+}
+
+/// Solve the chart for `rules_json`, a `RuleSet` as JSON (missing keys keep the defaults, `{}` is the
+/// default 8-deck table). With an empty `config_json` the chart comes from the exact infinite-deck EVs in
+/// milliseconds; a `SolverConfig` as JSON runs the Monte Carlo solve with those settings instead,
+/// which can take minutes in a browser. Returns `{"rules", "method", "states": [{"state", "total",
+/// "dealer", "soft", "pair", "action", "ev"}]}`. Rules that don't parse or fail `RuleSet::validate` throw
+/// an error instead. The chart also answers `best_action`.
+#[wasm_bindgen]
+pub fn solve(rules_json: &str, config_json: &str) -> Result<String, JsValue> {
+    let rules: RuleSet = serde_json::from_str(rules_json).map_err(|err| error(format!("invalid rules: {}", err)))?;
+    rules.validate().map_err(|err| error(format!("invalid rules: {}", err)))?;
+    let (method, table) = if config_json.trim().is_empty() {
+        ("exact", StrategyTable::exact(&rules))
+    } else {
+        let config: SolverConfig =
+            serde_json::from_str(config_json).map_err(|err| error(format!("invalid solver config: {}", err)))?;
+        ("monte_carlo", StrategyTable::from_stats(&solve_with_config(&rules, &config)))
+    };
+
+    let states: Vec<Value> = table
+        .iter()
+        .map(|(state, action, ev)| {
+            json!({
+                "state": state.to_string(),
+                "total": state.total,
+                "dealer": state.dealer_upcard,
+                "soft": state.is_soft,
+                "pair": state.is_pair,
+                "action": action.to_string(),
+                "ev": ev,
+            })
+        })
+        .collect();
+    LAST.with(|last| *last.borrow_mut() = Some(table));
+    Ok(json!({ "rules": rules.to_string(), "method": method, "states": states }).to_string())
+}
+
+/// Best action for `state_json`, `{"total", "dealer", "soft", "pair"}` (`dealer` 2-10, with 1 or 11
+/// for an ace; `soft` and `pair` default to false), from the chart of the last `solve`, or the exact
+/// chart of the default rules before any. Returns `{"action", "ev"}`, or throws an error.
+#[wasm_bindgen]
+pub fn best_action(state_json: &str) -> Result<String, JsValue> {
+    let state: Value = serde_json::from_str(state_json).map_err(|err| error(format!("invalid state: {}", err)))?;
+    let number = |key: &str| state[key].as_u64().and_then(|n| u8::try_from(n).ok());
+    let flag = |key: &str| state[key].as_bool().unwrap_or(false);
+    let (Some(total), Some(dealer)) = (number("total"), number("dealer")) else {
+        return Err(error("state needs a numeric 'total' and 'dealer'".to_string()));
+    };
+
+    LAST.with(|last| {
+        let mut last = last.borrow_mut();
+        let table = last.get_or_insert_with(|| StrategyTable::exact(&RuleSet::default()));
+        match table.lookup(total, dealer, flag("soft"), flag("pair")) {
+            Some((action, ev)) => Ok(json!({ "action": action.to_string(), "ev": ev }).to_string()),
+            None => Err(error(format!("no chart entry for total {} against {}", total, dealer))),
+        }
+    })
+}
+
+/// Error `message` as a JS string, thrown to the caller
+fn error(message: String) -> JsValue {
+    JsValue::from_str(&message)
+}
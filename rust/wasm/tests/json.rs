@@ -0,0 +1,65 @@
+//! Every serde type of the solver survives the JSON the bindings read and write. Plain `cargo test`:
+//! nothing here touches JS.
+
+use blackjack_solver::engine::SplitHandCounts;
+use blackjack_solver::{
+    Action, ActionStats, DeckType, DoubleOn, Outcome, OutcomeCounts, PlayerState, RuleSet, SolverConfig,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Debug;
+
+fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: &T) {
+    let json = serde_json::to_string(value).unwrap();
+    assert_eq!(&serde_json::from_str::<T>(&json).unwrap(), value, "{}", json);
+}
+
+#[test]
+fn rules_and_settings_round_trip() {
+    round_trip(&RuleSet::default());
+    round_trip(&RuleSet {
+        num_decks: None,
+        deck_type: DeckType::Spanish,
+        hit_soft_17: true,
+        double_on: DoubleOn::NineToEleven,
+        charlie: Some(5),
+        max_splits: 3,
+        surrender_vs: Some(vec![9, 10, 11]),
+        double_fraction: 0.5,
+        ..RuleSet::default()
+    });
+    round_trip(&SolverConfig::default());
+    let checkpoint = Some("run.ckpt".to_string());
+    round_trip(&SolverConfig { seed: Some(42), threads: Some(4), checkpoint, ..SolverConfig::default() });
+}
+
+#[test]
+fn states_actions_and_statistics_round_trip() {
+    round_trip(&PlayerState::new(18, 11, true, false));
+    for action in Action::ALL {
+        round_trip(&action);
+    }
+
+    let mut stats = ActionStats::new();
+    for result in [1.0, -1.0, 1.5, 0.0, -2.0] {
+        stats.update(result);
+    }
+    round_trip(&stats);
+
+    let mut outcomes = OutcomeCounts::default();
+    outcomes.record(Outcome::Win);
+    outcomes.record(Outcome::Blackjack);
+    round_trip(&outcomes);
+
+    let mut splits = SplitHandCounts::default();
+    splits.record(2);
+    splits.record(4);
+    round_trip(&splits);
+}
+
+#[test]
+fn rules_failing_validation_are_rejected() {
+    assert!(serde_json::from_str::<RuleSet>(r#"{"max_splits": 0}"#).is_err());
+    assert!(serde_json::from_str::<RuleSet>(r#"{"charlie": 2}"#).is_err());
+    assert_eq!(serde_json::from_str::<RuleSet>("{}").unwrap(), RuleSet::default());
+}
@@ -0,0 +1,44 @@
+//! The bindings as a page calls them, run in Node by `wasm-pack test --node rust/wasm`.
+//! `JsValue` needs a JS engine, so these tests only build for wasm32.
+
+#![cfg(target_arch = "wasm32")]
+
+use blackjack_solver_wasm::{best_action, solve, wasm_best_action};
+use serde_json::Value;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn exact_chart_stands_on_hard_20_vs_6() {
+    let chart: Value = serde_json::from_str(&solve("{}", "").unwrap()).unwrap();
+    assert_eq!(chart["method"], "exact");
+    let cell = chart["states"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|cell| cell["total"] == 20 && cell["dealer"] == 6 && cell["soft"] == false && cell["pair"] == false)
+        .expect("hard 20 vs 6 in the chart");
+    assert_eq!(cell["action"], "Stand");
+}
+
+#[wasm_bindgen_test]
+fn best_action_answers_from_the_last_chart() {
+    solve("{}", "").unwrap();
+    let answer: Value = serde_json::from_str(&best_action(r#"{"total": 16, "dealer": 10}"#).unwrap()).unwrap();
+    assert_eq!(answer["action"], "Surrender");
+    assert!(best_action(r#"{"total": 16}"#).is_err());
+}
+
+#[wasm_bindgen_test]
+fn rules_out_of_range_are_an_error() {
+    for rules in [r#"{"max_splits": 0}"#, r#"{"dealer_stands_on": 30}"#, r#"{"charlie": 2}"#, "{not json"] {
+        let err = solve(rules, "").expect_err(rules);
+        assert!(err.as_string().unwrap().starts_with("invalid rules"), "{}", rules);
+    }
+}
+
+#[wasm_bindgen_test]
+fn lookup_surrenders_hard_16_vs_10() {
+    assert_eq!(wasm_best_action(16, 10, false, false, false, true), "Surrender");
+    assert_eq!(wasm_best_action(20, 6, false, false, true, true), "Stand");
+    assert_eq!(wasm_best_action(3, 10, false, false, false, true), "");
+}